@@ -7,7 +7,10 @@
 CREATE TABLE users (
     id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
     username VARCHAR(64) NOT NULL UNIQUE,
-    email VARCHAR(128) NOT NULL
+    email VARCHAR(128) NOT NULL,
+    role VARCHAR(16) NOT NULL DEFAULT 'customer' CHECK (role IN ('customer', 'staff', 'admin')),
+    -- set once the user proves they own the address; cleared when the email changes
+    email_verified_at TIMESTAMPTZ
 );
 
 -- Separate index for email lookup
@@ -28,9 +31,14 @@ CREATE TABLE user_auth (
 -- ------------------------------------------------
 CREATE TABLE categories (
     id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
-    name VARCHAR(64) NOT NULL UNIQUE
+    name VARCHAR(64) NOT NULL UNIQUE,
+    -- optional parent category; a category cannot be deleted while it has children
+    parent_id INT REFERENCES categories(id) ON DELETE RESTRICT
 );
 
+-- Separate index for child lookup
+CREATE INDEX idx_categories_parent ON categories(parent_id);
+
 -- ------------------------------------------------
 -- 4) products table
 -- ------------------------------------------------
@@ -46,8 +54,347 @@ create table products (
         and discount <= 100
     ),
     category_id int not null,
+    stock_quantity int not null default 0 check (stock_quantity >= 0),
+    reserved_quantity int not null default 0 check (
+        reserved_quantity >= 0
+        and reserved_quantity <= stock_quantity
+    ),
+    low_stock_threshold int not null default 5 check (low_stock_threshold >= 0),
     foreign key (category_id) references categories(id) on delete cascade
 );
 
 -- Separate index for category lookup
-CREATE INDEX idx_products_categories ON products(category_id);
\ No newline at end of file
+CREATE INDEX idx_products_categories ON products(category_id);
+
+-- ------------------------------------------------
+-- 5) cart_items table
+-- ------------------------------------------------
+CREATE TABLE cart_items (
+    user_id INT NOT NULL,
+    product_id INT NOT NULL,
+    quantity INT NOT NULL CHECK (quantity > 0 AND quantity <= 99),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (user_id, product_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE
+);
+
+-- ------------------------------------------------
+-- 6) orders table
+-- ------------------------------------------------
+CREATE TABLE orders (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    user_id INT NOT NULL,
+    status VARCHAR(32) NOT NULL DEFAULT 'pending' CHECK (
+        status IN (
+            'pending',
+            'confirmed',
+            'preparing',
+            'out_for_delivery',
+            'delivered',
+            'cancelled',
+            'refunded'
+        )
+    ),
+    subtotal DECIMAL(12, 2) NOT NULL CHECK (subtotal >= 0),
+    discount_total DECIMAL(12, 2) NOT NULL CHECK (discount_total >= 0),
+    total DECIMAL(12, 2) NOT NULL CHECK (total >= 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_orders_user ON orders(user_id);
+
+-- ------------------------------------------------
+-- 7) order_items table
+-- ------------------------------------------------
+-- Product name, unit price and discount are snapshotted at purchase time
+-- so later catalogue edits do not change historical orders.
+CREATE TABLE order_items (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    order_id INT NOT NULL,
+    product_id INT,
+    product_name VARCHAR(64) NOT NULL,
+    unit_price DECIMAL(10, 2) NOT NULL CHECK (unit_price >= 0),
+    discount DECIMAL(5, 2) NOT NULL CHECK (
+        discount >= 0
+        AND discount <= 100
+    ),
+    quantity INT NOT NULL CHECK (quantity > 0),
+    line_total DECIMAL(12, 2) NOT NULL CHECK (line_total >= 0),
+    FOREIGN KEY (order_id) REFERENCES orders(id) ON DELETE CASCADE,
+    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_order_items_order ON order_items(order_id);
+
+-- ------------------------------------------------
+-- 8) refresh_tokens table
+-- ------------------------------------------------
+-- Only a SHA-256 hash of each opaque refresh token is stored.
+-- Tokens issued by rotation share the family_id of the login they descend from,
+-- so presenting an already used token revokes the whole family.
+-- The family_id is also the id of the login's row in sessions.
+CREATE TABLE refresh_tokens (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    user_id INT NOT NULL,
+    family_id UUID NOT NULL,
+    token_hash CHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Separate index for family revocation
+CREATE INDEX idx_refresh_tokens_family ON refresh_tokens(family_id);
+
+-- ------------------------------------------------
+-- 9) password_reset_tokens table
+-- ------------------------------------------------
+-- Only a SHA-256 hash of each emailed reset token is stored.
+-- A token can be used once; requesting a new one invalidates the previous ones.
+CREATE TABLE password_reset_tokens (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    user_id INT NOT NULL,
+    token_hash CHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Separate index for invalidating a user's outstanding tokens
+CREATE INDEX idx_password_reset_tokens_user ON password_reset_tokens(user_id);
+
+-- ------------------------------------------------
+-- 10) login_failures table
+-- ------------------------------------------------
+-- Failed login counters per username and per client IP, wrong second-factor codes
+-- per user ID and password reset requests per client IP.
+-- Usernames are tracked whether or not they exist so lockouts do not reveal accounts.
+CREATE TABLE login_failures (
+    scope VARCHAR(16) NOT NULL CHECK (scope IN ('username', 'ip', 'second_factor', 'password_reset')),
+    subject VARCHAR(255) NOT NULL,
+    failures INT NOT NULL CHECK (failures > 0),
+    last_failure_at TIMESTAMPTZ NOT NULL,
+    locked_until TIMESTAMPTZ,
+    PRIMARY KEY (scope, subject)
+);
+
+-- ------------------------------------------------
+-- 11) email_verification_tokens table
+-- ------------------------------------------------
+-- Only a SHA-256 hash of each emailed verification token is stored.
+-- The token records the address it was sent to, so it stops working if the email changes.
+CREATE TABLE email_verification_tokens (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    user_id INT NOT NULL,
+    email VARCHAR(128) NOT NULL,
+    token_hash CHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Separate index for the resend rate limit and invalidation
+CREATE INDEX idx_email_verification_tokens_user ON email_verification_tokens(user_id, created_at);
+
+-- ------------------------------------------------
+-- 12) user_totp table
+-- ------------------------------------------------
+-- TOTP secret of a user; two-factor login is enabled once confirmed_at is set.
+-- last_used_step is the time step of the last accepted code, so codes cannot be replayed.
+CREATE TABLE user_totp (
+    user_id INT PRIMARY KEY,
+    secret VARCHAR(64) NOT NULL,
+    confirmed_at TIMESTAMPTZ,
+    last_used_step BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- ------------------------------------------------
+-- 13) totp_recovery_codes table
+-- ------------------------------------------------
+-- Single-use codes that stand in for a TOTP code, stored as Argon2 hashes.
+CREATE TABLE totp_recovery_codes (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    user_id INT NOT NULL,
+    code_hash VARCHAR(255) NOT NULL,
+    used_at TIMESTAMPTZ,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Separate index for looking up the codes of a user
+CREATE INDEX idx_totp_recovery_codes_user ON totp_recovery_codes(user_id);
+
+-- ------------------------------------------------
+-- 14) login_challenges table
+-- ------------------------------------------------
+-- Issued after a correct password when two-factor login is enabled;
+-- exchanged together with a TOTP or recovery code for the real tokens.
+CREATE TABLE login_challenges (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    user_id INT NOT NULL,
+    token_hash CHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    attempts INT NOT NULL DEFAULT 0,
+    used_at TIMESTAMPTZ,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- ------------------------------------------------
+-- 15) user_identities table
+-- ------------------------------------------------
+-- Links an account at an external OpenID Connect provider (issuer + subject) to a user.
+CREATE TABLE user_identities (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    user_id INT NOT NULL,
+    issuer VARCHAR(255) NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    email VARCHAR(128),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (issuer, subject),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Separate index for listing the identities of a user
+CREATE INDEX idx_user_identities_user ON user_identities(user_id);
+
+-- ------------------------------------------------
+-- 16) oidc_login_states table
+-- ------------------------------------------------
+-- Pending OpenID Connect logins, keyed by a SHA-256 hash of the state parameter.
+-- Rows are deleted when the provider redirects back, so each state is used once.
+CREATE TABLE oidc_login_states (
+    state_hash CHAR(64) PRIMARY KEY,
+    nonce VARCHAR(64) NOT NULL,
+    code_verifier VARCHAR(128) NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+-- ------------------------------------------------
+-- 17) api_keys table
+-- ------------------------------------------------
+-- Long-lived credentials for machine clients such as POS terminals, owned by a user.
+-- Only a SHA-256 hash of the key is stored; the public prefix identifies it in listings.
+CREATE TABLE api_keys (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    user_id INT NOT NULL,
+    name VARCHAR(64) NOT NULL,
+    prefix VARCHAR(16) NOT NULL,
+    key_hash CHAR(64) NOT NULL UNIQUE,
+    -- e.g. 'product:read', 'order:write'
+    scopes VARCHAR(32)[] NOT NULL,
+    -- NULL for keys that do not expire
+    expires_at TIMESTAMPTZ,
+    last_used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Separate index for listing the keys of a user
+CREATE INDEX idx_api_keys_user ON api_keys(user_id);
+
+-- ------------------------------------------------
+-- 18) sessions table
+-- ------------------------------------------------
+-- One row per login. The id is the family_id of its refresh tokens and the `sid` claim
+-- of its access tokens, which are rejected once the session is revoked.
+CREATE TABLE sessions (
+    id UUID PRIMARY KEY,
+    user_id INT NOT NULL,
+    user_agent VARCHAR(255),
+    -- client IP at login, updated on every refresh
+    ip VARCHAR(45) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    revoked_at TIMESTAMPTZ,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Separate index for listing the sessions of a user
+CREATE INDEX idx_sessions_user ON sessions(user_id);
+
+-- ------------------------------------------------
+-- 19) product search
+-- ------------------------------------------------
+CREATE EXTENSION IF NOT EXISTS unaccent;
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Lower-cases and strips accents, so "borek" matches "Börek" and "isik" matches "IŞIK".
+-- Requires a UTF8 database; the parser does not see non-ASCII letters in SQL_ASCII ones.
+CREATE TEXT SEARCH CONFIGURATION product_search (COPY = simple);
+ALTER TEXT SEARCH CONFIGURATION product_search
+    ALTER MAPPING FOR hword, hword_part, word WITH unaccent, simple;
+
+-- The same folding for trigram matching; unaccent itself is not IMMUTABLE and cannot be indexed.
+CREATE FUNCTION search_fold(value TEXT) RETURNS TEXT
+    LANGUAGE sql IMMUTABLE PARALLEL SAFE STRICT
+    AS $$ SELECT lower(public.unaccent('public.unaccent'::regdictionary, value)) $$;
+
+-- Names rank above descriptions
+ALTER TABLE products ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('product_search', name), 'A')
+    || setweight(to_tsvector('product_search', description), 'B')
+) STORED;
+
+CREATE INDEX idx_products_search ON products USING GIN (search_vector);
+
+-- Typo-tolerant matching on names
+CREATE INDEX idx_products_name_trgm ON products USING GIN (search_fold(name) gin_trgm_ops);
+
+-- ------------------------------------------------
+-- 20) product_images table
+-- ------------------------------------------------
+-- Images of a product in gallery order. Each upload is stored with resized variants;
+-- the *_key columns are keys in the public asset storage, served under ASSETS_PUBLIC_URL.
+CREATE TABLE product_images (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    product_id INT NOT NULL,
+    position INT NOT NULL CHECK (position >= 0),
+    is_primary BOOLEAN NOT NULL DEFAULT false,
+    original_key VARCHAR(255) NOT NULL,
+    thumbnail_key VARCHAR(255) NOT NULL,
+    card_key VARCHAR(255) NOT NULL,
+    detail_key VARCHAR(255) NOT NULL,
+    webp_key VARCHAR(255) NOT NULL,
+    -- dimensions of the original
+    width INT NOT NULL,
+    height INT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE
+);
+
+-- Separate index for listing the images of a product in order
+CREATE INDEX idx_product_images_product ON product_images(product_id, position);
+
+-- At most one primary image per product
+CREATE UNIQUE INDEX idx_product_images_primary ON product_images(product_id) WHERE is_primary;
+
+-- ------------------------------------------------
+-- 21) private_assets table
+-- ------------------------------------------------
+-- Owners of the files in the private asset storage, such as invoices and ID photos.
+-- Only the owner and admins may download a file, or hand out signed URLs for it.
+-- Files of deleted users stay in storage; their rows go with the user.
+CREATE TABLE private_assets (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    owner_id INT NOT NULL,
+    storage_key VARCHAR(255) NOT NULL UNIQUE,
+    original_name VARCHAR(255) NOT NULL,
+    content_type VARCHAR(100) NOT NULL,
+    size BIGINT NOT NULL,
+    sha256 CHAR(64) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    FOREIGN KEY (owner_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Separate index for listing the files of a user
+CREATE INDEX idx_private_assets_owner ON private_assets(owner_id, created_at);
@@ -1,22 +1,22 @@
 use axum::{
     body::{Body, Bytes},
     error_handling::HandleErrorLayer,
-    extract::{DefaultBodyLimit, Request},
+    extract::{DefaultBodyLimit, Path, Query, Request, State},
     http::{
         header::{AUTHORIZATION, CONTENT_TYPE},
-        Method, StatusCode,
+        HeaderName, Method, StatusCode,
     },
     middleware::{self, Next},
     response::{IntoResponse, Response},
+    routing::get,
     Router,
 };
-use http_body_util::BodyExt;
+use http_body_util::{BodyExt, Limited};
 
 use std::time::Duration;
 use tower::ServiceBuilder;
 use tower_http::{
     cors::{Any, CorsLayer},
-    services::ServeDir,
     trace::TraceLayer,
 };
 
@@ -25,14 +25,21 @@ use utoipa::OpenApi;
 use crate::{
     common::{
         app_state::AppState,
+        authz::AuthUser,
         error::{handle_error, AppError},
         jwt,
+        signed_url::SignedUrlQuery,
+        storage,
     },
     domains::{
-        auth::{user_auth_routes, UserAuthApiDoc},
+        api_key::{api_key_routes, ApiKeyApiDoc},
+        asset::{asset_routes, AssetApiDoc, DownloadAccess},
+        auth::{user_auth_private_routes, user_auth_routes, well_known_routes, UserAuthApiDoc},
+        cart::{cart_routes, CartApiDoc},
         category::{category_routes, CategoryApiDoc},
+        order::{order_routes, OrderApiDoc},
         product::{product_routes, ProductApiDoc},
-        user::{user_private_routes, user_public_routes, UserPrivateApiDoc, UserPublicApiDoc},
+        user::{user_private_routes, UserPrivateApiDoc},
     },
 };
 
@@ -49,25 +56,36 @@ pub static FORBIDDEN_PATTERNS: Lazy<Vec<Regex>> =
     Lazy::new(|| vec![Regex::new(r"(?i)<\s*script\b[^>]*>").unwrap()]);
 
 fn create_swagger_ui() -> SwaggerUi {
-    let mut user_openapi = UserPublicApiDoc::openapi();
-    user_openapi.merge(UserPrivateApiDoc::openapi());
-
     SwaggerUi::new("/docs")
-        .url("/api-docs/user/openapi.json", user_openapi)
+        .url("/api-docs/user/openapi.json", UserPrivateApiDoc::openapi())
         .url(
             "/api-docs/user-auth/openapi.json",
             UserAuthApiDoc::openapi(),
         )
         .url("/api-docs/category/openapi.json", CategoryApiDoc::openapi())
         .url("/api-docs/product/openapi.json", ProductApiDoc::openapi())
+        .url("/api-docs/cart/openapi.json", CartApiDoc::openapi())
+        .url("/api-docs/order/openapi.json", OrderApiDoc::openapi())
+        .url("/api-docs/api-key/openapi.json", ApiKeyApiDoc::openapi())
+        .url("/api-docs/asset/openapi.json", AssetApiDoc::openapi())
 }
 
 pub fn create_router(state: AppState) -> Router {
     // Build a CORS layer that applies to everyone
     let cors = CorsLayer::new()
-        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+        ])
         .allow_origin(Any)
-        .allow_headers([AUTHORIZATION, CONTENT_TYPE]);
+        .allow_headers([
+            AUTHORIZATION,
+            CONTENT_TYPE,
+            HeaderName::from_static(jwt::API_KEY_HEADER),
+        ]);
 
     // Create a common middleware stack for error handling, timeouts, and CORS.
     let middleware_stack = ServiceBuilder::new()
@@ -75,40 +93,57 @@ pub fn create_router(state: AppState) -> Router {
         .timeout(Duration::from_secs(1800))
         .layer(cors);
 
-    // /auth routes (login, register, refresh, etc.) — no logging here
+    // /auth routes (login, signup, refresh, etc.) — no logging here
     let auth_router = Router::new()
         .nest("/auth", user_auth_routes())
-        .nest("/user", user_public_routes())
-        .layer(middleware::from_fn(make_request_response_inspecter(false)));
+        .nest("/.well-known", well_known_routes())
+        .layer(middleware::from_fn(make_request_response_inspecter(
+            false,
+            state.config.asset_max_size,
+        )));
 
     // Protected API routes
     let protected_routes = Router::new()
+        .nest("/auth", user_auth_private_routes())
         .nest("/user", user_private_routes())
         .nest("/product", product_routes())
         .nest("/category", category_routes())
+        .nest("/cart", cart_routes())
+        .nest("/order", order_routes())
+        .nest("/api-keys", api_key_routes())
+        .nest("/asset", asset_routes())
         // by default, Multipart limits to 2MB; override with `asset_max_size`
         // See https://docs.rs/axum/latest/axum/extract/struct.Multipart.html
         .layer(DefaultBodyLimit::max(state.config.asset_max_size))
-        // enforce JWT authentication
-        .route_layer(middleware::from_fn(jwt::jwt_auth))
+        // enforce JWT or API key authentication
+        .route_layer(middleware::from_fn_with_state(state.clone(), jwt::jwt_auth))
         // attach inspecter
-        .layer(middleware::from_fn(make_request_response_inspecter(true)));
+        .layer(middleware::from_fn(make_request_response_inspecter(
+            true,
+            state.config.asset_max_size,
+        )));
 
     // setup assets routes
-    let public_assets_routes = Router::new().nest_service(
-        state.config.assets_public_url.as_str(),
-        ServeDir::new(state.config.assets_public_path.clone()),
+    let public_assets_routes = Router::new().route(
+        &asset_route(&state.config.assets_public_url),
+        get(serve_public_asset),
     );
 
     let private_assets_routes = Router::new()
-        .nest_service(
-            state.config.assets_private_url.as_str(),
-            ServeDir::new(state.config.assets_private_path.clone()),
+        .route(
+            &asset_route(&state.config.assets_private_url),
+            get(serve_private_asset),
         )
-        // enforce JWT authentication
-        .route_layer(middleware::from_fn(jwt::jwt_auth))
+        // enforce JWT or API key authentication, unless the URL is signed
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            private_asset_auth,
+        ))
         // attach inspecter
-        .layer(middleware::from_fn(make_request_response_inspecter(true)));
+        .layer(middleware::from_fn(make_request_response_inspecter(
+            true,
+            state.config.asset_max_size,
+        )));
 
     // Create the main router
     // and merge all the routes
@@ -151,6 +186,55 @@ async fn health_check() -> &'static str {
     "OK\n"
 }
 
+/// Route matching every asset key under the URL assets are served at.
+fn asset_route(base_url: &str) -> String {
+    format!("{}/{{*key}}", base_url.trim_end_matches('/'))
+}
+
+/// Serves public assets. Keys are never reused for different contents, so clients may
+/// cache them indefinitely.
+async fn serve_public_asset(
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+) -> Result<Response, AppError> {
+    storage::serve(
+        state.public_storage.as_ref(),
+        &key,
+        "public, max-age=31536000, immutable",
+    )
+    .await
+}
+
+/// Lets requests with a signed URL through to `serve_private_asset`, which verifies the
+/// signature; all other requests must authenticate.
+async fn private_asset_auth(
+    State(state): State<AppState>,
+    Query(query): Query<SignedUrlQuery>,
+    req: Request,
+    next: Next,
+) -> Result<Response, Response> {
+    if query.is_signed() {
+        return Ok(next.run(req).await);
+    }
+    jwt::jwt_auth(State(state), req, next).await
+}
+
+/// Serves private assets to their owner and admins, or to anyone with a valid signed URL.
+/// Shared caches must not keep them.
+async fn serve_private_asset(
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+    Query(query): Query<SignedUrlQuery>,
+    auth_user: Option<AuthUser>,
+) -> Result<Response, AppError> {
+    let access = match auth_user {
+        Some(user) if !query.is_signed() => DownloadAccess::User(user),
+        _ => DownloadAccess::Signed(&query),
+    };
+    state.asset_service.authorize_download(&key, access).await?;
+    storage::serve(state.private_storage.as_ref(), &key, "private, no-cache").await
+}
+
 /// Fallback handler for unmatched routes
 /// This function returns a 404 Not Found response with a message.
 pub async fn fallback() -> Result<impl IntoResponse, AppError> {
@@ -164,13 +248,16 @@ type InspectorFuture = std::pin::Pin<
 
 /// Middleware that inspects request bodies and URL query strings, as well as response bodies, logging them for debugging, and rejects forbidden content.
 /// Intercepts HTTP requests and responses: buffers bodies and query strings, then logs their content.
-/// Returns a 403 Forbidden error if any forbidden patterns are detected in the request body or query string.
-/// Note: multipart/form-data requests bypass this middleware and must be validated within their handlers.
+/// Returns a 403 Forbidden error if any forbidden patterns are detected in the request body or query string,
+/// and a 413 Payload Too Large error if the request body exceeds `body_limit` bytes.
+/// Note: the bodies of multipart requests are passed through unread, so uploads keep streaming;
+/// `parse_multipart` validates them instead.
 fn make_request_response_inspecter(
     log_enabled: bool,
+    body_limit: usize,
 ) -> impl Fn(Request<Body>, Next) -> InspectorFuture + Clone + Send + Sync + 'static {
     move |req, next| {
-        let fut = request_response_inspecter(req, next, log_enabled);
+        let fut = request_response_inspecter(req, next, log_enabled, body_limit);
         Box::pin(fut)
     }
 }
@@ -179,6 +266,7 @@ async fn request_response_inspecter(
     req: Request<Body>,
     next: Next,
     log_enabled: bool,
+    body_limit: usize,
 ) -> Result<Response, (StatusCode, String)> {
     // inspect forbidden query string
     if let Some(query) = req.uri().query() {
@@ -187,8 +275,23 @@ async fn request_response_inspecter(
         }
     }
 
+    let is_multipart = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value
+                .trim_start()
+                .get(..10)
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case("multipart/"))
+        });
+    if is_multipart {
+        return Ok(next.run(req).await);
+    }
+
     let (parts, body) = req.into_parts();
-    let bytes = request_inspect_print("request", log_enabled, body).await?;
+    let bytes =
+        request_inspect_print("request", log_enabled, Limited::new(body, body_limit)).await?;
     let req = Request::from_parts(parts, Body::from(bytes));
 
     let mut res = next.run(req).await;
@@ -205,14 +308,20 @@ async fn request_response_inspecter(
 async fn request_inspect_print<B>(
     direction: &str,
     log_enabled: bool,
-    body: B,
+    body: Limited<B>,
 ) -> Result<Bytes, (StatusCode, String)>
 where
     B: axum::body::HttpBody<Data = Bytes>,
-    B::Error: std::fmt::Display,
+    B::Error: Into<axum::BoxError>,
 {
     let bytes = match body.collect().await {
         Ok(collected) => collected.to_bytes(),
+        Err(err) if err.is::<http_body_util::LengthLimitError>() => {
+            return Err((
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("{direction} body is too large"),
+            ));
+        }
         Err(err) => {
             return Err((
                 StatusCode::BAD_REQUEST,
@@ -256,3 +365,98 @@ where
 
     Ok(bytes)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Multipart, routing::post};
+    use std::{
+        pin::Pin,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        task::{Context, Poll},
+    };
+    use tokio::io::{AsyncRead, AsyncReadExt, ReadBuf};
+    use tokio_util::io::ReaderStream;
+    use tower::ServiceExt;
+
+    const LIMIT: usize = 64 * 1024;
+
+    /// Counts the bytes the request body has been read up to.
+    struct CountingReader<R> {
+        inner: R,
+        read: Arc<AtomicUsize>,
+    }
+
+    impl<R: AsyncRead + Unpin> AsyncRead for CountingReader<R> {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            let before = buf.filled().len();
+            let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+            self.read
+                .fetch_add(buf.filled().len() - before, Ordering::Relaxed);
+            poll
+        }
+    }
+
+    async fn upload(mut multipart: Multipart) -> StatusCode {
+        loop {
+            let mut field = match multipart.next_field().await {
+                Ok(Some(field)) => field,
+                Ok(None) => return StatusCode::OK,
+                Err(err) => return err.status(),
+            };
+            loop {
+                match field.chunk().await {
+                    Ok(Some(_)) => {}
+                    Ok(None) => break,
+                    Err(err) => return err.status(),
+                }
+            }
+        }
+    }
+
+    /// Uploads are limited to `LIMIT` bytes; other bodies to `body_limit`.
+    fn router(body_limit: usize) -> Router {
+        Router::new()
+            .route("/upload", post(upload))
+            .layer(DefaultBodyLimit::max(LIMIT))
+            .layer(middleware::from_fn(make_request_response_inspecter(
+                false, body_limit,
+            )))
+    }
+
+    #[tokio::test]
+    async fn test_oversized_bodies_are_rejected_without_buffering() {
+        let read = Arc::new(AtomicUsize::new(0));
+        let contents =
+            (&b"--XX\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.png\"\r\n\r\n"
+                [..])
+                .chain(tokio::io::repeat(b'a').take(16 * 1024 * 1024))
+                .chain(&b"\r\n--XX--\r\n"[..]);
+        let reader = CountingReader {
+            inner: contents,
+            read: read.clone(),
+        };
+        let req = Request::post("/upload")
+            .header(CONTENT_TYPE, "multipart/form-data; boundary=XX")
+            .body(Body::from_stream(ReaderStream::new(reader)))
+            .unwrap();
+        let res = router(usize::MAX).oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        // Rejected by the multipart limit, not after the whole upload was read.
+        assert!(read.load(Ordering::Relaxed) < 4 * LIMIT);
+
+        let req = Request::post("/upload")
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(vec![b' '; LIMIT + 1]))
+            .unwrap();
+        let res = router(LIMIT).oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -1,9 +1,24 @@
 pub mod app_state;
+pub mod authz;
 pub mod bootstrap;
+pub mod client_ip;
 pub mod config;
 pub mod dto;
 pub mod error;
+pub mod file_scanner;
+pub mod file_type;
 pub mod hash_util;
+pub mod image_variants;
 pub mod jwt;
+pub mod mailer;
 pub mod multipart_helper;
+pub mod oidc;
+pub mod pagination;
+pub mod password_policy;
+pub mod price_util;
+pub mod query_params;
+pub mod s3_storage;
+pub mod signed_url;
+pub mod storage;
+pub mod totp;
 pub mod ts_format;
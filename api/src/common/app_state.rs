@@ -1,11 +1,12 @@
 use std::sync::Arc;
 
 use crate::domains::{
-    auth::AuthServiceTrait, category::CategoryServiceTrait, product::ProductServiceTrait,
-    user::UserServiceTrait,
+    api_key::ApiKeyServiceTrait, asset::AssetServiceTrait, auth::AuthServiceTrait,
+    cart::CartServiceTrait, category::CategoryServiceTrait, order::OrderServiceTrait,
+    product::ProductServiceTrait, user::UserServiceTrait,
 };
 
-use super::config::Config;
+use super::{config::Config, file_scanner::FileScanner, jwt::JwtKeys, storage::Storage};
 
 /// AppState is a struct that holds the application-wide shared state.
 /// It is passed to request handlers via Axum's extension mechanism.
@@ -13,29 +14,62 @@ use super::config::Config;
 pub struct AppState {
     /// Global application configuration.
     pub config: Config,
+    /// Keys used to sign and validate access tokens.
+    pub jwt_keys: Arc<JwtKeys>,
+    /// Malware scanner uploads are checked with before they are stored.
+    pub file_scanner: Arc<dyn FileScanner>,
+    /// Storage of assets anyone may download.
+    pub public_storage: Arc<dyn Storage>,
+    /// Storage of assets only signed-in users may download.
+    pub private_storage: Arc<dyn Storage>,
     /// Service handling authentication-related logic.
     pub auth_service: Arc<dyn AuthServiceTrait>,
     /// Service handling user-related logic.
     pub user_service: Arc<dyn UserServiceTrait>,
     pub product_service: Arc<dyn ProductServiceTrait>,
     pub category_service: Arc<dyn CategoryServiceTrait>,
+    /// Service handling shopping cart logic.
+    pub cart_service: Arc<dyn CartServiceTrait>,
+    /// Service handling order placement and lifecycle.
+    pub order_service: Arc<dyn OrderServiceTrait>,
+    /// Service managing API keys and authenticating requests made with them.
+    pub api_key_service: Arc<dyn ApiKeyServiceTrait>,
+    /// Service tracking who owns private assets and signing their URLs.
+    pub asset_service: Arc<dyn AssetServiceTrait>,
 }
 
 impl AppState {
     /// Creates a new instance of AppState with the provided dependencies.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         config: Config,
+        jwt_keys: Arc<JwtKeys>,
+        file_scanner: Arc<dyn FileScanner>,
+        public_storage: Arc<dyn Storage>,
+        private_storage: Arc<dyn Storage>,
         auth_service: Arc<dyn AuthServiceTrait>,
         user_service: Arc<dyn UserServiceTrait>,
         product_service: Arc<dyn ProductServiceTrait>,
         category_service: Arc<dyn CategoryServiceTrait>,
+        cart_service: Arc<dyn CartServiceTrait>,
+        order_service: Arc<dyn OrderServiceTrait>,
+        api_key_service: Arc<dyn ApiKeyServiceTrait>,
+        asset_service: Arc<dyn AssetServiceTrait>,
     ) -> Self {
         Self {
             config,
+            jwt_keys,
+            file_scanner,
+            public_storage,
+            private_storage,
             auth_service,
             user_service,
             product_service,
             category_service,
+            cart_service,
+            order_service,
+            api_key_service,
+            asset_service,
         }
     }
 }
@@ -0,0 +1,190 @@
+//! Role-based access control on top of the JWT claims.
+//!
+//! `jwt_auth` only proves who the caller is; the items in this module decide what they may do.
+//! Routes declare their requirements with one of the middlewares, e.g.
+//! `.route_layer(middleware::from_fn_with_state(authz::ADMIN, authz::require_roles))`,
+//! and handlers that need finer-grained checks take an [`AuthUser`] extractor.
+//!
+//! Callers authenticated with an API key are additionally limited to the key's scopes,
+//! see [`required_scope`].
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::{FromRequestParts, OptionalFromRequestParts, Path, Request, State},
+    http::{request::Parts, Method},
+    middleware::Next,
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{error::AppError, jwt::Claims};
+
+/// Role is an enum that represents the role of a user.
+/// It is stored in the `users.role` column and carried in the JWT claims.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type,
+)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum Role {
+    #[default]
+    Customer,
+    Staff,
+    Admin,
+}
+
+/// Roles allowed to manage the catalogue and user accounts.
+pub const ADMIN: &[Role] = &[Role::Admin];
+
+/// Roles allowed to run day-to-day operations such as inventory and order fulfilment.
+pub const STAFF: &[Role] = &[Role::Staff, Role::Admin];
+
+/// Scopes an API key can be granted. Each protected resource has a `read` scope for
+/// `GET` requests and a `write` scope for everything else.
+pub const API_KEY_SCOPES: &[&str] = &[
+    "user:read",
+    "user:write",
+    "product:read",
+    "product:write",
+    "category:read",
+    "category:write",
+    "cart:read",
+    "cart:write",
+    "order:read",
+    "order:write",
+];
+
+/// Returns the scope an API key needs for a request, derived from the first path segment
+/// and the method. Routes without a scope, such as account and key management,
+/// cannot be called with an API key.
+pub fn required_scope(method: &Method, path: &str) -> Option<&'static str> {
+    let resource = path.trim_start_matches('/').split('/').next()?;
+    let access = if matches!(*method, Method::GET | Method::HEAD) {
+        "read"
+    } else {
+        "write"
+    };
+
+    API_KEY_SCOPES
+        .iter()
+        .find(|scope| scope.split_once(':') == Some((resource, access)))
+        .copied()
+}
+
+/// The authenticated caller, built from the claims inserted by `jwt_auth`.
+#[derive(Debug, Clone, Copy)]
+pub struct AuthUser {
+    pub user_id: i32,
+    pub role: Role,
+    pub email_verified: bool,
+    /// The session of the access token; `None` for callers using an API key.
+    pub session_id: Option<Uuid>,
+}
+
+impl AuthUser {
+    /// Returns whether the caller has one of the given roles.
+    pub fn has_any_role(&self, roles: &[Role]) -> bool {
+        roles.contains(&self.role)
+    }
+
+    /// Fails with `Forbidden` unless the caller has one of the given roles.
+    pub fn require_any_role(&self, roles: &[Role]) -> Result<(), AppError> {
+        if self.has_any_role(roles) {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden)
+        }
+    }
+
+    /// Fails with `Forbidden` unless the caller is the given user or has one of the given roles.
+    pub fn require_self_or_any_role(&self, user_id: i32, roles: &[Role]) -> Result<(), AppError> {
+        if self.user_id == user_id {
+            Ok(())
+        } else {
+            self.require_any_role(roles)
+        }
+    }
+
+    /// Fails with `EmailNotVerified` unless the caller has verified their email address.
+    pub fn require_verified_email(&self) -> Result<(), AppError> {
+        if self.email_verified {
+            Ok(())
+        } else {
+            Err(AppError::EmailNotVerified)
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for AuthUser
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let claims = parts
+            .extensions
+            .get::<Claims>()
+            .ok_or(AppError::InvalidToken)?;
+
+        Ok(Self {
+            user_id: claims.user_id()?,
+            role: claims.role,
+            email_verified: claims.email_verified,
+            session_id: claims.sid,
+        })
+    }
+}
+
+/// `Option<AuthUser>` is `None` for requests that were let through without authentication,
+/// such as private asset downloads with a signed URL.
+impl<S> OptionalFromRequestParts<S> for AuthUser
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        if parts.extensions.get::<Claims>().is_none() {
+            return Ok(None);
+        }
+        <Self as FromRequestParts<S>>::from_request_parts(parts, state)
+            .await
+            .map(Some)
+    }
+}
+
+/// Middleware that only lets callers with one of the given roles through.
+pub async fn require_roles(
+    State(roles): State<&'static [Role]>,
+    auth_user: AuthUser,
+    req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    auth_user.require_any_role(roles)?;
+    Ok(next.run(req).await)
+}
+
+/// Middleware for routes with an `{id}` user path parameter.
+/// Only the user themselves or callers with one of the given roles are let through.
+pub async fn require_self_or_roles(
+    State(roles): State<&'static [Role]>,
+    auth_user: AuthUser,
+    Path(params): Path<HashMap<String, String>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let user_id = params
+        .get("id")
+        .and_then(|id| id.parse::<i32>().ok())
+        .ok_or_else(|| AppError::ValidationError("Invalid user id".into()))?;
+
+    auth_user.require_self_or_any_role(user_id, roles)?;
+    Ok(next.run(req).await)
+}
@@ -1,10 +1,23 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use sqlx::PgPool;
 
-use crate::common::config::Config;
+use crate::common::{
+    config::{Config, StorageBackend},
+    file_scanner::{ClamdScanner, FileScanner, NoopScanner},
+    jwt::JwtKeys,
+    mailer::{FileMailer, Mailer},
+    oidc::OidcClient,
+    s3_storage::S3Storage,
+    signed_url::UrlSigner,
+    storage::{LocalStorage, Storage},
+};
+use crate::domains::api_key::{ApiKeyService, ApiKeyServiceTrait};
+use crate::domains::asset::{AssetService, AssetServiceTrait};
 use crate::domains::auth::{AuthService, AuthServiceTrait};
+use crate::domains::cart::{CartService, CartServiceTrait};
 use crate::domains::category::{CategoryService, CategoryServiceTrait};
+use crate::domains::order::{OrderService, OrderServiceTrait};
 use crate::domains::product::{ProductService, ProductServiceTrait};
 use crate::domains::user::UserServiceTrait;
 use crate::{common::app_state::AppState, domains::user::UserService};
@@ -12,23 +25,74 @@ use crate::{common::app_state::AppState, domains::user::UserService};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 /// Constructs and wires all application services and returns a configured AppState.
-pub fn build_app_state(pool: PgPool, config: Config) -> AppState {
-    let auth_service: Arc<dyn AuthServiceTrait> = AuthService::create_service(pool.clone());
+pub fn build_app_state(
+    pool: PgPool,
+    config: Config,
+    jwt_keys: Arc<JwtKeys>,
+    oidc: Option<Arc<OidcClient>>,
+    url_signer: Arc<UrlSigner>,
+) -> AppState {
+    let mailer: Arc<dyn Mailer> = Arc::new(FileMailer::new(
+        config.mail_from.clone(),
+        config.mail_outbox_path.clone(),
+    ));
+
+    let file_scanner: Arc<dyn FileScanner> = match &config.clamd_address {
+        Some(address) => Arc::new(ClamdScanner::new(address.clone(), Duration::from_secs(30))),
+        None => Arc::new(NoopScanner),
+    };
+
+    let (public_storage, private_storage): (Arc<dyn Storage>, Arc<dyn Storage>) =
+        match config.storage_backend {
+            StorageBackend::Local => (
+                Arc::new(LocalStorage::new(&config.assets_public_path)),
+                Arc::new(LocalStorage::new(&config.assets_private_path)),
+            ),
+            StorageBackend::S3 => (
+                Arc::new(S3Storage::new(&config, "public/")),
+                Arc::new(S3Storage::new(&config, "private/")),
+            ),
+        };
+
+    let auth_service: Arc<dyn AuthServiceTrait> =
+        AuthService::create_service(pool.clone(), config.clone(), jwt_keys.clone(), mailer, oidc);
 
     let user_service: Arc<dyn UserServiceTrait> = UserService::create_service(pool.clone());
 
     let product_service: Arc<dyn ProductServiceTrait> =
-        ProductService::create_service(pool.clone());
+        ProductService::create_service(pool.clone(), config.clone(), public_storage.clone());
 
     let category_service: Arc<dyn CategoryServiceTrait> =
-        CategoryService::create_service(pool.clone());
+        CategoryService::create_service(pool.clone(), public_storage.clone());
+
+    let cart_service: Arc<dyn CartServiceTrait> = CartService::create_service(pool.clone());
+
+    let order_service: Arc<dyn OrderServiceTrait> = OrderService::create_service(pool.clone());
+
+    let api_key_service: Arc<dyn ApiKeyServiceTrait> =
+        ApiKeyService::create_service(pool.clone(), config.clone());
+
+    let asset_service: Arc<dyn AssetServiceTrait> = AssetService::create_service(
+        pool.clone(),
+        config.clone(),
+        private_storage.clone(),
+        url_signer,
+    );
 
     AppState::new(
         config,
+        jwt_keys,
+        file_scanner,
+        public_storage,
+        private_storage,
         auth_service,
         user_service,
         product_service,
         category_service,
+        cart_service,
+        order_service,
+        api_key_service,
+        asset_service,
     )
 }
 
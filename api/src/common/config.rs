@@ -4,6 +4,41 @@ use std::env;
 use std::time::Duration;
 use tokio::time::sleep;
 
+/// Which actions require the user to have verified their email address.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EmailVerificationPolicy {
+    /// Verification is offered but nothing is blocked.
+    #[default]
+    Optional,
+    /// Unverified users may sign in but not place orders.
+    Checkout,
+    /// Unverified users may not sign in at all.
+    Login,
+}
+
+impl EmailVerificationPolicy {
+    /// Returns whether unverified users are kept from signing in.
+    pub fn blocks_login(&self) -> bool {
+        *self == EmailVerificationPolicy::Login
+    }
+
+    /// Returns whether unverified users are kept from placing orders.
+    pub fn blocks_checkout(&self) -> bool {
+        *self != EmailVerificationPolicy::Optional
+    }
+}
+
+/// Where uploaded assets are stored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StorageBackend {
+    /// The `ASSETS_PUBLIC_PATH` and `ASSETS_PRIVATE_PATH` directories. Multiple replicas
+    /// of the API need a shared volume for them.
+    #[default]
+    Local,
+    /// An S3-compatible bucket, with public and private assets under separate prefixes.
+    S3,
+}
+
 /// Config is a struct that holds the configuration for the application.
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -22,6 +57,101 @@ pub struct Config {
 
     pub asset_allowed_extensions_pattern: Regex,
     pub asset_max_size: usize,
+    /// Maximum width and height of uploaded images in pixels.
+    pub asset_max_image_dimension: u32,
+    /// Maximum number of pixels of uploaded images.
+    pub asset_max_image_pixels: u64,
+    /// clamd socket uploads are scanned with, either a Unix socket path or `host:port`.
+    /// Uploads are not scanned when unset.
+    pub clamd_address: Option<String>,
+    /// Directory uploads are written to while they are being checked.
+    pub asset_temp_path: String,
+
+    /// Where assets are stored.
+    pub storage_backend: StorageBackend,
+    /// Base URL of the S3-compatible service, e.g. `http://localhost:9000` for MinIO.
+    pub s3_endpoint: String,
+    pub s3_region: String,
+    pub s3_bucket: String,
+    pub s3_access_key_id: String,
+    pub s3_secret_access_key: String,
+
+    /// Secret private asset URLs are signed with; shared by every replica of the API.
+    pub asset_url_signing_key: String,
+    /// Lifetime of signed asset URLs in seconds when the request does not set one.
+    pub asset_signed_url_ttl_secs: i64,
+
+    /// JWT signing keys as comma-separated `kid:alg:path` entries, e.g.
+    /// `2025-01:RS256:keys/2025-01.pem,2025-06:EdDSA:keys/2025-06.pem`.
+    pub jwt_keys: String,
+    /// Key id of the entry in `jwt_keys` used to sign new tokens.
+    pub jwt_active_kid: String,
+    /// Lifetime of access tokens in seconds; clients renew them with a refresh token.
+    pub jwt_access_token_ttl_secs: i64,
+    /// Lifetime of refresh tokens in seconds.
+    pub jwt_refresh_token_ttl_secs: i64,
+
+    /// Sender address of outgoing email.
+    pub mail_from: String,
+    /// Directory the development mailer writes outgoing email to.
+    pub mail_outbox_path: String,
+
+    /// Frontend page that completes a password reset; the token is appended as `?token=`.
+    pub password_reset_url: String,
+    /// Lifetime of password reset tokens in seconds.
+    pub password_reset_token_ttl_secs: i64,
+    /// Minimum number of seconds between two password reset emails to the same account.
+    pub password_reset_resend_interval_secs: i64,
+    /// Password reset requests per client IP, within the login failure window,
+    /// before the IP is locked out.
+    pub password_reset_max_per_ip: i32,
+
+    /// Failed logins per username before the username is locked out.
+    pub login_max_failures_per_username: i32,
+    /// Failed logins per client IP before the IP is locked out.
+    pub login_max_failures_per_ip: i32,
+    /// Length of the first lockout in seconds; each further failure doubles it.
+    pub login_lockout_base_secs: i64,
+    /// Upper bound of a lockout in seconds.
+    pub login_lockout_max_secs: i64,
+    /// Failures older than this many seconds no longer count.
+    pub login_failure_window_secs: i64,
+    /// Take the client IP from `X-Forwarded-For`; only enable behind a trusted reverse proxy.
+    pub trust_forwarded_for: bool,
+
+    /// Which actions require a verified email address.
+    pub email_verification_policy: EmailVerificationPolicy,
+    /// Frontend page that completes email verification; the token is appended as `?token=`.
+    pub email_verification_url: String,
+    /// Lifetime of email verification tokens in seconds.
+    pub email_verification_token_ttl_secs: i64,
+    /// Minimum number of seconds between two verification emails to the same account.
+    pub email_verification_resend_interval_secs: i64,
+
+    /// Issuer shown in authenticator apps for TOTP two-factor authentication.
+    pub totp_issuer: String,
+    /// Lifetime in seconds of the challenge token handed out when a login needs a second factor.
+    pub login_challenge_ttl_secs: i64,
+    /// Wrong codes accepted per login challenge before it is discarded.
+    pub login_challenge_max_attempts: i32,
+    /// Wrong codes per user, across challenges, before two-factor login is locked out.
+    pub login_max_second_factor_failures: i32,
+
+    /// Discovery document of the OpenID Connect provider; OIDC login is off when unset.
+    pub oidc_discovery_url: Option<String>,
+    pub oidc_client_id: String,
+    /// Left empty for public clients, which are authenticated by PKCE alone.
+    pub oidc_client_secret: String,
+    /// Frontend page the provider redirects back to with `code` and `state`.
+    pub oidc_redirect_url: String,
+    /// Space-separated scopes to request; `openid` is required.
+    pub oidc_scopes: String,
+    /// Seconds the user has to complete the login at the provider.
+    pub oidc_state_ttl_secs: i64,
+
+    /// Seconds a session check is cached in memory. Sessions revoked through another
+    /// instance of the API keep working here for up to this long.
+    pub session_cache_ttl_secs: i64,
 }
 
 /// from_env reads the environment variables and returns a Config struct.
@@ -63,10 +193,131 @@ impl Config {
 
             asset_max_size: env::var("ASSET_MAX_SIZE")
                 .map(|s| s.parse::<usize>().unwrap_or(50 * 1024 * 1024))?, // Default to 50MB
+            asset_max_image_dimension: positive_from_env("ASSET_MAX_IMAGE_DIMENSION", 8192),
+            asset_max_image_pixels: positive_from_env("ASSET_MAX_IMAGE_PIXELS", 40_000_000),
+            clamd_address: env::var("CLAMD_ADDRESS").ok().filter(|s| !s.is_empty()),
+            asset_temp_path: env::var("ASSET_TEMP_PATH").unwrap_or_else(|_| {
+                env::temp_dir()
+                    .join("foodzy-uploads")
+                    .to_string_lossy()
+                    .into_owned()
+            }),
+
+            storage_backend: match env::var("ASSET_STORAGE")
+                .unwrap_or_default()
+                .to_ascii_lowercase()
+                .as_str()
+            {
+                "" | "local" => StorageBackend::Local,
+                "s3" => StorageBackend::S3,
+                other => {
+                    eprintln!("Invalid ASSET_STORAGE value: {}", other);
+                    StorageBackend::default()
+                }
+            },
+            s3_endpoint: env::var("S3_ENDPOINT").unwrap_or_default(),
+            s3_region: env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".into()),
+            s3_bucket: env::var("S3_BUCKET").unwrap_or_default(),
+            s3_access_key_id: env::var("S3_ACCESS_KEY_ID").unwrap_or_default(),
+            s3_secret_access_key: env::var("S3_SECRET_ACCESS_KEY").unwrap_or_default(),
+
+            asset_url_signing_key: env::var("ASSET_URL_SIGNING_KEY")?,
+            asset_signed_url_ttl_secs: positive_from_env("ASSET_SIGNED_URL_TTL_SECS", 60 * 60),
+
+            jwt_keys: env::var("JWT_KEYS")?,
+            jwt_active_kid: env::var("JWT_ACTIVE_KID")?,
+            jwt_access_token_ttl_secs: positive_from_env("JWT_ACCESS_TOKEN_TTL_SECS", 15 * 60),
+            jwt_refresh_token_ttl_secs: positive_from_env(
+                "JWT_REFRESH_TOKEN_TTL_SECS",
+                30 * 24 * 60 * 60,
+            ),
+
+            mail_from: env::var("MAIL_FROM").unwrap_or_else(|_| "no-reply@foodzy.local".into()),
+            mail_outbox_path: env::var("MAIL_OUTBOX_PATH").unwrap_or_else(|_| "outbox".into()),
+
+            password_reset_url: env::var("PASSWORD_RESET_URL")
+                .unwrap_or_else(|_| "http://localhost:8080/reset-password".into()),
+            password_reset_token_ttl_secs: positive_from_env(
+                "PASSWORD_RESET_TOKEN_TTL_SECS",
+                60 * 60,
+            ),
+            password_reset_resend_interval_secs: positive_from_env(
+                "PASSWORD_RESET_RESEND_INTERVAL_SECS",
+                60,
+            ),
+            password_reset_max_per_ip: positive_from_env("PASSWORD_RESET_MAX_PER_IP", 10),
+
+            login_max_failures_per_username: positive_from_env(
+                "LOGIN_MAX_FAILURES_PER_USERNAME",
+                5,
+            ),
+            login_max_failures_per_ip: positive_from_env("LOGIN_MAX_FAILURES_PER_IP", 20),
+            login_lockout_base_secs: positive_from_env("LOGIN_LOCKOUT_BASE_SECS", 30),
+            login_lockout_max_secs: positive_from_env("LOGIN_LOCKOUT_MAX_SECS", 15 * 60),
+            login_failure_window_secs: positive_from_env("LOGIN_FAILURE_WINDOW_SECS", 60 * 60),
+            trust_forwarded_for: env::var("TRUST_X_FORWARDED_FOR")
+                .map(|s| s.eq_ignore_ascii_case("true") || s == "1")
+                .unwrap_or(false),
+
+            email_verification_policy: match env::var("EMAIL_VERIFICATION_REQUIRED_FOR")
+                .unwrap_or_default()
+                .to_ascii_lowercase()
+                .as_str()
+            {
+                "" | "none" => EmailVerificationPolicy::Optional,
+                "checkout" => EmailVerificationPolicy::Checkout,
+                "login" => EmailVerificationPolicy::Login,
+                other => {
+                    eprintln!("Invalid EMAIL_VERIFICATION_REQUIRED_FOR value: {}", other);
+                    EmailVerificationPolicy::default()
+                }
+            },
+            email_verification_url: env::var("EMAIL_VERIFICATION_URL")
+                .unwrap_or_else(|_| "http://localhost:8080/verify-email".into()),
+            email_verification_token_ttl_secs: positive_from_env(
+                "EMAIL_VERIFICATION_TOKEN_TTL_SECS",
+                24 * 60 * 60,
+            ),
+            email_verification_resend_interval_secs: positive_from_env(
+                "EMAIL_VERIFICATION_RESEND_INTERVAL_SECS",
+                60,
+            ),
+
+            totp_issuer: env::var("TOTP_ISSUER").unwrap_or_else(|_| "Foodzy".into()),
+            login_challenge_ttl_secs: positive_from_env("LOGIN_CHALLENGE_TTL_SECS", 5 * 60),
+            login_challenge_max_attempts: positive_from_env("LOGIN_CHALLENGE_MAX_ATTEMPTS", 5),
+            login_max_second_factor_failures: positive_from_env(
+                "LOGIN_MAX_SECOND_FACTOR_FAILURES",
+                10,
+            ),
+
+            oidc_discovery_url: env::var("OIDC_DISCOVERY_URL")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            oidc_client_id: env::var("OIDC_CLIENT_ID").unwrap_or_default(),
+            oidc_client_secret: env::var("OIDC_CLIENT_SECRET").unwrap_or_default(),
+            oidc_redirect_url: env::var("OIDC_REDIRECT_URL").unwrap_or_default(),
+            oidc_scopes: env::var("OIDC_SCOPES").unwrap_or_else(|_| "openid email profile".into()),
+            oidc_state_ttl_secs: positive_from_env("OIDC_STATE_TTL_SECS", 10 * 60),
+
+            session_cache_ttl_secs: positive_from_env("SESSION_CACHE_TTL_SECS", 30),
         })
     }
 }
 
+/// Reads a positive number from the environment, falling back to the default
+/// when the variable is missing, malformed or not positive.
+fn positive_from_env<T>(key: &str, default: T) -> T
+where
+    T: std::str::FromStr + PartialOrd + Default,
+{
+    env::var(key)
+        .ok()
+        .and_then(|s| s.parse::<T>().ok())
+        .filter(|value| *value > T::default())
+        .unwrap_or(default)
+}
+
 /// setup_database initializes the database connection pool.
 pub async fn setup_database(config: &Config) -> Result<PgPool, sqlx::Error> {
     // Attempt to connect repeatedly, with a small delay, until success (or a max number of tries)
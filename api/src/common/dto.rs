@@ -1,5 +1,8 @@
+use std::str::FromStr;
+
 use axum::response::{IntoResponse, Response};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 /// A standardized API response format.
 #[derive(Serialize, Deserialize, Debug)]
@@ -50,6 +53,63 @@ where
     }
 }
 
+/// Direction of a sorted list.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+}
+
+impl FromStr for SortOrder {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "asc" => Ok(SortOrder::Asc),
+            "desc" => Ok(SortOrder::Desc),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Query parameters shared by paginated list endpoints.
+/// Pages are addressed either by `offset` or by the `cursor` returned with the previous page.
+#[derive(Deserialize, IntoParams, Debug, Default)]
+#[into_params(parameter_in = Query)]
+pub struct PageQuery {
+    /// Number of items per page, 1 to 100 (default 20).
+    pub limit: Option<i64>,
+    /// Number of items to skip; cannot be combined with `cursor`.
+    pub offset: Option<i64>,
+    /// `next_cursor` of the previous page.
+    pub cursor: Option<String>,
+    /// Field to sort by; the allowed fields depend on the endpoint.
+    pub sort: Option<String>,
+    #[param(inline)]
+    pub order: Option<SortOrder>,
+}
+
+/// One page of a list, returned as the `data` of an `ApiResponse`.
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Number of items in the whole list.
+    pub total: i64,
+    /// Cursor of the next page; absent on the last page.
+    pub next_cursor: Option<String>,
+}
+
 /// A wrapper struct for the API response.
 /// This struct is used to convert the API response into a format that can be returned by Axum.
 /// It implements the `IntoResponse` trait, which allows it to be used as a response in Axum handlers.
@@ -1,12 +1,14 @@
 use axum::{
-    http::StatusCode,
+    http::{header::RETRY_AFTER, StatusCode},
     response::{IntoResponse, Response},
     BoxError,
 };
 
+use serde::{Deserialize, Serialize};
 use sqlx::Error as SqlxError;
 use thiserror::Error;
 use tracing::error;
+use utoipa::ToSchema;
 
 use crate::common::dto::RestApiResponse;
 
@@ -28,9 +30,16 @@ pub enum AppError {
     #[error("Validation error: {0}")]
     ValidationError(String),
 
+    /// Used for requests with one or more invalid parameters; the response lists each of them.
+    #[error("Invalid parameters")]
+    InvalidParameters(Vec<FieldError>),
+
     #[error("Forbidden Request")]
     Forbidden,
 
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
     /// Used for file-related errors
     #[error("File data is empty")]
     InvalidFileData,
@@ -44,6 +53,15 @@ pub enum AppError {
     #[error("Unsupported file extension")]
     UnsupportedFileExtension,
 
+    #[error("File contents do not match the file extension")]
+    FileContentMismatch,
+
+    #[error("Image dimensions exceed the allowed limits")]
+    ImageTooLarge,
+
+    #[error("File rejected by virus scan")]
+    InfectedFile,
+
     /// Used for authentication-related errors
     #[error("Wrong credentials")]
     WrongCredentials,
@@ -55,6 +73,29 @@ pub enum AppError {
     TokenCreation,
     #[error("User not found")]
     UserNotFound,
+    #[error("Too many attempts, try again in {retry_after_secs} seconds")]
+    TooManyAttempts { retry_after_secs: i64 },
+    #[error("Email address is not verified")]
+    EmailNotVerified,
+    #[error("Invalid or expired signature")]
+    InvalidSignature,
+
+    /// Used for inventory errors
+    #[error("Insufficient stock: {0}")]
+    InsufficientStock(String),
+
+    /// Used for order lifecycle errors
+    #[error("Invalid order status transition from {from} to {to}")]
+    InvalidStatusTransition { from: String, to: String },
+}
+
+/// A problem with a single request parameter.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+pub struct FieldError {
+    #[schema(example = "min_price")]
+    pub field: String,
+    #[schema(example = "Must be a number")]
+    pub message: String,
 }
 
 /// Converts the AppError enum into an HTTP response.
@@ -63,26 +104,50 @@ impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let status = match self {
             AppError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            AppError::InvalidParameters(_) => StatusCode::BAD_REQUEST,
             AppError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::NotFound(_) => StatusCode::NOT_FOUND,
             AppError::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::Forbidden => StatusCode::FORBIDDEN,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
             AppError::InvalidFileData
             | AppError::FileSizeExceeded
             | AppError::InvalidFileName
-            | AppError::UnsupportedFileExtension => StatusCode::BAD_REQUEST,
+            | AppError::UnsupportedFileExtension
+            | AppError::FileContentMismatch
+            | AppError::ImageTooLarge
+            | AppError::InfectedFile => StatusCode::BAD_REQUEST,
             AppError::WrongCredentials => StatusCode::UNAUTHORIZED,
             AppError::MissingCredentials => StatusCode::BAD_REQUEST,
             AppError::InvalidToken => StatusCode::UNAUTHORIZED,
             AppError::TokenCreation => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::UserNotFound => StatusCode::NOT_FOUND,
+            AppError::TooManyAttempts { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AppError::EmailNotVerified => StatusCode::FORBIDDEN,
+            AppError::InvalidSignature => StatusCode::FORBIDDEN,
+            AppError::InsufficientStock(_) => StatusCode::CONFLICT,
+            AppError::InvalidStatusTransition { .. } => StatusCode::CONFLICT,
         };
+
+        if let AppError::InvalidParameters(errors) = &self {
+            let body = axum::Json(ApiResponse {
+                status: status.as_u16(),
+                message: self.to_string(),
+                data: Some(errors),
+            });
+            return (status, body).into_response();
+        }
+
         let body = axum::Json(ApiResponse::<()> {
             status: status.as_u16(),
             message: self.to_string(),
             data: None,
         });
 
+        if let AppError::TooManyAttempts { retry_after_secs } = self {
+            return (status, [(RETRY_AFTER, retry_after_secs.to_string())], body).into_response();
+        }
+
         (status, body).into_response()
     }
 }
@@ -0,0 +1,184 @@
+//! Malware scanning of uploaded files.
+//!
+//! Uploads are scanned through the [`FileScanner`] trait before they are moved into the
+//! asset directories. [`NoopScanner`] accepts everything and is used when `CLAMD_ADDRESS`
+//! is not set. [`ClamdScanner`] streams the file to a ClamAV daemon, or anything speaking
+//! its protocol, over a Unix or TCP socket.
+
+use std::{path::Path, time::Duration};
+
+use async_trait::async_trait;
+use tokio::{
+    fs,
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::{TcpStream, UnixStream},
+    time::timeout,
+};
+
+use super::error::AppError;
+
+/// Outcome of scanning a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanResult {
+    Clean,
+    /// The file matched a signature, named by the scanner.
+    Infected(String),
+}
+
+/// Trait implemented by malware scanners.
+#[async_trait]
+pub trait FileScanner: Send + Sync {
+    /// Scans the file at `path`. Errors mean the file could not be scanned, not that it
+    /// is infected; callers reject the upload either way.
+    async fn scan(&self, path: &Path) -> Result<ScanResult, AppError>;
+}
+
+/// Scanner that accepts every file.
+pub struct NoopScanner;
+
+#[async_trait]
+impl FileScanner for NoopScanner {
+    async fn scan(&self, _path: &Path) -> Result<ScanResult, AppError> {
+        Ok(ScanResult::Clean)
+    }
+}
+
+/// Size of the chunks files are sent to clamd in.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Scanner that sends files to clamd using its `INSTREAM` command.
+pub struct ClamdScanner {
+    /// A Unix socket path such as `/run/clamav/clamd.ctl`, or `host:port` for TCP.
+    address: String,
+    timeout: Duration,
+}
+
+impl ClamdScanner {
+    pub fn new(address: impl Into<String>, timeout: Duration) -> Self {
+        Self {
+            address: address.into(),
+            timeout,
+        }
+    }
+
+    async fn scan_with<S>(&self, mut stream: S, path: &Path) -> Result<ScanResult, AppError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut file = fs::File::open(path).await.map_err(scan_error)?;
+        let mut chunk = vec![0u8; CHUNK_SIZE];
+
+        stream.write_all(b"zINSTREAM\0").await.map_err(scan_error)?;
+        loop {
+            let read = file.read(&mut chunk).await.map_err(scan_error)?;
+            if read == 0 {
+                break;
+            }
+            stream
+                .write_all(&(read as u32).to_be_bytes())
+                .await
+                .map_err(scan_error)?;
+            stream.write_all(&chunk[..read]).await.map_err(scan_error)?;
+        }
+        stream.write_all(&[0; 4]).await.map_err(scan_error)?;
+        stream.flush().await.map_err(scan_error)?;
+
+        let mut reply = Vec::new();
+        stream.read_to_end(&mut reply).await.map_err(scan_error)?;
+        parse_reply(&reply)
+    }
+}
+
+#[async_trait]
+impl FileScanner for ClamdScanner {
+    async fn scan(&self, path: &Path) -> Result<ScanResult, AppError> {
+        let scan = async {
+            if self.address.starts_with('/') {
+                let stream = UnixStream::connect(&self.address)
+                    .await
+                    .map_err(scan_error)?;
+                self.scan_with(stream, path).await
+            } else {
+                let stream = TcpStream::connect(&self.address)
+                    .await
+                    .map_err(scan_error)?;
+                self.scan_with(stream, path).await
+            }
+        };
+        timeout(self.timeout, scan).await.unwrap_or_else(|_| {
+            tracing::error!("Virus scan of {} timed out", path.display());
+            Err(AppError::InternalError)
+        })
+    }
+}
+
+/// Parses replies such as `stream: OK` and `stream: Eicar-Signature FOUND`.
+fn parse_reply(reply: &[u8]) -> Result<ScanResult, AppError> {
+    let reply = String::from_utf8_lossy(reply);
+    let reply = reply.trim_end_matches(['\0', '\n']);
+    let result = reply.strip_prefix("stream: ").unwrap_or(reply);
+
+    if result == "OK" {
+        Ok(ScanResult::Clean)
+    } else if let Some(signature) = result.strip_suffix(" FOUND") {
+        Ok(ScanResult::Infected(signature.to_string()))
+    } else {
+        tracing::error!("Unexpected reply from clamd: {}", reply);
+        Err(AppError::InternalError)
+    }
+}
+
+fn scan_error(err: std::io::Error) -> AppError {
+    tracing::error!("Virus scan failed: {err}");
+    AppError::InternalError
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Accepts one connection, checks the file arrives in `INSTREAM` framing and answers
+    /// with `reply`.
+    async fn fake_clamd(reply: &'static [u8]) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut command = [0u8; 10];
+            socket.read_exact(&mut command).await.unwrap();
+            assert_eq!(&command, b"zINSTREAM\0");
+            let mut received = Vec::new();
+            loop {
+                let length = socket.read_u32().await.unwrap() as usize;
+                if length == 0 {
+                    break;
+                }
+                let mut chunk = vec![0u8; length];
+                socket.read_exact(&mut chunk).await.unwrap();
+                received.extend(chunk);
+            }
+            assert_eq!(received, b"file contents");
+            socket.write_all(reply).await.unwrap();
+        });
+        address
+    }
+
+    #[tokio::test]
+    async fn test_clamd_scanner() {
+        let path = std::env::temp_dir().join(format!("foodzy-scan-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "file contents").unwrap();
+
+        let scanner = ClamdScanner::new(fake_clamd(b"stream: OK\0").await, Duration::from_secs(5));
+        assert_eq!(scanner.scan(&path).await.unwrap(), ScanResult::Clean);
+
+        let address = fake_clamd(b"stream: Eicar-Signature FOUND\0").await;
+        let scanner = ClamdScanner::new(address, Duration::from_secs(5));
+        assert_eq!(
+            scanner.scan(&path).await.unwrap(),
+            ScanResult::Infected("Eicar-Signature".into())
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
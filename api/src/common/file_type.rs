@@ -0,0 +1,277 @@
+//! File types of uploads, detected from their contents rather than the name the client sent.
+//!
+//! Only the formats listed in [`FileType`] can be recognized, so uploads with any other
+//! extension are rejected even if `ASSET_ALLOWED_EXTENSIONS` lets them through. Image
+//! dimensions are read from the file header, which lets oversized images be rejected
+//! before anything tries to decode them.
+
+/// Number of leading bytes [`FileType::detect`] and [`image_dimensions`] need for every
+/// format except JPEG, whose dimensions may come after large metadata segments.
+pub const HEADER_LEN: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileType {
+    Jpeg,
+    Png,
+    Gif,
+    Webp,
+    Pdf,
+}
+
+impl FileType {
+    /// The type a file with the given lower-cased extension must have.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "jpg" | "jpeg" => Some(FileType::Jpeg),
+            "png" => Some(FileType::Png),
+            "gif" => Some(FileType::Gif),
+            "webp" => Some(FileType::Webp),
+            "pdf" => Some(FileType::Pdf),
+            _ => None,
+        }
+    }
+
+    /// Recognizes a file by the magic bytes at its start.
+    pub fn detect(header: &[u8]) -> Option<Self> {
+        if header.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(FileType::Jpeg)
+        } else if header.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(FileType::Png)
+        } else if header.starts_with(b"GIF87a") || header.starts_with(b"GIF89a") {
+            Some(FileType::Gif)
+        } else if header.len() >= 12 && &header[..4] == b"RIFF" && &header[8..12] == b"WEBP" {
+            Some(FileType::Webp)
+        } else if header.starts_with(b"%PDF-") {
+            Some(FileType::Pdf)
+        } else {
+            None
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            FileType::Jpeg => "image/jpeg",
+            FileType::Png => "image/png",
+            FileType::Gif => "image/gif",
+            FileType::Webp => "image/webp",
+            FileType::Pdf => "application/pdf",
+        }
+    }
+
+    pub fn is_image(&self) -> bool {
+        *self != FileType::Pdf
+    }
+}
+
+/// Largest images that are accepted.
+#[derive(Debug, Clone, Copy)]
+pub struct ImageLimits {
+    /// Maximum width and height in pixels.
+    pub max_dimension: u32,
+    /// Maximum width times height. Decoding needs memory in proportion to it, so a small,
+    /// highly compressed file can otherwise exhaust memory.
+    pub max_pixels: u64,
+}
+
+impl ImageLimits {
+    pub fn allows(&self, (width, height): (u32, u32)) -> bool {
+        width <= self.max_dimension
+            && height <= self.max_dimension
+            && width as u64 * height as u64 <= self.max_pixels
+    }
+}
+
+/// Width and height of an image as declared in its header, or `None` if the header is
+/// truncated or malformed. JPEG needs the whole file, the other formats [`HEADER_LEN`] bytes.
+pub fn image_dimensions(file_type: FileType, data: &[u8]) -> Option<(u32, u32)> {
+    match file_type {
+        FileType::Jpeg => jpeg_segments(data)
+            .find(|segment| segment.is_frame_header())
+            .and_then(|segment| {
+                let payload = segment.payload(data);
+                (payload.len() >= 5).then(|| {
+                    (
+                        u16::from_be_bytes([payload[3], payload[4]]) as u32,
+                        u16::from_be_bytes([payload[1], payload[2]]) as u32,
+                    )
+                })
+            }),
+        FileType::Png => (data.len() >= 24 && &data[12..16] == b"IHDR").then(|| {
+            (
+                u32::from_be_bytes([data[16], data[17], data[18], data[19]]),
+                u32::from_be_bytes([data[20], data[21], data[22], data[23]]),
+            )
+        }),
+        FileType::Gif => (data.len() >= 10).then(|| {
+            (
+                u16::from_le_bytes([data[6], data[7]]) as u32,
+                u16::from_le_bytes([data[8], data[9]]) as u32,
+            )
+        }),
+        FileType::Webp => webp_dimensions(data),
+        FileType::Pdf => None,
+    }
+}
+
+fn webp_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let le24 = |b: &[u8]| b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16;
+    match data.get(12..16)? {
+        // Lossy: a 14-bit width and height after the frame tag and start code.
+        b"VP8 " => {
+            let b = data.get(26..30)?;
+            Some((
+                u16::from_le_bytes([b[0], b[1]]) as u32 & 0x3fff,
+                u16::from_le_bytes([b[2], b[3]]) as u32 & 0x3fff,
+            ))
+        }
+        // Lossless: width and height minus one, packed into 14 bits each after the signature.
+        b"VP8L" => {
+            let b = data.get(21..25)?;
+            let bits = u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+            Some(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+        }
+        // Extended: canvas width and height minus one, 24 bits each.
+        b"VP8X" => Some((le24(data.get(24..27)?) + 1, le24(data.get(27..30)?) + 1)),
+        _ => None,
+    }
+}
+
+/// Removes the APP1 (Exif, XMP) and APP13 (IPTC) segments of a JPEG file, which carry
+/// camera details and GPS coordinates. Image data and colour profiles are kept.
+/// Returns `None` if there was nothing to remove.
+///
+/// The Exif orientation goes with the rest, so photos that relied on it show rotated.
+pub fn strip_jpeg_metadata(data: &[u8]) -> Option<Vec<u8>> {
+    let mut stripped = Vec::with_capacity(data.len());
+    stripped.extend_from_slice(&data[..2.min(data.len())]);
+    let mut copied = stripped.len();
+    let mut removed = false;
+
+    for segment in jpeg_segments(data) {
+        if segment.is_metadata() {
+            stripped.extend_from_slice(&data[copied..segment.start]);
+            copied = segment.end;
+            removed = true;
+        }
+    }
+    if !removed {
+        return None;
+    }
+    stripped.extend_from_slice(&data[copied..]);
+    Some(stripped)
+}
+
+/// A marker segment of a JPEG file, spanning `start..end` including the marker itself.
+struct JpegSegment {
+    marker: u8,
+    start: usize,
+    end: usize,
+}
+
+impl JpegSegment {
+    /// Start-of-frame markers, which hold the image dimensions.
+    fn is_frame_header(&self) -> bool {
+        matches!(self.marker, 0xC0..=0xCF) && !matches!(self.marker, 0xC4 | 0xC8 | 0xCC)
+    }
+
+    fn is_metadata(&self) -> bool {
+        matches!(self.marker, 0xE1 | 0xED)
+    }
+
+    fn payload<'a>(&self, data: &'a [u8]) -> &'a [u8] {
+        &data[self.start + 4..self.end]
+    }
+}
+
+/// Iterates over the segments before the compressed image data, stopping at the
+/// start-of-scan marker or at the first malformed segment.
+fn jpeg_segments(data: &[u8]) -> impl Iterator<Item = JpegSegment> + '_ {
+    let mut pos = 2;
+    std::iter::from_fn(move || {
+        // Markers may be preceded by any number of 0xFF fill bytes.
+        while data.get(pos) == Some(&0xFF) && data.get(pos + 1) == Some(&0xFF) {
+            pos += 1;
+        }
+        if data.get(pos) != Some(&0xFF) {
+            return None;
+        }
+        let marker = *data.get(pos + 1)?;
+        if marker == 0xDA || marker == 0xD9 {
+            return None;
+        }
+        let length = u16::from_be_bytes([*data.get(pos + 2)?, *data.get(pos + 3)?]) as usize;
+        if length < 2 || pos + 2 + length > data.len() {
+            return None;
+        }
+        let segment = JpegSegment {
+            marker,
+            start: pos,
+            end: pos + 2 + length,
+        };
+        pos = segment.end;
+        Some(segment)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(marker: u8, payload: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0xFF, marker];
+        bytes.extend_from_slice(&(payload.len() as u16 + 2).to_be_bytes());
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+
+    #[test]
+    fn test_jpeg_dimensions_and_metadata() {
+        let jfif = segment(0xE0, b"JFIF\0\x01\x01\0\0\x01\0\x01\0\0");
+        let frame = segment(0xC0, &[8, 0x01, 0xE0, 0x02, 0x80, 1, 1, 0x11, 0]);
+        let scan = [0xFF, 0xDA, 0x00, 0x02, 0x12, 0x34, 0xFF, 0xD9];
+
+        let mut jpeg = vec![0xFF, 0xD8];
+        jpeg.extend(&jfif);
+        jpeg.extend(segment(0xE1, b"Exif\0\0GPS 52.37N 4.89E"));
+        jpeg.extend(&frame);
+        jpeg.extend(segment(0xED, b"Photoshop 3.0\0"));
+        jpeg.extend(scan);
+
+        assert_eq!(FileType::detect(&jpeg), Some(FileType::Jpeg));
+        assert_eq!(image_dimensions(FileType::Jpeg, &jpeg), Some((640, 480)));
+
+        let stripped = strip_jpeg_metadata(&jpeg).unwrap();
+        let mut expected = vec![0xFF, 0xD8];
+        expected.extend(jfif);
+        expected.extend(frame);
+        expected.extend(scan);
+        assert_eq!(stripped, expected);
+        assert_eq!(strip_jpeg_metadata(&stripped), None);
+
+        let limits = ImageLimits {
+            max_dimension: 1000,
+            max_pixels: 300_000,
+        };
+        assert!(!limits.allows((640, 480)));
+        assert!(limits.allows((500, 500)));
+    }
+
+    #[test]
+    fn test_header_dimensions() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend(300u32.to_be_bytes());
+        png.extend(200u32.to_be_bytes());
+        assert_eq!(FileType::detect(&png), Some(FileType::Png));
+        assert_eq!(image_dimensions(FileType::Png, &png), Some((300, 200)));
+
+        let gif = b"GIF89a\x2c\x01\xc8\x00";
+        assert_eq!(image_dimensions(FileType::Gif, gif), Some((300, 200)));
+
+        let mut webp = b"RIFF\0\0\0\0WEBPVP8X\x0a\0\0\0\0\0\0\0".to_vec();
+        webp.extend([0x2b, 0x01, 0x00, 0xc7, 0x00, 0x00]);
+        assert_eq!(FileType::detect(&webp), Some(FileType::Webp));
+        assert_eq!(image_dimensions(FileType::Webp, &webp), Some((300, 200)));
+
+        assert_eq!(FileType::detect(b"<svg"), None);
+    }
+}
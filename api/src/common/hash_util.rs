@@ -2,6 +2,15 @@ use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Argon2,
 };
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::sync::LazyLock;
+
+/// Hash of a random password, verified against when a login names an unknown user
+/// so that rejecting it takes as long as rejecting a wrong password.
+static DUMMY_PASSWORD_HASH: LazyLock<String> =
+    LazyLock::new(|| hash_password(&generate_token()).unwrap_or_default());
 
 /// Hash the provided password using Argon2.
 pub fn hash_password(password: &str) -> Result<String, argon2::Error> {
@@ -35,6 +44,27 @@ pub fn verify_password(password_hash: &str, password: &str) -> bool {
         .is_ok()
 }
 
+/// Spend the same time as `verify_password` without a stored hash to compare against.
+pub fn verify_dummy_password(password: &str) {
+    let _ = verify_password(&DUMMY_PASSWORD_HASH, password);
+}
+
+/// Generate an opaque, URL-safe random token with 256 bits of entropy.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Hash a high-entropy token with SHA-256 for storage, returning it hex encoded.
+/// Unlike passwords, random tokens do not need a slow, salted hash.
+pub fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -56,4 +86,16 @@ mod tests {
         let hash = "$argon2i$v=19$m=65536,t=2,p=1$vNVL5PZ1hRwgLUlGmCQVTA$fg1d0/f8pdtMnzQTeh2YE6R0E8vfqMOQOs5k6Y22Qi0";
         assert!(verify_password(hash, password));
     }
+
+    #[test]
+    fn test_generate_and_hash_token() {
+        let token = generate_token();
+        assert_eq!(token.len(), 43);
+        assert_ne!(token, generate_token());
+
+        let hash = hash_token(&token);
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, hash_token(&token));
+        assert_ne!(hash, hash_token(&generate_token()));
+    }
 }
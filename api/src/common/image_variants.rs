@@ -0,0 +1,205 @@
+//! Resized variants of uploaded images.
+//!
+//! Variants are generated once at upload time so clients can pick the size they display
+//! instead of scaling the original. Images are only ever scaled down and keep their aspect
+//! ratio. Decoding and encoding are CPU-bound; run [`generate_variants`] on a blocking thread.
+
+use std::io::Cursor;
+
+use image::{
+    codecs::{jpeg::JpegEncoder, webp::WebPEncoder},
+    imageops::FilterType,
+    DynamicImage, ImageReader, Limits, Rgb, RgbImage,
+};
+
+use super::{error::AppError, file_type::ImageLimits};
+
+const JPEG_QUALITY: u8 = 85;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariantFormat {
+    /// Transparent areas are flattened onto white.
+    Jpeg,
+    /// Lossless, keeping transparency.
+    Webp,
+}
+
+impl VariantFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            VariantFormat::Jpeg => "jpg",
+            VariantFormat::Webp => "webp",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            VariantFormat::Jpeg => "image/jpeg",
+            VariantFormat::Webp => "image/webp",
+        }
+    }
+}
+
+/// A size an image is made available in.
+#[derive(Debug)]
+pub struct Variant {
+    /// Appended to the key of the original, e.g. `3f/3f2a…-thumbnail.jpg`.
+    pub name: &'static str,
+    /// Maximum width and height in pixels.
+    pub max_size: u32,
+    pub format: VariantFormat,
+}
+
+/// An encoded variant, ready to be stored.
+#[derive(Debug)]
+pub struct EncodedVariant {
+    /// Key of the original with the variant name appended, e.g. `3f/3f2a…-thumbnail.jpg`.
+    pub key: String,
+    pub content_type: &'static str,
+    pub data: Vec<u8>,
+}
+
+/// The variants generated for an image.
+#[derive(Debug)]
+pub struct GeneratedVariants {
+    /// Width of the original in pixels.
+    pub width: u32,
+    /// Height of the original in pixels.
+    pub height: u32,
+    /// The variants, in the order they were requested.
+    pub variants: Vec<EncodedVariant>,
+}
+
+/// Decodes `data`, the image stored under `key`, and encodes the requested variants of it.
+pub fn generate_variants(
+    key: &str,
+    data: &[u8],
+    variants: &[Variant],
+    limits: ImageLimits,
+) -> Result<GeneratedVariants, AppError> {
+    let image = decode(key, data, limits)?;
+    let stem = key.rsplit_once('.').map_or(key, |(stem, _)| stem);
+
+    let variants = variants
+        .iter()
+        .map(|variant| {
+            let key = format!("{stem}-{}.{}", variant.name, variant.format.extension());
+            Ok(EncodedVariant {
+                data: encode_variant(&image, variant, &key)?,
+                content_type: variant.format.content_type(),
+                key,
+            })
+        })
+        .collect::<Result<_, AppError>>()?;
+
+    Ok(GeneratedVariants {
+        width: image.width(),
+        height: image.height(),
+        variants,
+    })
+}
+
+fn decode(key: &str, data: &[u8], limits: ImageLimits) -> Result<DynamicImage, AppError> {
+    let mut decoder_limits = Limits::default();
+    decoder_limits.max_image_width = Some(limits.max_dimension);
+    decoder_limits.max_image_height = Some(limits.max_dimension);
+    // Room for the decoded pixels at up to 16 bits per RGBA channel.
+    decoder_limits.max_alloc = Some(limits.max_pixels.saturating_mul(8));
+
+    let mut reader = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|err| {
+            tracing::error!("Error opening image {key}: {err}");
+            AppError::InternalError
+        })?;
+    reader.limits(decoder_limits);
+    reader.decode().map_err(|err| {
+        tracing::error!("Error decoding image {key}: {err}");
+        AppError::InvalidFileData
+    })
+}
+
+fn encode_variant(image: &DynamicImage, variant: &Variant, key: &str) -> Result<Vec<u8>, AppError> {
+    let resized = if image.width() > variant.max_size || image.height() > variant.max_size {
+        image.resize(variant.max_size, variant.max_size, FilterType::Lanczos3)
+    } else {
+        image.clone()
+    };
+
+    let mut data = Vec::new();
+    let written = match variant.format {
+        VariantFormat::Jpeg => DynamicImage::ImageRgb8(flatten(&resized))
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut data, JPEG_QUALITY)),
+        VariantFormat::Webp => DynamicImage::ImageRgba8(resized.to_rgba8())
+            .write_with_encoder(WebPEncoder::new_lossless(&mut data)),
+    };
+
+    written.map(|()| data).map_err(|err| {
+        tracing::error!("Error encoding image variant {key}: {err}");
+        AppError::InternalError
+    })
+}
+
+/// Blends transparent pixels with a white background.
+fn flatten(image: &DynamicImage) -> RgbImage {
+    if !image.color().has_alpha() {
+        return image.to_rgb8();
+    }
+    let rgba = image.to_rgba8();
+    RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let blend = |c: u8| ((c as u16 * a as u16 + 255 * (255 - a as u16)) / 255) as u8;
+        Rgb([blend(r), blend(g), blend(b)])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, Rgba, RgbaImage};
+
+    #[test]
+    fn test_generate_variants() {
+        let mut png = Vec::new();
+        RgbaImage::from_pixel(400, 200, Rgba([200, 40, 40, 128]))
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        let variants = [
+            Variant {
+                name: "thumbnail",
+                max_size: 100,
+                format: VariantFormat::Jpeg,
+            },
+            Variant {
+                name: "detail",
+                max_size: 1000,
+                format: VariantFormat::Webp,
+            },
+        ];
+        let limits = ImageLimits {
+            max_dimension: 1000,
+            max_pixels: 1_000_000,
+        };
+        let generated = generate_variants("ab/abc.png", &png, &variants, limits).unwrap();
+        assert_eq!((generated.width, generated.height), (400, 200));
+        let [thumbnail, detail] = &generated.variants[..] else {
+            panic!("expected two variants");
+        };
+        assert_eq!(thumbnail.key, "ab/abc-thumbnail.jpg");
+        assert_eq!(thumbnail.content_type, "image/jpeg");
+        assert_eq!(detail.key, "ab/abc-detail.webp");
+
+        let thumbnail = image::load_from_memory(&thumbnail.data).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (100, 50));
+        let detail = image::load_from_memory(&detail.data).unwrap();
+        assert_eq!((detail.width(), detail.height()), (400, 200));
+        assert!(detail.color().has_alpha());
+
+        let too_small = ImageLimits {
+            max_dimension: 300,
+            max_pixels: 1_000_000,
+        };
+        assert!(generate_variants("ab/abc.png", &png, &variants, too_small).is_err());
+    }
+}
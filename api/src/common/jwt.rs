@@ -1,55 +1,283 @@
 use axum::{
-    extract::Request,
+    extract::{OriginalUri, Request, State},
     middleware::Next,
     response::{IntoResponse, Response},
 };
 
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{
+    decode, decode_header, encode,
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+        OctetKeyPairParameters, PublicKeyUse, RSAKeyParameters,
+    },
+    Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use ring::{
+    rsa,
+    signature::{Ed25519KeyPair, KeyPair},
+};
 use serde::{Deserialize, Serialize};
-use std::sync::LazyLock;
-use std::{env, fmt::Display};
+use std::fmt::Display;
+use thiserror::Error;
 use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{
+    app_state::AppState,
+    authz::{self, Role},
+    config::Config,
+    error::AppError,
+};
+
+/// Header carrying an API key, accepted by `jwt_auth` in place of a Bearer token.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Error raised while loading the JWT signing keys at startup.
+#[derive(Debug, Error)]
+pub enum JwtKeyError {
+    #[error("Invalid JWT_KEYS entry `{0}`, expected `kid:alg:path`")]
+    InvalidEntry(String),
+
+    #[error("Unsupported algorithm `{alg}` for key `{kid}`, expected RS256 or EdDSA")]
+    UnsupportedAlgorithm { kid: String, alg: String },
 
-use super::error::AppError;
+    #[error("Duplicate JWT key id `{0}`")]
+    DuplicateKid(String),
 
-/// JWT_SECRET_KEY is the environment variable that holds the secret key for JWT encoding and decoding.
-/// It is loaded from the environment variables using the dotenv crate.
-/// The secret key is used to sign the JWT tokens and should be kept secret.
-pub static KEYS: LazyLock<Keys> = LazyLock::new(|| {
-    dotenvy::dotenv().ok();
+    #[error("Failed to read JWT key `{kid}`: {source}")]
+    Io {
+        kid: String,
+        #[source]
+        source: std::io::Error,
+    },
 
-    let secret = env::var("JWT_SECRET_KEY").expect("JWT_SECRET_KEY must be set");
-    Keys::new(secret.as_bytes())
-});
+    #[error("Invalid private key for JWT key `{kid}`: {reason}")]
+    InvalidKey { kid: String, reason: String },
 
-/// Keys is a struct that holds the encoding and decoding keys for JWT.
-pub struct Keys {
-    pub encoding: EncodingKey,
-    pub decoding: DecodingKey,
+    #[error("JWT_ACTIVE_KID `{0}` is not one of the configured keys")]
+    UnknownActiveKid(String),
 }
 
-/// The Keys struct is used to create the encoding and decoding keys for JWT.
-impl Keys {
-    fn new(secret: &[u8]) -> Self {
-        Self {
-            encoding: EncodingKey::from_secret(secret),
-            decoding: DecodingKey::from_secret(secret),
+/// A single signing key identified by its `kid`.
+struct JwtKey {
+    kid: String,
+    algorithm: Algorithm,
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    jwk: Jwk,
+}
+
+impl JwtKey {
+    /// Loads a PEM encoded private key and derives its public half.
+    /// RSA keys may be PKCS#1 or PKCS#8, Ed25519 keys must be PKCS#8.
+    fn from_pem(kid: &str, algorithm: Algorithm, pem: &[u8]) -> Result<Self, JwtKeyError> {
+        let invalid = |reason: String| JwtKeyError::InvalidKey {
+            kid: kid.to_string(),
+            reason,
+        };
+        let parsed = pem::parse(pem).map_err(|err| invalid(err.to_string()))?;
+
+        let (encoding, decoding, params, key_algorithm) = match algorithm {
+            Algorithm::RS256 => {
+                let key_pair = match parsed.tag() {
+                    "RSA PRIVATE KEY" => rsa::KeyPair::from_der(parsed.contents()),
+                    "PRIVATE KEY" => rsa::KeyPair::from_pkcs8(parsed.contents()),
+                    tag => return Err(invalid(format!("unexpected PEM block `{tag}`"))),
+                }
+                .map_err(|err| invalid(err.to_string()))?;
+                let public: rsa::PublicKeyComponents<Vec<u8>> = key_pair.public().into();
+
+                (
+                    EncodingKey::from_rsa_pem(pem).map_err(|err| invalid(err.to_string()))?,
+                    DecodingKey::from_rsa_raw_components(&public.n, &public.e),
+                    AlgorithmParameters::RSA(RSAKeyParameters {
+                        key_type: Default::default(),
+                        n: URL_SAFE_NO_PAD.encode(&public.n),
+                        e: URL_SAFE_NO_PAD.encode(&public.e),
+                    }),
+                    KeyAlgorithm::RS256,
+                )
+            }
+            Algorithm::EdDSA => {
+                if parsed.tag() != "PRIVATE KEY" {
+                    return Err(invalid(format!(
+                        "unexpected PEM block `{}`, expected PKCS#8",
+                        parsed.tag()
+                    )));
+                }
+                let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(parsed.contents())
+                    .map_err(|err| invalid(err.to_string()))?;
+                let public = key_pair.public_key().as_ref();
+
+                (
+                    EncodingKey::from_ed_pem(pem).map_err(|err| invalid(err.to_string()))?,
+                    DecodingKey::from_ed_der(public),
+                    AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                        key_type: Default::default(),
+                        curve: EllipticCurve::Ed25519,
+                        x: URL_SAFE_NO_PAD.encode(public),
+                    }),
+                    KeyAlgorithm::EdDSA,
+                )
+            }
+            _ => {
+                return Err(JwtKeyError::UnsupportedAlgorithm {
+                    kid: kid.to_string(),
+                    alg: format!("{algorithm:?}"),
+                })
+            }
+        };
+
+        Ok(Self {
+            kid: kid.to_string(),
+            algorithm,
+            encoding,
+            decoding,
+            jwk: Jwk {
+                common: CommonParameters {
+                    public_key_use: Some(PublicKeyUse::Signature),
+                    key_algorithm: Some(key_algorithm),
+                    key_id: Some(kid.to_string()),
+                    ..Default::default()
+                },
+                algorithm: params,
+            },
+        })
+    }
+}
+
+/// JwtKeys holds every key accepted for access tokens and the one used to sign new ones.
+///
+/// Tokens carry the `kid` of their signing key, so keys can be rotated without logging
+/// everyone out: add the new key, make it active, and drop the old one from `JWT_KEYS`
+/// once the tokens it signed have expired. Dropped keys are retired and no longer accepted.
+pub struct JwtKeys {
+    keys: Vec<JwtKey>,
+    active: usize,
+}
+
+impl JwtKeys {
+    /// Loads the keys listed in `JWT_KEYS` and selects `JWT_ACTIVE_KID` for signing.
+    pub fn from_config(config: &Config) -> Result<Self, JwtKeyError> {
+        let mut entries = Vec::new();
+        for entry in config
+            .jwt_keys
+            .split(',')
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+        {
+            let mut parts = entry.splitn(3, ':').map(str::trim);
+            let (Some(kid), Some(alg), Some(path)) = (parts.next(), parts.next(), parts.next())
+            else {
+                return Err(JwtKeyError::InvalidEntry(entry.to_string()));
+            };
+            if kid.is_empty() || path.is_empty() {
+                return Err(JwtKeyError::InvalidEntry(entry.to_string()));
+            }
+
+            let algorithm = match alg {
+                "RS256" => Algorithm::RS256,
+                "EdDSA" => Algorithm::EdDSA,
+                _ => {
+                    return Err(JwtKeyError::UnsupportedAlgorithm {
+                        kid: kid.to_string(),
+                        alg: alg.to_string(),
+                    })
+                }
+            };
+            let pem = std::fs::read(path).map_err(|source| JwtKeyError::Io {
+                kid: kid.to_string(),
+                source,
+            })?;
+            entries.push((kid.to_string(), algorithm, pem));
+        }
+
+        Self::from_pems(entries, &config.jwt_active_kid)
+    }
+
+    /// Builds the key set from `(kid, algorithm, private key PEM)` entries.
+    pub fn from_pems(
+        entries: Vec<(String, Algorithm, Vec<u8>)>,
+        active_kid: &str,
+    ) -> Result<Self, JwtKeyError> {
+        let mut keys: Vec<JwtKey> = Vec::with_capacity(entries.len());
+        for (kid, algorithm, pem) in entries {
+            if keys.iter().any(|k| k.kid == kid) {
+                return Err(JwtKeyError::DuplicateKid(kid));
+            }
+            keys.push(JwtKey::from_pem(&kid, algorithm, &pem)?);
+        }
+
+        let active = keys
+            .iter()
+            .position(|k| k.kid == active_kid)
+            .ok_or_else(|| JwtKeyError::UnknownActiveKid(active_kid.to_string()))?;
+
+        Ok(Self { keys, active })
+    }
+
+    /// Signs the claims with the active key, recording its `kid` in the header.
+    pub fn encode(&self, claims: &Claims) -> Result<String, AppError> {
+        let key = &self.keys[self.active];
+        let mut header = Header::new(key.algorithm);
+        header.kid = Some(key.kid.clone());
+        encode(&header, claims, &key.encoding).map_err(|_| AppError::TokenCreation)
+    }
+
+    /// Validates a token against the key named by its `kid`.
+    /// The algorithm is taken from the key, never from the token header.
+    pub fn decode(&self, token: &str) -> Result<Claims, AppError> {
+        let header = decode_header(token).map_err(|_| AppError::InvalidToken)?;
+        let key = header
+            .kid
+            .and_then(|kid| self.keys.iter().find(|k| k.kid == kid))
+            .ok_or(AppError::InvalidToken)?;
+
+        decode::<Claims>(token, &key.decoding, &Validation::new(key.algorithm))
+            .map(|data| data.claims)
+            .map_err(|err| {
+                tracing::error!("Error decoding token: {:?}", err);
+                AppError::InvalidToken
+            })
+    }
+
+    /// Returns the public keys as a JSON Web Key Set.
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self.keys.iter().map(|k| k.jwk.clone()).collect(),
         }
     }
 }
 
 /// Claims is a struct that represents the claims in the JWT token.
-/// It contains the subject (user ID), expiration time, and issued at time.
+/// It contains the subject (user ID), the user's role, whether their email address is verified,
+/// the session the token belongs to, expiration time, and issued at time.
 /// The `sub` field is the user ID, `exp` is the expiration time, and `iat` is the issued at time.
 /// The `Claims` struct is used to encode and decode the JWT tokens.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
+    #[serde(default)]
+    pub role: Role,
+    #[serde(default)]
+    pub email_verified: bool,
+    /// Session ID, checked against the revoked sessions on every request.
+    /// Absent only for requests authenticated with an API key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<Uuid>,
     pub exp: usize,
     pub iat: usize,
 }
 
+impl Claims {
+    /// Parses the subject claim into the numeric user ID.
+    pub fn user_id(&self) -> Result<i32, AppError> {
+        self.sub.parse().map_err(|_| AppError::InvalidToken)
+    }
+}
+
 /// The Claims struct implements the `Display` trait for easy printing.
 /// It formats the claims as a string, showing the user ID.
 impl Display for Claims {
@@ -59,35 +287,41 @@ impl Display for Claims {
 }
 
 /// The Default trait is implemented for the Claims struct.
-/// It sets the default values for the claims.
+/// It sets the default values for the claims; `exp` is the issue time,
+/// so tokens get their lifetime from `make_jwt_token`.
 impl Default for Claims {
     fn default() -> Self {
-        let now = Utc::now();
-        let expire: Duration = Duration::hours(24);
-        let exp: usize = (now + expire).timestamp() as usize;
-        let iat: usize = now.timestamp() as usize;
+        let now = Utc::now().timestamp() as usize;
         Claims {
             sub: String::new(),
-            exp,
-            iat,
+            role: Role::default(),
+            email_verified: false,
+            sid: None,
+            exp: now,
+            iat: now,
         }
     }
 }
 
 /// AuthBody is a struct that represents the authentication body.
+/// `expires_in` is the lifetime of the access token in seconds.
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct AuthBody {
     pub access_token: String,
     pub token_type: String,
+    pub expires_in: i64,
+    pub refresh_token: String,
 }
 
 /// The AuthBody struct is used to create a new instance of the authentication body.
-/// It takes an access token as a parameter and sets the token type to "Bearer".
+/// It takes the tokens and the access token lifetime as parameters and sets the token type to "Bearer".
 impl AuthBody {
-    pub fn new(access_token: String) -> Self {
+    pub fn new(access_token: String, refresh_token: String, expires_in: i64) -> Self {
         Self {
             access_token,
             token_type: "Bearer".to_string(),
+            expires_in,
+            refresh_token,
         }
     }
 }
@@ -101,21 +335,66 @@ pub struct AuthPayload {
 }
 
 /// make_jwt_token is a function that creates a JWT token.
-/// It takes a user ID as a parameter and returns a Result with the JWT token or an error.
-pub fn make_jwt_token(user_id: &i32) -> Result<String, AppError> {
+/// It takes the key set, a user ID, role, email verification state, session ID and lifetime
+/// in seconds as parameters and returns a Result with the JWT token or an error.
+pub fn make_jwt_token(
+    keys: &JwtKeys,
+    user_id: &i32,
+    role: Role,
+    email_verified: bool,
+    session_id: Uuid,
+    ttl_secs: i64,
+) -> Result<String, AppError> {
+    let iat = Utc::now();
     let claims = Claims {
         sub: user_id.to_string(),
-        ..Default::default()
+        role,
+        email_verified,
+        sid: Some(session_id),
+        exp: (iat + Duration::seconds(ttl_secs)).timestamp() as usize,
+        iat: iat.timestamp() as usize,
     };
-    encode(&Header::default(), &claims, &KEYS.encoding).map_err(|_| AppError::TokenCreation)
+    keys.encode(&claims)
 }
 
-/// Middleware to validate JWT tokens.
-/// If the token is valid, the request proceeds; otherwise, a 401 Unauthorized is returned.
-pub async fn jwt_auth<B>(mut req: Request<B>, next: Next) -> Result<Response, Response>
-where
-    B: Send + Into<axum::body::Body>,
-{
+/// Middleware to validate JWT tokens or API keys.
+/// If the token is valid and its session has not been revoked, the request proceeds;
+/// otherwise, a 401 Unauthorized is returned.
+/// API keys are also checked against the scope the route requires and get a 403 without it.
+pub async fn jwt_auth(
+    State(state): State<AppState>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, Response> {
+    if let Some(api_key) = req
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|k| k.trim())
+    {
+        // Scopes are derived from the full path, which nested routers strip from the URI.
+        let path = req
+            .extensions()
+            .get::<OriginalUri>()
+            .map_or_else(|| req.uri().path(), |uri| uri.path());
+        let scope = authz::required_scope(req.method(), path)
+            .ok_or_else(|| AppError::Forbidden.into_response())?;
+
+        let auth_user = state
+            .api_key_service
+            .authenticate(api_key, scope)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        req.extensions_mut().insert(Claims {
+            sub: auth_user.user_id.to_string(),
+            role: auth_user.role,
+            email_verified: auth_user.email_verified,
+            ..Default::default()
+        });
+        return Ok(next.run(req).await);
+    }
+
     // Try to extract and trim the token in one go.
     let token = req
         .headers()
@@ -127,13 +406,79 @@ where
         .ok_or_else(|| AppError::InvalidToken.into_response())?;
 
     // Validate and decode the token.
-    let token_data =
-        decode::<Claims>(token, &KEYS.decoding, &Validation::default()).map_err(|err| {
-            tracing::error!("Error decoding token: {:?}", err);
-            AppError::InvalidToken.into_response()
-        })?;
+    let claims = state
+        .jwt_keys
+        .decode(token)
+        .map_err(IntoResponse::into_response)?;
+
+    // Reject tokens of revoked sessions, and tokens issued before sessions were tracked.
+    let session_id = claims
+        .sid
+        .ok_or_else(|| AppError::InvalidToken.into_response())?;
+    let active = state
+        .auth_service
+        .is_session_active(session_id)
+        .await
+        .map_err(IntoResponse::into_response)?;
+    if !active {
+        return Err(AppError::InvalidToken.into_response());
+    }
 
     // Insert the decoded claims into the request extensions.
-    req.extensions_mut().insert(token_data.claims);
-    Ok(next.run(req.map(Into::into)).await)
+    req.extensions_mut().insert(claims);
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+
+    fn ed25519_pem() -> Vec<u8> {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        pem::encode(&pem::Pem::new("PRIVATE KEY", pkcs8.as_ref().to_vec())).into_bytes()
+    }
+
+    #[test]
+    fn test_rotated_keys_keep_accepting_tokens() {
+        let old_pem = ed25519_pem();
+        let new_pem = ed25519_pem();
+
+        let old_keys = JwtKeys::from_pems(
+            vec![("old".into(), Algorithm::EdDSA, old_pem.clone())],
+            "old",
+        )
+        .unwrap();
+        let session_id = Uuid::new_v4();
+        let token = make_jwt_token(&old_keys, &42, Role::Staff, true, session_id, 900).unwrap();
+
+        // After rotation the old key only verifies; new tokens use the new kid.
+        let rotated = JwtKeys::from_pems(
+            vec![
+                ("old".into(), Algorithm::EdDSA, old_pem),
+                ("new".into(), Algorithm::EdDSA, new_pem.clone()),
+            ],
+            "new",
+        )
+        .unwrap();
+        let claims = rotated.decode(&token).unwrap();
+        assert_eq!(claims.user_id().unwrap(), 42);
+        assert_eq!(claims.role, Role::Staff);
+        assert!(claims.email_verified);
+        assert_eq!(claims.sid, Some(session_id));
+        assert_eq!(claims.exp - claims.iat, 900);
+        assert_eq!(rotated.jwks().keys.len(), 2);
+
+        let new_token = make_jwt_token(&rotated, &42, Role::Staff, true, session_id, 900).unwrap();
+        assert_eq!(
+            decode_header(&new_token).unwrap().kid.as_deref(),
+            Some("new")
+        );
+
+        // Once the old key is retired its tokens are rejected.
+        let retired =
+            JwtKeys::from_pems(vec![("new".into(), Algorithm::EdDSA, new_pem)], "new").unwrap();
+        assert!(retired.decode(&token).is_err());
+        assert!(retired.decode(&new_token).is_ok());
+    }
 }
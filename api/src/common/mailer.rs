@@ -0,0 +1,82 @@
+//! Outgoing email delivery.
+//!
+//! Services send mail through the [`Mailer`] trait so the transport can be swapped
+//! without touching business logic. [`FileMailer`] is meant for local development:
+//! it writes every message to an outbox directory and logs where it went.
+
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use uuid::Uuid;
+
+use super::error::AppError;
+
+/// A plain-text email message.
+#[derive(Debug, Clone)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Trait implemented by email transports.
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    /// Delivers the message or returns an error if the transport rejected it.
+    async fn send(&self, message: EmailMessage) -> Result<(), AppError>;
+}
+
+/// Mailer that stores each message as an `.eml` file in the outbox directory.
+pub struct FileMailer {
+    from: String,
+    outbox_path: PathBuf,
+}
+
+impl FileMailer {
+    pub fn new(from: impl Into<String>, outbox_path: impl Into<PathBuf>) -> Self {
+        Self {
+            from: from.into(),
+            outbox_path: outbox_path.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, message: EmailMessage) -> Result<(), AppError> {
+        let now = Utc::now();
+        let path = self.outbox_path.join(format!(
+            "{}-{}.eml",
+            now.format("%Y%m%dT%H%M%S"),
+            Uuid::new_v4()
+        ));
+        let contents = format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\n\r\n{}\r\n",
+            self.from,
+            message.to,
+            message.subject,
+            now.to_rfc2822(),
+            message.body
+        );
+
+        tokio::fs::create_dir_all(&self.outbox_path)
+            .await
+            .map_err(|err| {
+                tracing::error!("Error creating mail outbox: {err}");
+                AppError::InternalError
+            })?;
+        tokio::fs::write(&path, contents).await.map_err(|err| {
+            tracing::error!("Error writing email: {err}");
+            AppError::InternalError
+        })?;
+
+        tracing::info!(
+            "Email \"{}\" to {} written to {}",
+            message.subject,
+            message.to,
+            path.display()
+        );
+        Ok(())
+    }
+}
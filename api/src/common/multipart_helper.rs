@@ -1,92 +1,446 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, path::Path};
 
-use crate::{app::FORBIDDEN_PATTERNS, common::error::AppError};
+use axum::{
+    extract::{
+        multipart::{Field, MultipartError},
+        Multipart,
+    },
+    http::StatusCode,
+};
+use regex::Regex;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tokio::{fs, io::AsyncWriteExt};
+use utoipa::ToSchema;
+use uuid::Uuid;
 
-async fn parse_multipart_internal(
-    mut multipart: axum::extract::Multipart,
-    asset_allowed_extensions_pattern: &regex::Regex,
-) -> Result<std::collections::HashMap<String, Vec<String>>, AppError> {
-    let mut fields: HashMap<String, Vec<String>> = HashMap::new();
+use crate::{
+    app::FORBIDDEN_PATTERNS,
+    common::{
+        app_state::AppState,
+        error::AppError,
+        file_scanner::{FileScanner, ScanResult},
+        file_type::{image_dimensions, strip_jpeg_metadata, FileType, ImageLimits, HEADER_LEN},
+        storage::Storage,
+    },
+};
 
-    // Helper closure to map multipart errors to AppError.
-    let map_err_internal = |err| {
-        tracing::error!("Multipart error: {}", err);
-        AppError::InternalError
-    };
+/// Where the files of a multipart request are stored, and the checks they must pass.
+pub struct UploadOptions<'a> {
+    /// Storage the files are moved to once they have passed every check.
+    pub storage: &'a dyn Storage,
+    /// Directory files are written to while they are being checked.
+    pub temp_dir: &'a Path,
+    /// URL the storage is served under.
+    pub base_url: &'a str,
+    pub allowed_extensions: &'a Regex,
+    /// Maximum size of a single file in bytes.
+    pub max_size: usize,
+    pub image_limits: ImageLimits,
+    pub scanner: &'a dyn FileScanner,
+}
 
-    while let Some(field) = multipart.next_field().await.map_err(map_err_internal)? {
-        let name = field
-            .name()
-            .ok_or_else(|| AppError::ValidationError("Field name is missing".to_string()))?
-            .to_string();
+impl<'a> UploadOptions<'a> {
+    /// Stores files in the public storage, served to everyone.
+    pub fn public(state: &'a AppState) -> Self {
+        Self::new(
+            state,
+            state.public_storage.as_ref(),
+            &state.config.assets_public_url,
+        )
+    }
+
+    /// Stores files in the private storage, served to authenticated clients only.
+    pub fn private(state: &'a AppState) -> Self {
+        Self::new(
+            state,
+            state.private_storage.as_ref(),
+            &state.config.assets_private_url,
+        )
+    }
 
-        if FORBIDDEN_PATTERNS.iter().any(|re| re.is_match(&name)) {
-            tracing::error!("Invalid field name: {}", name);
-            return Err(AppError::Forbidden);
+    fn new(state: &'a AppState, storage: &'a dyn Storage, base_url: &'a str) -> Self {
+        let config = &state.config;
+        Self {
+            storage,
+            temp_dir: Path::new(&config.asset_temp_path),
+            base_url,
+            allowed_extensions: &config.asset_allowed_extensions_pattern,
+            max_size: config.asset_max_size,
+            image_limits: ImageLimits {
+                max_dimension: config.asset_max_image_dimension,
+                max_pixels: config.asset_max_image_pixels,
+            },
+            scanner: state.file_scanner.as_ref(),
         }
+    }
+}
 
-        if let Some(filename) = field.file_name() {
-            let original_filename = filename.to_string();
-            if original_filename.contains("..") || original_filename.contains("/") {
-                tracing::error!("Invalid file name: {}", original_filename);
-                return Err(AppError::InvalidFileName);
-            }
-            if FORBIDDEN_PATTERNS
-                .iter()
-                .any(|re| re.is_match(&original_filename))
-            {
-                tracing::error!("Invalid file name: {}", original_filename);
+/// A file part of a multipart request, as stored.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct UploadedFile {
+    /// Name of the form field the file was sent in.
+    pub field: String,
+    /// File name given by the client; only used for display.
+    pub original_name: String,
+    /// Storage key of the file, e.g. `3f/3f2a….png`.
+    pub key: String,
+    /// URL the file is served under.
+    pub url: String,
+    pub size: u64,
+    /// Content type detected from the contents.
+    pub content_type: String,
+    /// Hex-encoded SHA-256 of the contents.
+    pub sha256: String,
+}
+
+/// The text fields and stored files of a multipart request.
+#[derive(Debug, Default)]
+pub struct MultipartForm {
+    /// All values of each text field, in the order they were sent.
+    pub fields: HashMap<String, Vec<String>>,
+    pub files: Vec<UploadedFile>,
+}
+
+impl MultipartForm {
+    /// The last value of a text field.
+    pub fn text(&self, name: &str) -> Option<&str> {
+        self.fields
+            .get(name)
+            .and_then(|values| values.last())
+            .map(String::as_str)
+    }
+
+    /// Deletes the stored files, e.g. when the request they belong to fails later on.
+    pub async fn remove_files(&self, storage: &dyn Storage) {
+        remove_files(storage, &self.files).await;
+    }
+}
+
+/// Parses multipart form data, streaming file parts to storage.
+///
+/// Field names, file names and text values are validated. Each file is stored under a
+/// generated key in one of 256 prefixes of `options.storage`, so uploads never collide or
+/// overwrite each other, and is rejected as soon as it exceeds `options.max_size`. A file must also
+/// have contents matching its extension, stay within `options.image_limits` if it is an
+/// image, and pass `options.scanner`; JPEG files are stripped of their metadata.
+/// If any part is rejected, the files stored so far are deleted again.
+pub async fn parse_multipart(
+    mut multipart: Multipart,
+    options: &UploadOptions<'_>,
+) -> Result<MultipartForm, AppError> {
+    let mut form = MultipartForm::default();
+
+    let result = async {
+        while let Some(field) = multipart.next_field().await.map_err(map_multipart_error)? {
+            let name = field
+                .name()
+                .ok_or_else(|| AppError::ValidationError("Field name is missing".to_string()))?
+                .to_string();
+
+            if FORBIDDEN_PATTERNS.iter().any(|re| re.is_match(&name)) {
+                tracing::error!("Invalid field name: {}", name);
                 return Err(AppError::Forbidden);
             }
-            if !asset_allowed_extensions_pattern.is_match(&original_filename) {
-                tracing::error!("Unsupported file extension: {}", original_filename);
-                return Err(AppError::UnsupportedFileExtension);
+
+            if field.file_name().is_some() {
+                let file = store_file(field, name, options).await?;
+                form.files.push(file);
+            } else {
+                let text = field.text().await.map_err(map_multipart_error)?;
+                if FORBIDDEN_PATTERNS.iter().any(|re| re.is_match(&text)) {
+                    tracing::error!("Invalid text field value: {}", text);
+                    return Err(AppError::Forbidden);
+                }
+                form.fields.entry(name).or_default().push(text);
             }
-        } else {
-            let text = field.text().await.map_err(map_err_internal)?;
-            if FORBIDDEN_PATTERNS.iter().any(|re| re.is_match(&text)) {
-                tracing::error!("Invalid text field value: {}", text);
-                return Err(AppError::Forbidden);
+        }
+        Ok(())
+    }
+    .await;
+
+    match result {
+        Ok(()) => Ok(form),
+        Err(err) => {
+            form.remove_files(options.storage).await;
+            Err(err)
+        }
+    }
+}
+
+/// Deletes stored files, logging rather than failing on errors.
+pub async fn remove_files(storage: &dyn Storage, files: &[UploadedFile]) {
+    for file in files {
+        if let Err(err) = storage.delete(&file.key).await {
+            tracing::error!("Error removing uploaded file {}: {err}", file.key);
+        }
+    }
+}
+
+fn map_multipart_error(err: MultipartError) -> AppError {
+    tracing::error!("Multipart error: {}", err);
+    if err.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        return AppError::FileSizeExceeded;
+    }
+    AppError::ValidationError(format!("Invalid multipart data: {}", err.body_text()))
+}
+
+/// Validates the file name of a file part and returns its lower-cased extension.
+fn file_extension(original_name: &str, allowed_extensions: &Regex) -> Result<String, AppError> {
+    if original_name.contains("..") || original_name.contains('/') || original_name.contains('\\') {
+        tracing::error!("Invalid file name: {}", original_name);
+        return Err(AppError::InvalidFileName);
+    }
+    if FORBIDDEN_PATTERNS
+        .iter()
+        .any(|re| re.is_match(original_name))
+    {
+        tracing::error!("Invalid file name: {}", original_name);
+        return Err(AppError::Forbidden);
+    }
+    if !allowed_extensions.is_match(original_name) {
+        tracing::error!("Unsupported file extension: {}", original_name);
+        return Err(AppError::UnsupportedFileExtension);
+    }
+    original_name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_ascii_lowercase())
+        .ok_or(AppError::UnsupportedFileExtension)
+}
+
+/// Streams a file part to a temporary file and moves it into storage once it has passed
+/// every check.
+async fn store_file(
+    mut field: Field<'_>,
+    name: String,
+    options: &UploadOptions<'_>,
+) -> Result<UploadedFile, AppError> {
+    let original_name = field.file_name().unwrap_or_default().to_string();
+    let extension = file_extension(&original_name, options.allowed_extensions)?;
+    let expected_type =
+        FileType::from_extension(&extension).ok_or(AppError::UnsupportedFileExtension)?;
+
+    // Spread files over 256 prefixes so no single directory grows too large.
+    let id = Uuid::new_v4().simple().to_string();
+    let key = format!("{}/{}.{}", &id[..2], id, extension);
+    let temp_path = options.temp_dir.join(format!("{id}.part"));
+
+    fs::create_dir_all(options.temp_dir)
+        .await
+        .map_err(map_io_error)?;
+    let mut file = fs::File::create(&temp_path).await.map_err(map_io_error)?;
+
+    let checked = async {
+        let mut hasher = Sha256::new();
+        let mut size: usize = 0;
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        while let Some(chunk) = field.chunk().await.map_err(map_multipart_error)? {
+            size += chunk.len();
+            if size > options.max_size {
+                tracing::error!(
+                    "Uploaded file {} exceeds {} bytes",
+                    original_name,
+                    options.max_size
+                );
+                return Err(AppError::FileSizeExceeded);
+            }
+            if header.len() < HEADER_LEN {
+                let missing = (HEADER_LEN - header.len()).min(chunk.len());
+                header.extend_from_slice(&chunk[..missing]);
+                // Reject a mismatch before the rest of the file is received.
+                if header.len() == HEADER_LEN {
+                    check_type(&header, expected_type, &original_name)?;
+                }
             }
-            fields.entry(name).or_default().push(text);
+            hasher.update(&chunk);
+            file.write_all(&chunk).await.map_err(map_io_error)?;
+        }
+        if size == 0 {
+            return Err(AppError::InvalidFileData);
         }
+        if header.len() < HEADER_LEN {
+            check_type(&header, expected_type, &original_name)?;
+        }
+        file.flush().await.map_err(map_io_error)?;
+
+        let mut size = size as u64;
+        let mut sha256 = format!("{:x}", hasher.finalize());
+        if expected_type == FileType::Jpeg {
+            // JPEG dimensions may follow large metadata segments, so the whole file is needed.
+            let data = fs::read(&temp_path).await.map_err(map_io_error)?;
+            check_dimensions(expected_type, &data, options.image_limits, &original_name)?;
+            if let Some(stripped) = strip_jpeg_metadata(&data) {
+                fs::write(&temp_path, &stripped)
+                    .await
+                    .map_err(map_io_error)?;
+                size = stripped.len() as u64;
+                sha256 = format!("{:x}", Sha256::digest(&stripped));
+            }
+        } else if expected_type.is_image() {
+            check_dimensions(expected_type, &header, options.image_limits, &original_name)?;
+        }
+
+        match options.scanner.scan(&temp_path).await? {
+            ScanResult::Clean => Ok((size, sha256)),
+            ScanResult::Infected(signature) => {
+                tracing::error!("Uploaded file {} contains {}", original_name, signature);
+                Err(AppError::InfectedFile)
+            }
+        }
+    }
+    .await;
+    drop(file);
+
+    let (size, sha256) = match checked {
+        Ok(checked) => checked,
+        Err(err) => {
+            let _ = fs::remove_file(&temp_path).await;
+            return Err(err);
+        }
+    };
+    let content_type = expected_type.mime_type();
+    if let Err(err) = options
+        .storage
+        .put_file(&key, &temp_path, content_type)
+        .await
+    {
+        let _ = fs::remove_file(&temp_path).await;
+        return Err(err);
     }
 
-    Ok(fields)
+    Ok(UploadedFile {
+        field: name,
+        original_name,
+        url: format!("{}/{}", options.base_url.trim_end_matches('/'), key),
+        key,
+        size,
+        content_type: content_type.to_string(),
+        sha256,
+    })
 }
 
-/// Parses multipart form data into a map of single string values.
-/// Consumes the multipart stream, validating field names.
-/// For text fields, if a field appears multiple times,
-/// the last value is retained. Returns:
-/// - `fields`: `HashMap<String, String>` mapping each field name to its text value.
-pub async fn parse_multipart_to_maps(
-    multipart: axum::extract::Multipart,
-    asset_allowed_extensions_pattern: &regex::Regex,
-) -> Result<std::collections::HashMap<String, String>, AppError> {
-    let multi_fields =
-        parse_multipart_internal(multipart, asset_allowed_extensions_pattern).await?;
-
-    let fields = multi_fields
-        .into_iter()
-        .map(|(k, mut v)| {
-            let last = v.pop().unwrap_or_default();
-            (k, last)
-        })
-        .collect();
-    Ok(fields)
+/// Checks that the magic bytes at the start of a file match the type its extension implies.
+fn check_type(header: &[u8], expected: FileType, original_name: &str) -> Result<(), AppError> {
+    if FileType::detect(header) != Some(expected) {
+        tracing::error!(
+            "Contents of uploaded file {} are not {}",
+            original_name,
+            expected.mime_type()
+        );
+        return Err(AppError::FileContentMismatch);
+    }
+    Ok(())
 }
 
-/// Parses multipart form data into maps of text values.
-///
-/// Consumes the multipart stream, validating field names.
-/// All occurrences of a text field are retained
-/// in the returned map.
-/// Returns:
-/// - `fields`: `HashMap<String, Vec<String>>` mapping each field name to its list of text values.
-pub async fn parse_multipart_to_multi_maps(
-    multipart: axum::extract::Multipart,
-    asset_allowed_extensions_pattern: &regex::Regex,
-) -> Result<std::collections::HashMap<String, Vec<String>>, AppError> {
-    parse_multipart_internal(multipart, asset_allowed_extensions_pattern).await
+fn check_dimensions(
+    file_type: FileType,
+    data: &[u8],
+    limits: ImageLimits,
+    original_name: &str,
+) -> Result<(), AppError> {
+    let dimensions = image_dimensions(file_type, data).ok_or_else(|| {
+        tracing::error!("Uploaded image {} has no valid header", original_name);
+        AppError::FileContentMismatch
+    })?;
+    if !limits.allows(dimensions) {
+        tracing::error!(
+            "Uploaded image {} is {}x{} pixels",
+            original_name,
+            dimensions.0,
+            dimensions.1
+        );
+        return Err(AppError::ImageTooLarge);
+    }
+    Ok(())
+}
+
+fn map_io_error(err: std::io::Error) -> AppError {
+    tracing::error!("Error storing uploaded file: {err}");
+    AppError::InternalError
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{file_scanner::NoopScanner, storage::LocalStorage};
+    use axum::{body::Body, extract::FromRequest, http::Request};
+
+    fn multipart_request(file_contents: &[u8]) -> Request<Body> {
+        let mut body =
+            b"--XX\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\nBreakfast\r\n\
+              --XX\r\nContent-Disposition: form-data; name=\"image\"; filename=\"Menu.PNG\"\r\n\
+              Content-Type: application/octet-stream\r\n\r\n"
+                .to_vec();
+        body.extend_from_slice(file_contents);
+        body.extend_from_slice(b"\r\n--XX--\r\n");
+        Request::builder()
+            .header("content-type", "multipart/form-data; boundary=XX")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend(width.to_be_bytes());
+        png.extend(height.to_be_bytes());
+        png.extend([8, 6, 0, 0, 0]);
+        png
+    }
+
+    async fn upload(
+        contents: &[u8],
+        options: &UploadOptions<'_>,
+    ) -> Result<MultipartForm, AppError> {
+        let multipart = Multipart::from_request(multipart_request(contents), &())
+            .await
+            .unwrap();
+        parse_multipart(multipart, options).await
+    }
+
+    #[tokio::test]
+    async fn test_parse_multipart_stores_files() {
+        let dir = std::env::temp_dir().join(format!("foodzy-upload-{}", Uuid::new_v4()));
+        let allowed = Regex::new(r"(?i)^.*\.(png|jpg)$").unwrap();
+        let storage = LocalStorage::new(dir.join("public"));
+        let temp_dir = dir.join("temp");
+        let options = UploadOptions {
+            storage: &storage,
+            temp_dir: &temp_dir,
+            base_url: "/assets/public",
+            allowed_extensions: &allowed,
+            max_size: 64,
+            image_limits: ImageLimits {
+                max_dimension: 1000,
+                max_pixels: 500_000,
+            },
+            scanner: &NoopScanner,
+        };
+
+        let contents = png(640, 480);
+        let form = upload(&contents, &options).await.unwrap();
+        assert_eq!(form.text("title"), Some("Breakfast"));
+        let file = &form.files[0];
+        assert_eq!(file.field, "image");
+        assert_eq!(file.size, contents.len() as u64);
+        assert_eq!(file.content_type, "image/png");
+        assert_eq!(file.sha256, format!("{:x}", Sha256::digest(&contents)));
+        assert!(file.key.ends_with(".png"));
+        assert_eq!(file.url, format!("/assets/public/{}", file.key));
+        assert_eq!(
+            std::fs::read(dir.join("public").join(&file.key)).unwrap(),
+            contents
+        );
+
+        let err = upload(&[0; 100], &options).await.unwrap_err();
+        assert!(matches!(err, AppError::FileSizeExceeded));
+        let err = upload(b"GIF89a\x2c\x01\xc8\x00", &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::FileContentMismatch));
+        let err = upload(&png(1000, 1000), &options).await.unwrap_err();
+        assert!(matches!(err, AppError::ImageTooLarge));
+        assert_eq!(std::fs::read_dir(&temp_dir).unwrap().count(), 0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
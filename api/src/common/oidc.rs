@@ -0,0 +1,434 @@
+//! OpenID Connect client for signing in with an external identity provider.
+//!
+//! Implements the authorization code flow with PKCE (RFC 7636) against a provider found
+//! through its discovery document. ID tokens are verified against the provider's JWKS,
+//! which is fetched again when a token names a key id that is not cached yet.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{
+    decode, decode_header,
+    jwk::{Jwk, JwkSet},
+    Algorithm, DecodingKey, Validation,
+};
+use serde::{Deserialize, Deserializer};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tokio::sync::{OnceCell, RwLock};
+use url::Url;
+
+use super::{config::Config, hash_util};
+
+/// Errors raised while configuring the client or talking to the provider.
+#[derive(Debug, Error)]
+pub enum OidcError {
+    #[error("OIDC_CLIENT_ID and OIDC_REDIRECT_URL must be set when OIDC_DISCOVERY_URL is")]
+    IncompleteConfig,
+    #[error("Request to the identity provider failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Invalid identity provider endpoint: {0}")]
+    InvalidEndpoint(#[from] url::ParseError),
+    #[error("Identity provider rejected the authorization code: {0}")]
+    CodeRejected(String),
+    #[error("Invalid ID token: {0}")]
+    InvalidIdToken(String),
+}
+
+/// The subset of the provider's discovery document the client relies on.
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+    #[serde(default)]
+    token_endpoint_auth_methods_supported: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// An authorization request. `state`, `nonce` and `code_verifier` must be kept
+/// until the provider redirects back, and `url` is where the user is sent.
+#[derive(Debug, Clone)]
+pub struct AuthorizationRequest {
+    pub url: String,
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+}
+
+/// Verified claims of an ID token identifying the external user.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub email: Option<String>,
+    #[serde(default, deserialize_with = "bool_or_string")]
+    pub email_verified: bool,
+    pub preferred_username: Option<String>,
+    nonce: Option<String>,
+}
+
+/// Some providers send `email_verified` as the string `"true"`.
+fn bool_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum BoolOrString {
+        Bool(bool),
+        String(String),
+    }
+
+    Ok(match BoolOrString::deserialize(deserializer)? {
+        BoolOrString::Bool(value) => value,
+        BoolOrString::String(value) => value.eq_ignore_ascii_case("true"),
+    })
+}
+
+/// Client for a single OpenID Connect provider.
+pub struct OidcClient {
+    http: reqwest::Client,
+    discovery_url: String,
+    client_id: String,
+    client_secret: String,
+    redirect_url: String,
+    scopes: String,
+    provider: OnceCell<ProviderMetadata>,
+    jwks: RwLock<JwkSet>,
+}
+
+impl OidcClient {
+    /// Builds the client from the configuration, or returns `None` when OIDC login is off.
+    pub fn from_config(config: &Config) -> Result<Option<Self>, OidcError> {
+        let Some(discovery_url) = config.oidc_discovery_url.clone() else {
+            return Ok(None);
+        };
+        if config.oidc_client_id.is_empty() || config.oidc_redirect_url.is_empty() {
+            return Err(OidcError::IncompleteConfig);
+        }
+
+        Ok(Some(Self {
+            http: reqwest::Client::new(),
+            discovery_url,
+            client_id: config.oidc_client_id.clone(),
+            client_secret: config.oidc_client_secret.clone(),
+            redirect_url: config.oidc_redirect_url.clone(),
+            scopes: config.oidc_scopes.clone(),
+            provider: OnceCell::new(),
+            jwks: RwLock::new(JwkSet { keys: Vec::new() }),
+        }))
+    }
+
+    /// Fetches the discovery document once and caches it.
+    async fn provider(&self) -> Result<&ProviderMetadata, OidcError> {
+        self.provider
+            .get_or_try_init(|| async {
+                let metadata = self
+                    .http
+                    .get(&self.discovery_url)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json::<ProviderMetadata>()
+                    .await?;
+                Ok(metadata)
+            })
+            .await
+    }
+
+    /// Starts a login: generates the state, nonce and PKCE verifier
+    /// and builds the URL of the provider's authorization endpoint.
+    pub async fn authorization_request(&self) -> Result<AuthorizationRequest, OidcError> {
+        let provider = self.provider().await?;
+
+        let state = hash_util::generate_token();
+        let nonce = hash_util::generate_token();
+        let code_verifier = hash_util::generate_token();
+
+        let mut url = Url::parse(&provider.authorization_endpoint)?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.client_id)
+            .append_pair("redirect_uri", &self.redirect_url)
+            .append_pair("scope", &self.scopes)
+            .append_pair("state", &state)
+            .append_pair("nonce", &nonce)
+            .append_pair("code_challenge", &pkce_challenge(&code_verifier))
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(AuthorizationRequest {
+            url: url.into(),
+            state,
+            nonce,
+            code_verifier,
+        })
+    }
+
+    /// Exchanges the authorization code at the token endpoint and verifies the returned ID token.
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, OidcError> {
+        let provider = self.provider().await?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.redirect_url.as_str()),
+            ("code_verifier", code_verifier),
+            ("client_id", self.client_id.as_str()),
+        ];
+        let mut request = self.http.post(&provider.token_endpoint);
+        // Public clients without a secret rely on PKCE alone. Otherwise client_secret_basic
+        // is used, which is also the default when the provider does not list its methods.
+        if !self.client_secret.is_empty() {
+            let methods = &provider.token_endpoint_auth_methods_supported;
+            if methods.is_empty() || methods.iter().any(|m| m == "client_secret_basic") {
+                request = request.basic_auth(&self.client_id, Some(&self.client_secret));
+            } else {
+                form.push(("client_secret", self.client_secret.as_str()));
+            }
+        }
+
+        let response = request.form(&form).send().await?;
+        if response.status().is_client_error() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(OidcError::CodeRejected(format!("{status}: {body}")));
+        }
+        let tokens = response.error_for_status()?.json::<TokenResponse>().await?;
+
+        self.verify_id_token(&tokens.id_token, nonce).await
+    }
+
+    /// Checks the signature, issuer, audience, expiry and nonce of an ID token.
+    async fn verify_id_token(
+        &self,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, OidcError> {
+        let provider = self.provider().await?;
+
+        let header =
+            decode_header(id_token).map_err(|err| OidcError::InvalidIdToken(err.to_string()))?;
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(OidcError::InvalidIdToken(
+                "symmetric signatures are not accepted".into(),
+            ));
+        }
+
+        let jwk = self.find_jwk(provider, header.kid.as_deref()).await?;
+        let key = DecodingKey::from_jwk(&jwk)
+            .map_err(|err| OidcError::InvalidIdToken(err.to_string()))?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&provider.issuer]);
+        validation.set_audience(&[&self.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(|err| OidcError::InvalidIdToken(err.to_string()))?
+            .claims;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(OidcError::InvalidIdToken("nonce mismatch".into()));
+        }
+
+        Ok(claims)
+    }
+
+    /// Looks up a signing key of the provider, refreshing the cached JWKS when the key is unknown.
+    async fn find_jwk(
+        &self,
+        provider: &ProviderMetadata,
+        kid: Option<&str>,
+    ) -> Result<Jwk, OidcError> {
+        let find = |jwks: &JwkSet| match kid {
+            Some(kid) => jwks.find(kid).cloned(),
+            None if jwks.keys.len() == 1 => jwks.keys.first().cloned(),
+            None => None,
+        };
+
+        if let Some(jwk) = find(&*self.jwks.read().await) {
+            return Ok(jwk);
+        }
+
+        let fresh = self
+            .http
+            .get(&provider.jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<JwkSet>()
+            .await?;
+        let jwk = find(&fresh);
+        *self.jwks.write().await = fresh;
+
+        jwk.ok_or_else(|| OidcError::InvalidIdToken("unknown signing key".into()))
+    }
+}
+
+/// Derives the S256 code challenge sent with the authorization request.
+fn pkce_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use axum::{
+        extract::{Form, State},
+        http::StatusCode,
+        routing::{get, post},
+        Json, Router,
+    };
+    use chrono::Utc;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use ring::{rand::SystemRandom, signature::Ed25519KeyPair};
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::common::jwt::JwtKeys;
+
+    /// A minimal OIDC provider that accepts one authorization code.
+    struct MockProvider {
+        issuer: String,
+        pem: Vec<u8>,
+        jwks: Value,
+        /// PKCE challenge and nonce of the pending authorization, keyed by the code it issued.
+        pending: Mutex<HashMap<String, (String, String)>>,
+    }
+
+    async fn discovery(State(mock): State<Arc<MockProvider>>) -> Json<Value> {
+        Json(json!({
+            "issuer": mock.issuer,
+            "authorization_endpoint": format!("{}/authorize", mock.issuer),
+            "token_endpoint": format!("{}/token", mock.issuer),
+            "jwks_uri": format!("{}/jwks", mock.issuer),
+            "token_endpoint_auth_methods_supported": ["client_secret_post"],
+        }))
+    }
+
+    async fn jwks(State(mock): State<Arc<MockProvider>>) -> Json<Value> {
+        Json(mock.jwks.clone())
+    }
+
+    async fn token(
+        State(mock): State<Arc<MockProvider>>,
+        Form(form): Form<HashMap<String, String>>,
+    ) -> Result<Json<Value>, StatusCode> {
+        if form.get("client_secret").map(String::as_str) != Some("s3cret") {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        let (challenge, nonce) = mock
+            .pending
+            .lock()
+            .unwrap()
+            .remove(&form["code"])
+            .ok_or(StatusCode::BAD_REQUEST)?;
+        if pkce_challenge(&form["code_verifier"]) != challenge {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        let claims = json!({
+            "iss": mock.issuer,
+            "aud": "foodzy",
+            "sub": "external-42",
+            "email": "ext@example.com",
+            "email_verified": "true",
+            "nonce": nonce,
+            "exp": Utc::now().timestamp() + 60,
+        });
+        let mut header = Header::new(Algorithm::EdDSA);
+        header.kid = Some("mock".into());
+        let key = EncodingKey::from_ed_pem(&mock.pem).unwrap();
+        Ok(Json(json!({
+            "access_token": "unused",
+            "token_type": "Bearer",
+            "id_token": encode(&header, &claims, &key).unwrap(),
+        })))
+    }
+
+    #[tokio::test]
+    async fn test_authorization_code_flow_against_mock_provider() {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        let pem = pem::encode(&pem::Pem::new("PRIVATE KEY", pkcs8.as_ref().to_vec())).into_bytes();
+        let keys = JwtKeys::from_pems(vec![("mock".into(), Algorithm::EdDSA, pem.clone())], "mock")
+            .unwrap();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let mock = Arc::new(MockProvider {
+            issuer: issuer.clone(),
+            pem,
+            jwks: serde_json::to_value(keys.jwks()).unwrap(),
+            pending: Mutex::new(HashMap::new()),
+        });
+        let app = Router::new()
+            .route("/.well-known/openid-configuration", get(discovery))
+            .route("/jwks", get(jwks))
+            .route("/token", post(token))
+            .with_state(mock.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = OidcClient {
+            http: reqwest::Client::new(),
+            discovery_url: format!("{issuer}/.well-known/openid-configuration"),
+            client_id: "foodzy".into(),
+            client_secret: "s3cret".into(),
+            redirect_url: "http://localhost:8080/oidc/callback".into(),
+            scopes: "openid email".into(),
+            provider: OnceCell::new(),
+            jwks: RwLock::new(JwkSet { keys: Vec::new() }),
+        };
+
+        let request = client.authorization_request().await.unwrap();
+        let url = Url::parse(&request.url).unwrap();
+        assert!(request.url.starts_with(&format!("{issuer}/authorize?")));
+        let params: HashMap<_, _> = url.query_pairs().into_owned().collect();
+        assert_eq!(params["code_challenge_method"], "S256");
+        assert_eq!(params["state"], request.state);
+
+        // The user signs in at the provider, which redirects back with a code.
+        mock.pending.lock().unwrap().insert(
+            "code-1".into(),
+            (params["code_challenge"].clone(), params["nonce"].clone()),
+        );
+        let claims = client
+            .exchange_code("code-1", &request.code_verifier, &request.nonce)
+            .await
+            .unwrap();
+        assert_eq!(claims.iss, issuer);
+        assert_eq!(claims.sub, "external-42");
+        assert_eq!(claims.email.as_deref(), Some("ext@example.com"));
+        assert!(claims.email_verified);
+
+        // A replayed code and a code bound to another nonce are both rejected.
+        assert!(matches!(
+            client
+                .exchange_code("code-1", &request.code_verifier, &request.nonce)
+                .await,
+            Err(OidcError::CodeRejected(_))
+        ));
+        mock.pending.lock().unwrap().insert(
+            "code-2".into(),
+            (params["code_challenge"].clone(), "other-nonce".into()),
+        );
+        assert!(matches!(
+            client
+                .exchange_code("code-2", &request.code_verifier, &request.nonce)
+                .await,
+            Err(OidcError::InvalidIdToken(_))
+        ));
+    }
+}
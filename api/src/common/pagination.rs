@@ -0,0 +1,334 @@
+//! Offset and cursor pagination for list endpoints.
+//!
+//! Lists are ordered by a whitelisted sort field with the row id as tie-breaker, so the order
+//! is total and a cursor can resume right after the last row of the previous page even when
+//! rows are inserted or deleted in between.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use bigdecimal::BigDecimal;
+use sqlx::{Postgres, QueryBuilder};
+use std::str::FromStr;
+
+use crate::common::{
+    dto::{Page, PageQuery, SortOrder},
+    error::AppError,
+    query_params::{FromQueryParams, QueryParams},
+};
+
+pub const DEFAULT_PAGE_SIZE: i64 = 20;
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+/// SQL type of a sort field, used to cast cursor values back for comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortType {
+    Integer,
+    Numeric,
+    Text,
+}
+
+impl SortType {
+    fn sql_type(&self) -> &'static str {
+        match self {
+            SortType::Integer => "INT",
+            SortType::Numeric => "NUMERIC",
+            SortType::Text => "TEXT",
+        }
+    }
+
+    fn accepts(&self, value: &str) -> bool {
+        match self {
+            SortType::Integer => value.parse::<i32>().is_ok(),
+            SortType::Numeric => BigDecimal::from_str(value).is_ok(),
+            SortType::Text => true,
+        }
+    }
+}
+
+/// A field a list can be sorted by.
+#[derive(Debug)]
+pub struct SortField {
+    /// Name used in the `sort` query parameter.
+    pub name: &'static str,
+    /// SQL expression the field maps to.
+    pub column: &'static str,
+    pub sort_type: SortType,
+}
+
+/// Rows that can be paged through with a cursor.
+pub trait Keyset {
+    fn keyset_id(&self) -> i32;
+
+    /// Text form of the given sort field's value, as written into cursors.
+    fn keyset_value(&self, sort: &str) -> String;
+}
+
+/// Position after the last row of a page. Cursors are only valid for the sort they came from.
+#[derive(Debug, PartialEq)]
+struct Cursor {
+    sort: String,
+    order: SortOrder,
+    id: i32,
+    value: String,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!(
+            "{}:{}:{}:{}",
+            self.sort,
+            self.order.as_str(),
+            self.id,
+            self.value
+        ))
+    }
+
+    fn decode(cursor: &str) -> Option<Self> {
+        let text = String::from_utf8(URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+        let mut parts = text.splitn(4, ':');
+        let sort = parts.next()?.to_string();
+        let order = match parts.next()? {
+            "asc" => SortOrder::Asc,
+            "desc" => SortOrder::Desc,
+            _ => return None,
+        };
+        let id = parts.next()?.parse().ok()?;
+        let value = parts.next()?.to_string();
+        Some(Self {
+            sort,
+            order,
+            id,
+            value,
+        })
+    }
+}
+
+/// Reads the paging parameters for endpoints that take [`ValidatedQuery`], checking what
+/// does not depend on the list; [`PageRequest::from_query`] checks the rest.
+///
+/// [`ValidatedQuery`]: crate::common::query_params::ValidatedQuery
+impl FromQueryParams for PageQuery {
+    fn from_query_params(params: &mut QueryParams) -> Self {
+        let limit = params.optional("limit", "a number");
+        if limit.is_some_and(|limit| !(1..=MAX_PAGE_SIZE).contains(&limit)) {
+            params.invalid("limit", format!("Must be between 1 and {MAX_PAGE_SIZE}"));
+        }
+        let offset = params.optional("offset", "a number");
+        if offset.is_some_and(|offset| offset < 0) {
+            params.invalid("offset", "Cannot be negative");
+        }
+        let cursor: Option<String> = params.optional("cursor", "a cursor");
+        if cursor
+            .as_deref()
+            .is_some_and(|cursor| Cursor::decode(cursor).is_none())
+        {
+            params.invalid("cursor", "Is not a valid cursor");
+        }
+        if cursor.is_some() && offset.is_some_and(|offset| offset > 0) {
+            params.invalid("offset", "Cannot be combined with cursor");
+        }
+
+        Self {
+            limit,
+            offset,
+            cursor,
+            sort: params.optional("sort", "a field name"),
+            order: params.optional("order", "'asc' or 'desc'"),
+        }
+    }
+}
+
+/// A validated [`PageQuery`].
+#[derive(Debug)]
+pub struct PageRequest {
+    pub limit: i64,
+    pub offset: i64,
+    pub sort: &'static SortField,
+    pub order: SortOrder,
+    after: Option<Cursor>,
+}
+
+impl PageRequest {
+    /// Validates the query against the sort fields of a list; the first field is the default.
+    pub fn from_query(query: PageQuery, fields: &'static [SortField]) -> Result<Self, AppError> {
+        let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+            return Err(AppError::ValidationError(format!(
+                "Limit must be between 1 and {MAX_PAGE_SIZE}"
+            )));
+        }
+        let offset = query.offset.unwrap_or(0);
+        if offset < 0 {
+            return Err(AppError::ValidationError(
+                "Offset cannot be negative".into(),
+            ));
+        }
+
+        let after = match query.cursor.as_deref() {
+            Some(cursor) => Some(
+                Cursor::decode(cursor)
+                    .ok_or_else(|| AppError::ValidationError("Invalid cursor".into()))?,
+            ),
+            None => None,
+        };
+        if after.is_some() && offset > 0 {
+            return Err(AppError::ValidationError(
+                "Use either offset or cursor, not both".into(),
+            ));
+        }
+
+        let sort_name = query
+            .sort
+            .as_deref()
+            .or(after.as_ref().map(|c| c.sort.as_str()))
+            .unwrap_or(fields[0].name);
+        let sort = fields
+            .iter()
+            .find(|field| field.name == sort_name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = fields.iter().map(|field| field.name).collect();
+                AppError::ValidationError(format!(
+                    "Cannot sort by '{sort_name}'; use one of: {}",
+                    names.join(", ")
+                ))
+            })?;
+        let order = query
+            .order
+            .or(after.as_ref().map(|c| c.order))
+            .unwrap_or_default();
+
+        if let Some(cursor) = &after {
+            if cursor.sort != sort.name
+                || cursor.order != order
+                || !sort.sort_type.accepts(&cursor.value)
+            {
+                return Err(AppError::ValidationError(
+                    "Cursor does not match the requested sort".into(),
+                ));
+            }
+        }
+
+        Ok(Self {
+            limit,
+            offset,
+            sort,
+            order,
+            after,
+        })
+    }
+
+    /// Appends the condition that skips rows up to the cursor to a query whose
+    /// `WHERE` clause is already open.
+    pub fn push_after(&self, builder: &mut QueryBuilder<'_, Postgres>, id_column: &str) {
+        if let Some(after) = &self.after {
+            let operator = match self.order {
+                SortOrder::Asc => ">",
+                SortOrder::Desc => "<",
+            };
+            builder.push(format!(
+                " AND ({}, {id_column}) {operator} (CAST(",
+                self.sort.column
+            ));
+            builder.push_bind(after.value.clone());
+            builder.push(format!(" AS {}), ", self.sort.sort_type.sql_type()));
+            builder.push_bind(after.id);
+            builder.push(")");
+        }
+    }
+
+    /// Appends the ordering and fetches one row more than the page holds,
+    /// which tells [`PageRequest::into_page`] whether another page follows.
+    pub fn push_order_and_limit(&self, builder: &mut QueryBuilder<'_, Postgres>, id_column: &str) {
+        let direction = match self.order {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        };
+        builder.push(format!(
+            " ORDER BY {} {direction}, {id_column} {direction} LIMIT ",
+            self.sort.column
+        ));
+        builder.push_bind(self.limit + 1);
+        builder.push(" OFFSET ");
+        builder.push_bind(self.offset);
+    }
+
+    /// Builds the page from rows fetched with [`PageRequest::push_order_and_limit`].
+    pub fn into_page<T, U>(&self, mut rows: Vec<T>, total: i64) -> Page<U>
+    where
+        T: Keyset,
+        U: From<T>,
+    {
+        let mut next_cursor = None;
+        if rows.len() as i64 > self.limit {
+            rows.truncate(self.limit as usize);
+            next_cursor = rows.last().map(|row| {
+                Cursor {
+                    sort: self.sort.name.to_string(),
+                    order: self.order,
+                    id: row.keyset_id(),
+                    value: row.keyset_value(self.sort.name),
+                }
+                .encode()
+            });
+        }
+
+        Page {
+            items: rows.into_iter().map(Into::into).collect(),
+            total,
+            next_cursor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static FIELDS: &[SortField] = &[
+        SortField {
+            name: "id",
+            column: "t.id",
+            sort_type: SortType::Integer,
+        },
+        SortField {
+            name: "price",
+            column: "t.price",
+            sort_type: SortType::Numeric,
+        },
+    ];
+
+    #[test]
+    fn test_cursor_must_match_sort() {
+        let cursor = Cursor {
+            sort: "price".into(),
+            order: SortOrder::Desc,
+            id: 7,
+            value: "8.50".into(),
+        };
+        let encoded = cursor.encode();
+        assert_eq!(Cursor::decode(&encoded), Some(cursor));
+
+        let page = PageRequest::from_query(
+            PageQuery {
+                cursor: Some(encoded.clone()),
+                ..Default::default()
+            },
+            FIELDS,
+        )
+        .unwrap();
+        assert_eq!(page.sort.name, "price");
+        assert_eq!(page.order, SortOrder::Desc);
+
+        let mismatched = PageQuery {
+            cursor: Some(encoded),
+            sort: Some("id".into()),
+            ..Default::default()
+        };
+        assert!(PageRequest::from_query(mismatched, FIELDS).is_err());
+
+        let unknown = PageQuery {
+            sort: Some("stock".into()),
+            ..Default::default()
+        };
+        assert!(PageRequest::from_query(unknown, FIELDS).is_err());
+    }
+}
@@ -0,0 +1,76 @@
+//! Rules for passwords chosen at signup, password change and password reset.
+
+use validator::ValidationError;
+
+pub const MIN_LENGTH: usize = 8;
+pub const MAX_LENGTH: usize = 128;
+
+/// Frequently breached passwords that pass the length and character rules.
+const COMMON_PASSWORDS: &[&str] = &[
+    "password1",
+    "password123",
+    "passw0rd",
+    "12345678a",
+    "abc12345",
+    "abcd1234",
+    "qwerty123",
+    "qwerty12",
+    "iloveyou1",
+    "letmein1",
+    "welcome1",
+    "welcome123",
+    "admin123",
+    "1q2w3e4r",
+    "1qaz2wsx",
+    "zaq12wsx",
+];
+
+/// Validator for new passwords: 8 to 128 characters with at least one letter
+/// and one digit, and not one of the most common passwords.
+pub fn validate_password(password: &str) -> Result<(), ValidationError> {
+    let length = password.chars().count();
+    if !(MIN_LENGTH..=MAX_LENGTH).contains(&length) {
+        return Err(ValidationError::new("password_length").with_message(
+            format!("Password must be between {MIN_LENGTH} and {MAX_LENGTH} characters").into(),
+        ));
+    }
+
+    if !password.chars().any(char::is_alphabetic) || !password.chars().any(|c| c.is_ascii_digit()) {
+        return Err(ValidationError::new("password_characters")
+            .with_message("Password must contain at least one letter and one digit".into()));
+    }
+
+    let lowercase = password.to_lowercase();
+    if COMMON_PASSWORDS.contains(&lowercase.as_str()) {
+        return Err(
+            ValidationError::new("password_common").with_message("Password is too common".into())
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns whether the password contains the username, ignoring case.
+pub fn contains_username(password: &str, username: &str) -> bool {
+    !username.is_empty() && password.to_lowercase().contains(&username.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_password_policy() {
+        assert!(validate_password("correct horse 7").is_ok());
+        assert!(validate_password("Grün4ever").is_ok());
+
+        assert!(validate_password("a1b2c3").is_err());
+        assert!(validate_password(&format!("a1{}", "x".repeat(127))).is_err());
+        assert!(validate_password("onlyletters").is_err());
+        assert!(validate_password("1234567890").is_err());
+        assert!(validate_password("Password1").is_err());
+
+        assert!(contains_username("xAlice2024", "alice"));
+        assert!(!contains_username("correct horse 7", "alice"));
+    }
+}
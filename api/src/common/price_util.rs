@@ -0,0 +1,35 @@
+use bigdecimal::{BigDecimal, RoundingMode};
+
+/// Number of decimal places used for monetary amounts, matching `decimal(10, 2)` columns.
+pub const MONEY_SCALE: i64 = 2;
+
+/// Rounds a monetary amount to two decimal places using half-up rounding.
+pub fn round_money(amount: &BigDecimal) -> BigDecimal {
+    amount.with_scale_round(MONEY_SCALE, RoundingMode::HalfUp)
+}
+
+/// Applies a percentage discount (0–100) to a unit price.
+pub fn apply_discount(price: &BigDecimal, discount: &BigDecimal) -> BigDecimal {
+    let hundred = BigDecimal::from(100);
+    round_money(&(price * (&hundred - discount) / hundred))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_apply_discount() {
+        let price = BigDecimal::from_str("85.50").unwrap();
+        let discount = BigDecimal::from_str("15.0").unwrap();
+        assert_eq!(
+            apply_discount(&price, &discount),
+            BigDecimal::from_str("72.68").unwrap()
+        );
+
+        // No discount keeps the original price.
+        let zero = BigDecimal::from(0);
+        assert_eq!(apply_discount(&price, &zero), price);
+    }
+}
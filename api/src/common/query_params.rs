@@ -0,0 +1,191 @@
+//! Typed query string parameters that report every invalid parameter at once.
+//!
+//! `axum::extract::Query` stops at the first value it cannot deserialize and does not say
+//! which parameter it was. [`ValidatedQuery`] parses each parameter on its own and answers
+//! `400` with the list of invalid fields instead.
+
+use std::str::FromStr;
+
+use axum::{extract::FromRequestParts, http::request::Parts};
+
+use super::error::{AppError, FieldError};
+
+/// Query parameters that can be read from [`QueryParams`].
+pub trait FromQueryParams: Sized {
+    /// Reads the parameters, recording each problem with [`QueryParams::invalid`].
+    /// The result is discarded if any problem was recorded.
+    fn from_query_params(params: &mut QueryParams) -> Self;
+}
+
+/// Extracts `T` from the query string, rejecting the request with
+/// [`AppError::InvalidParameters`] if any parameter is invalid.
+pub struct ValidatedQuery<T>(pub T);
+
+impl<S, T> FromRequestParts<S> for ValidatedQuery<T>
+where
+    S: Send + Sync,
+    T: FromQueryParams,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        QueryParams::extract(parts.uri.query().unwrap_or_default()).map(Self)
+    }
+}
+
+/// Reads two sets of parameters from the same query string, reporting the problems of both.
+impl<A: FromQueryParams, B: FromQueryParams> FromQueryParams for (A, B) {
+    fn from_query_params(params: &mut QueryParams) -> Self {
+        (A::from_query_params(params), B::from_query_params(params))
+    }
+}
+
+/// The decoded parameters of a query string and the problems found while reading them.
+pub struct QueryParams {
+    pairs: Vec<(String, String)>,
+    errors: Vec<FieldError>,
+}
+
+impl QueryParams {
+    pub fn parse(query: &str) -> Self {
+        Self {
+            pairs: url::form_urlencoded::parse(query.as_bytes())
+                .into_owned()
+                .collect(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Reads `T` from a query string, failing with every problem found.
+    pub fn extract<T: FromQueryParams>(query: &str) -> Result<T, AppError> {
+        let mut params = Self::parse(query);
+        let value = T::from_query_params(&mut params);
+        if params.errors.is_empty() {
+            Ok(value)
+        } else {
+            Err(AppError::InvalidParameters(params.errors))
+        }
+    }
+
+    /// All non-blank values of a parameter, in the order they were given.
+    pub fn values(&self, name: &str) -> Vec<String> {
+        self.pairs
+            .iter()
+            .filter(|(key, value)| key == name && !value.trim().is_empty())
+            .map(|(_, value)| value.trim().to_string())
+            .collect()
+    }
+
+    /// Parses the last value of an optional parameter. `expected` completes the error
+    /// message, e.g. "a number".
+    pub fn optional<T: FromStr>(&mut self, name: &str, expected: &str) -> Option<T> {
+        let value = self.values(name).pop()?;
+        match value.parse() {
+            Ok(parsed) => Some(parsed),
+            Err(_) => {
+                self.invalid(name, format!("Must be {expected}"));
+                None
+            }
+        }
+    }
+
+    /// Like [`QueryParams::optional`], but records an error if the parameter is missing.
+    pub fn required<T: FromStr>(&mut self, name: &str, expected: &str) -> Option<T> {
+        if self.values(name).is_empty() {
+            self.invalid(name, "Is required");
+            return None;
+        }
+        self.optional(name, expected)
+    }
+
+    /// Parses every value of a parameter that may be repeated or comma-separated.
+    pub fn list<T: FromStr>(&mut self, name: &str, expected: &str) -> Vec<T> {
+        let mut parsed = Vec::new();
+        for value in self.values(name) {
+            for item in value.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                match item.parse() {
+                    Ok(item) => parsed.push(item),
+                    Err(_) => self.invalid(name, format!("'{item}' is not {expected}")),
+                }
+            }
+        }
+        parsed
+    }
+
+    pub fn invalid(&mut self, field: &str, message: impl Into<String>) {
+        self.errors.push(FieldError {
+            field: field.to_string(),
+            message: message.into(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::to_bytes, http::StatusCode, response::IntoResponse};
+    use serde_json::Value;
+
+    #[derive(Debug)]
+    struct Probe {
+        id: Option<i32>,
+        name: Option<String>,
+        tags: Vec<u8>,
+    }
+
+    impl FromQueryParams for Probe {
+        fn from_query_params(params: &mut QueryParams) -> Self {
+            Self {
+                id: params.optional("id", "a number"),
+                name: params.required("name", "a name"),
+                tags: params.list("tag", "a tag"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_query_params() {
+        let probe: Probe =
+            QueryParams::extract("id=1&id=%2042%20&name=a+b&tag=1,2&tag=3,&tag=").unwrap();
+        assert_eq!(probe.id, Some(42));
+        assert_eq!(probe.name.as_deref(), Some("a b"));
+        assert_eq!(probe.tags, vec![1, 2, 3]);
+
+        let probe: Probe = QueryParams::extract("id=&name=x").unwrap();
+        assert_eq!(probe.id, None);
+        assert!(probe.tags.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_parameters_are_reported_together() {
+        let Err(err) = QueryParams::extract::<Probe>("id=one&name=%20&tag=1,x,300") else {
+            panic!("expected invalid parameters");
+        };
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["message"], "Invalid parameters");
+        let errors: Vec<(&str, &str)> = body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|error| {
+                (
+                    error["field"].as_str().unwrap(),
+                    error["message"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                ("id", "Must be a number"),
+                ("name", "Is required"),
+                ("tag", "'x' is not a tag"),
+                ("tag", "'300' is not a tag"),
+            ]
+        );
+    }
+}
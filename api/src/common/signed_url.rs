@@ -0,0 +1,133 @@
+//! Expiring, HMAC-signed URLs for private assets.
+//!
+//! A signed URL carries `expires` (a Unix timestamp) and `signature` query parameters, so
+//! it can be opened without an `Authorization` header, e.g. from an email or an `<img>` tag.
+//! The signature is an HMAC-SHA256 over the storage key and the expiry, keyed with
+//! `ASSET_URL_SIGNING_KEY`; every replica of the API must share that key.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ring::hmac;
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::config::Config;
+
+/// Shortest `ASSET_URL_SIGNING_KEY` accepted, the output size of HMAC-SHA256.
+pub const MIN_KEY_LEN: usize = 32;
+
+/// Error raised while loading the signing key at startup.
+#[derive(Debug, Error)]
+pub enum UrlSignerError {
+    #[error("ASSET_URL_SIGNING_KEY must be at least {MIN_KEY_LEN} bytes long, got {0}")]
+    KeyTooShort(usize),
+}
+
+/// The query parameters of a signed URL.
+#[derive(Debug, Default, Deserialize)]
+pub struct SignedUrlQuery {
+    pub expires: Option<i64>,
+    pub signature: Option<String>,
+}
+
+impl SignedUrlQuery {
+    /// Whether the request claims to be signed, rather than authenticated otherwise.
+    pub fn is_signed(&self) -> bool {
+        self.signature.is_some()
+    }
+}
+
+/// Signs and verifies asset URLs.
+pub struct UrlSigner {
+    key: hmac::Key,
+}
+
+impl UrlSigner {
+    /// Signs with `ASSET_URL_SIGNING_KEY`.
+    pub fn from_config(config: &Config) -> Result<Self, UrlSignerError> {
+        Self::new(config.asset_url_signing_key.as_bytes())
+    }
+
+    /// Rejects secrets shorter than `MIN_KEY_LEN` bytes, which could be guessed.
+    pub fn new(secret: &[u8]) -> Result<Self, UrlSignerError> {
+        if secret.len() < MIN_KEY_LEN {
+            return Err(UrlSignerError::KeyTooShort(secret.len()));
+        }
+        Ok(Self {
+            key: hmac::Key::new(hmac::HMAC_SHA256, secret),
+        })
+    }
+
+    /// Returns `{base_url}/{key}` with the query parameters that make it valid until `expires`.
+    pub fn signed_url(&self, base_url: &str, key: &str, expires: i64) -> String {
+        format!(
+            "{}/{key}?expires={expires}&signature={}",
+            base_url.trim_end_matches('/'),
+            self.sign(key, expires)
+        )
+    }
+
+    /// Checks that the query was produced by [`UrlSigner::signed_url`] for `key` and has
+    /// not expired by `now`.
+    pub fn verify(&self, key: &str, query: &SignedUrlQuery, now: i64) -> bool {
+        let (Some(expires), Some(signature)) = (query.expires, &query.signature) else {
+            return false;
+        };
+        let Ok(signature) = URL_SAFE_NO_PAD.decode(signature) else {
+            return false;
+        };
+        expires > now && hmac::verify(&self.key, &message(key, expires), &signature).is_ok()
+    }
+
+    fn sign(&self, key: &str, expires: i64) -> String {
+        URL_SAFE_NO_PAD.encode(hmac::sign(&self.key, &message(key, expires)))
+    }
+}
+
+fn message(key: &str, expires: i64) -> Vec<u8> {
+    format!("{key}\n{expires}").into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Query, http::Uri};
+
+    fn query_of(url: &str) -> SignedUrlQuery {
+        let uri: Uri = url.parse().unwrap();
+        Query::try_from_uri(&uri).unwrap().0
+    }
+
+    #[test]
+    fn test_signed_url() {
+        let signer = UrlSigner::new(&[1; MIN_KEY_LEN]).unwrap();
+        let url = signer.signed_url("/assets/private/", "ab/abc.pdf", 1_000);
+        assert!(url.starts_with("/assets/private/ab/abc.pdf?expires=1000&signature="));
+
+        let query = query_of(&url);
+        assert!(query.is_signed());
+        assert!(signer.verify("ab/abc.pdf", &query, 999));
+        assert!(!signer.verify("ab/abc.pdf", &query, 1_000));
+        assert!(!signer.verify("ab/other.pdf", &query, 999));
+        let other = UrlSigner::new(&[2; MIN_KEY_LEN]).unwrap();
+        assert!(!other.verify("ab/abc.pdf", &query, 999));
+
+        let extended = SignedUrlQuery {
+            expires: Some(2_000),
+            ..query
+        };
+        assert!(!signer.verify("ab/abc.pdf", &extended, 999));
+        assert!(!signer.verify("ab/abc.pdf", &SignedUrlQuery::default(), 999));
+    }
+
+    #[test]
+    fn test_short_keys_are_rejected() {
+        assert!(matches!(
+            UrlSigner::new(b""),
+            Err(UrlSignerError::KeyTooShort(0))
+        ));
+        assert!(matches!(
+            UrlSigner::new(&[1; MIN_KEY_LEN - 1]),
+            Err(UrlSignerError::KeyTooShort(31))
+        ));
+    }
+}
@@ -0,0 +1,220 @@
+//! Storage of uploaded assets.
+//!
+//! Uploads, image variants and asset downloads go through the [`Storage`] trait, so every
+//! replica of the API sees the same files. [`LocalStorage`] keeps objects in a directory,
+//! which only works for a single replica or a shared volume; [`S3Storage`] keeps them in an
+//! S3-compatible bucket.
+//!
+//! [`S3Storage`]: super::s3_storage::S3Storage
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use axum::{
+    body::Body,
+    http::header::{CACHE_CONTROL, CONTENT_LENGTH, CONTENT_TYPE, X_CONTENT_TYPE_OPTIONS},
+    response::{IntoResponse, Response},
+};
+use tokio::{fs, io::AsyncWriteExt};
+use tokio_util::io::ReaderStream;
+use uuid::Uuid;
+
+use super::{error::AppError, file_type::FileType};
+
+/// An object read from storage.
+pub struct StoredObject {
+    pub body: Body,
+    pub content_type: String,
+    pub content_length: Option<u64>,
+}
+
+/// Trait implemented by asset stores. Keys are relative paths such as `3f/3f2a….png`;
+/// see [`is_valid_key`].
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Moves a local file into storage, replacing any object with the same key.
+    async fn put_file(&self, key: &str, path: &Path, content_type: &str) -> Result<(), AppError>;
+
+    /// Stores data, replacing any object with the same key.
+    async fn put_bytes(&self, key: &str, data: Vec<u8>, content_type: &str)
+        -> Result<(), AppError>;
+
+    /// Opens an object for streaming, or returns `None` if there is none with that key.
+    async fn open(&self, key: &str) -> Result<Option<StoredObject>, AppError>;
+
+    /// Deletes an object. Deleting a missing object is not an error.
+    async fn delete(&self, key: &str) -> Result<(), AppError>;
+
+    /// Reads a whole object into memory.
+    async fn get_bytes(&self, key: &str) -> Result<Option<Vec<u8>>, AppError> {
+        let Some(object) = self.open(key).await? else {
+            return Ok(None);
+        };
+        let bytes = axum::body::to_bytes(object.body, usize::MAX)
+            .await
+            .map_err(|err| {
+                tracing::error!("Error reading stored object {key}: {err}");
+                AppError::InternalError
+            })?;
+        Ok(Some(bytes.to_vec()))
+    }
+}
+
+/// Whether a key is a plain relative path: segments of ASCII letters, digits, `-`, `_`
+/// and `.`, none of them empty or starting with a dot. This keeps keys from escaping the
+/// storage root and hides the temporary files of [`LocalStorage`].
+pub fn is_valid_key(key: &str) -> bool {
+    key.len() <= 255
+        && key.split('/').all(|segment| {
+            !segment.is_empty()
+                && !segment.starts_with('.')
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        })
+}
+
+/// Content type of a key, judged by its extension.
+pub fn content_type_of(key: &str) -> &'static str {
+    key.rsplit_once('.')
+        .and_then(|(_, extension)| FileType::from_extension(&extension.to_ascii_lowercase()))
+        .map_or("application/octet-stream", |file_type| {
+            file_type.mime_type()
+        })
+}
+
+/// Responds with a stored object, or `404` if there is none.
+pub async fn serve(
+    storage: &dyn Storage,
+    key: &str,
+    cache_control: &'static str,
+) -> Result<Response, AppError> {
+    if !is_valid_key(key) {
+        return Err(AppError::NotFound("Asset not found".into()));
+    }
+    let object = storage
+        .open(key)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Asset not found".into()))?;
+
+    let mut response = (
+        [
+            (CONTENT_TYPE, object.content_type),
+            (CACHE_CONTROL, cache_control.to_string()),
+            (X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        object.body,
+    )
+        .into_response();
+    if let Some(length) = object.content_length {
+        response.headers_mut().insert(CONTENT_LENGTH, length.into());
+    }
+    Ok(response)
+}
+
+/// Storage in a local directory.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Path of a key, creating its parent directory.
+    async fn prepare(&self, key: &str) -> Result<PathBuf, AppError> {
+        if !is_valid_key(key) {
+            tracing::error!("Invalid storage key: {key}");
+            return Err(AppError::InternalError);
+        }
+        let path = self.root.join(key);
+        fs::create_dir_all(path.parent().unwrap_or(&self.root))
+            .await
+            .map_err(map_io_error)?;
+        Ok(path)
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put_file(&self, key: &str, path: &Path, _content_type: &str) -> Result<(), AppError> {
+        let destination = self.prepare(key).await?;
+        if fs::rename(path, &destination).await.is_ok() {
+            return Ok(());
+        }
+        // Renaming fails across file systems; copy instead.
+        let temp_path = destination.with_file_name(format!(".{}.part", Uuid::new_v4().simple()));
+        let copied = async {
+            fs::copy(path, &temp_path).await?;
+            fs::rename(&temp_path, &destination).await
+        }
+        .await;
+        if let Err(err) = copied {
+            let _ = fs::remove_file(&temp_path).await;
+            return Err(map_io_error(err));
+        }
+        let _ = fs::remove_file(path).await;
+        Ok(())
+    }
+
+    async fn put_bytes(
+        &self,
+        key: &str,
+        data: Vec<u8>,
+        _content_type: &str,
+    ) -> Result<(), AppError> {
+        let destination = self.prepare(key).await?;
+        // Written under a temporary name first so readers never see a partial file.
+        let temp_path = destination.with_file_name(format!(".{}.part", Uuid::new_v4().simple()));
+        let written = async {
+            let mut file = fs::File::create(&temp_path).await?;
+            file.write_all(&data).await?;
+            file.flush().await?;
+            fs::rename(&temp_path, &destination).await
+        }
+        .await;
+        if let Err(err) = written {
+            let _ = fs::remove_file(&temp_path).await;
+            return Err(map_io_error(err));
+        }
+        Ok(())
+    }
+
+    async fn open(&self, key: &str) -> Result<Option<StoredObject>, AppError> {
+        if !is_valid_key(key) {
+            return Ok(None);
+        }
+        let file = match fs::File::open(self.root.join(key)).await {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(map_io_error(err)),
+        };
+        let metadata = file.metadata().await.map_err(map_io_error)?;
+        if !metadata.is_file() {
+            return Ok(None);
+        }
+        Ok(Some(StoredObject {
+            body: Body::from_stream(ReaderStream::new(file)),
+            content_type: content_type_of(key).to_string(),
+            content_length: Some(metadata.len()),
+        }))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        if !is_valid_key(key) {
+            tracing::error!("Invalid storage key: {key}");
+            return Err(AppError::InternalError);
+        }
+        match fs::remove_file(self.root.join(key)).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(map_io_error(err)),
+        }
+    }
+}
+
+fn map_io_error(err: std::io::Error) -> AppError {
+    tracing::error!("Storage error: {err}");
+    AppError::InternalError
+}
@@ -2,3 +2,7 @@ pub mod auth;
 pub mod user;
 pub mod product;
 pub mod category;
+pub mod cart;
+pub mod order;
+pub mod api_key;
+pub mod asset;
@@ -0,0 +1,23 @@
+mod api {
+    mod handlers;
+    pub mod routes;
+}
+
+mod domain {
+    pub mod model;
+    pub mod repository;
+    pub mod service;
+}
+
+pub mod dto {
+    pub mod api_key_dto;
+}
+
+mod infra {
+    mod impl_repository;
+    pub mod impl_service;
+}
+
+pub use api::routes::{api_key_routes, ApiKeyApiDoc};
+pub use domain::service::ApiKeyServiceTrait;
+pub use infra::impl_service::ApiKeyService;
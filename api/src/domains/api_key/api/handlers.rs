@@ -0,0 +1,78 @@
+use crate::{
+    common::{app_state::AppState, dto::RestApiResponse, error::AppError, jwt::Claims},
+    domains::api_key::dto::api_key_dto::{ApiKeyDto, CreateApiKeyDto, CreatedApiKeyDto},
+};
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+
+use validator::Validate;
+
+#[utoipa::path(
+    get,
+    path = "/api-keys",
+    responses((status = 200, description = "List the current user's API keys", body = [ApiKeyDto])),
+    tag = "ApiKeys"
+)]
+pub async fn get_api_keys(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<impl IntoResponse, AppError> {
+    let keys = state.api_key_service.list_keys(claims.user_id()?).await?;
+    Ok(RestApiResponse::success(keys))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api-keys",
+    request_body = CreateApiKeyDto,
+    responses(
+        (status = 200, description = "Create an API key; the key is only returned in this response", body = CreatedApiKeyDto),
+        (status = 400, description = "Invalid name, scopes or expiry")
+    ),
+    tag = "ApiKeys"
+)]
+pub async fn create_api_key(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<CreateApiKeyDto>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    let created = state
+        .api_key_service
+        .create_key(claims.user_id()?, payload)
+        .await?;
+    Ok(RestApiResponse::success(created))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api-keys/{id}",
+    responses(
+        (status = 200, description = "Revoke an API key"),
+        (status = 404, description = "API key not found")
+    ),
+    tag = "ApiKeys"
+)]
+pub async fn revoke_api_key(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let id: i32 = id
+        .parse()
+        .map_err(|_| AppError::ValidationError("Invalid API key id".into()))?;
+
+    state
+        .api_key_service
+        .revoke_key(claims.user_id()?, id)
+        .await?;
+    Ok(RestApiResponse::success_with_message("API key revoked", ()))
+}
@@ -0,0 +1,58 @@
+use super::handlers::*;
+use crate::{
+    common::app_state::AppState,
+    domains::api_key::dto::api_key_dto::{ApiKeyDto, CreateApiKeyDto, CreatedApiKeyDto},
+};
+
+use axum::{
+    routing::{delete, get},
+    Router,
+};
+
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    OpenApi,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        get_api_keys,
+        create_api_key,
+        revoke_api_key,
+    ),
+    components(schemas(ApiKeyDto, CreateApiKeyDto, CreatedApiKeyDto)),
+    tags(
+        (name = "ApiKeys", description = "API key management for machine clients")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    modifiers(&ApiKeyApiDoc)
+)]
+/// This struct is used to generate OpenAPI documentation for the API key routes.
+pub struct ApiKeyApiDoc;
+
+impl utoipa::Modify for ApiKeyApiDoc {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.as_mut().unwrap();
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .description(Some("Input your `<your‑jwt>`"))
+                    .build(),
+            ),
+        )
+    }
+}
+
+/// API keys can only be managed with a JWT; requests authenticated with an API key
+/// are rejected here because no key scope covers these routes.
+pub fn api_key_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_api_keys).post(create_api_key))
+        .route("/{id}", delete(revoke_api_key))
+}
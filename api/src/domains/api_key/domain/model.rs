@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+
+use crate::common::authz::Role;
+
+/// An API key as listed to its owner. The key itself is never stored.
+#[derive(Debug, Clone, FromRow)]
+pub struct ApiKey {
+    pub id: i32,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Data required to store a newly generated API key.
+#[derive(Debug, Clone)]
+pub struct NewApiKey {
+    pub user_id: i32,
+    pub name: String,
+    pub prefix: String,
+    pub key_hash: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// An active API key together with the current role and email verification state of its owner.
+#[derive(Debug, Clone, FromRow)]
+pub struct ApiKeyOwner {
+    pub key_id: i32,
+    pub user_id: i32,
+    pub role: Role,
+    pub email_verified: bool,
+    pub scopes: Vec<String>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,31 @@
+//! This module defines the `ApiKeyRepository` trait, which abstracts
+//! the database operations related to API keys.
+
+use super::model::{ApiKey, ApiKeyOwner, NewApiKey};
+
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+#[async_trait]
+/// Trait representing repository-level operations for API keys.
+pub trait ApiKeyRepository: Send + Sync {
+    /// Retrieves every key of the user, newest first, including revoked and expired ones.
+    async fn find_by_user_id(&self, pool: PgPool, user_id: i32)
+        -> Result<Vec<ApiKey>, sqlx::Error>;
+
+    /// Stores a new key and returns it.
+    async fn create(&self, pool: PgPool, key: NewApiKey) -> Result<ApiKey, sqlx::Error>;
+
+    /// Revokes a key of the user. Returns `false` if the user has no such key.
+    async fn revoke(&self, pool: PgPool, user_id: i32, key_id: i32) -> Result<bool, sqlx::Error>;
+
+    /// Finds the unrevoked, unexpired key with the given hash together with its owner.
+    async fn find_active_by_hash(
+        &self,
+        pool: PgPool,
+        key_hash: &str,
+    ) -> Result<Option<ApiKeyOwner>, sqlx::Error>;
+
+    /// Records that the key was just used.
+    async fn touch_last_used(&self, pool: PgPool, key_id: i32) -> Result<(), sqlx::Error>;
+}
@@ -0,0 +1,36 @@
+//! This module defines the `ApiKeyServiceTrait` responsible for API key management
+//! and for authenticating requests that carry an API key.
+
+use crate::{
+    common::{authz::AuthUser, config::Config, error::AppError},
+    domains::api_key::dto::api_key_dto::{ApiKeyDto, CreateApiKeyDto, CreatedApiKeyDto},
+};
+
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+#[async_trait]
+/// Trait defining business operations for API keys.
+pub trait ApiKeyServiceTrait: Send + Sync {
+    /// constructor for the service.
+    fn create_service(pool: PgPool, config: Config) -> Arc<dyn ApiKeyServiceTrait>
+    where
+        Self: Sized;
+
+    /// Lists every key of the user, including revoked and expired ones.
+    async fn list_keys(&self, user_id: i32) -> Result<Vec<ApiKeyDto>, AppError>;
+
+    /// Generates a new key for the user. The key is returned only this once.
+    async fn create_key(
+        &self,
+        user_id: i32,
+        payload: CreateApiKeyDto,
+    ) -> Result<CreatedApiKeyDto, AppError>;
+
+    /// Revokes one of the user's keys.
+    async fn revoke_key(&self, user_id: i32, key_id: i32) -> Result<(), AppError>;
+
+    /// Resolves an API key to the user it acts for, provided it grants the required scope.
+    async fn authenticate(&self, key: &str, required_scope: &str) -> Result<AuthUser, AppError>;
+}
@@ -0,0 +1,84 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+use crate::{common::authz::API_KEY_SCOPES, domains::api_key::domain::model::ApiKey};
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct CreateApiKeyDto {
+    #[validate(length(
+        min = 1,
+        max = 64,
+        message = "Name must be between 1 and 64 characters"
+    ))]
+    #[schema(example = "POS terminal 1")]
+    pub name: String,
+    #[validate(custom(function = "validate_scopes"))]
+    #[schema(example = json!(["product:read", "order:write"]))]
+    pub scopes: Vec<String>,
+    /// Lifetime of the key in days; the key does not expire when omitted.
+    #[validate(range(
+        min = 1,
+        max = 3650,
+        message = "Expiry must be between 1 and 3650 days"
+    ))]
+    #[schema(example = 365)]
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiKeyDto {
+    pub id: i32,
+    pub name: String,
+    /// Start of the key, to tell keys apart.
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    #[serde(with = "crate::common::ts_format::option")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(with = "crate::common::ts_format::option")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    #[serde(with = "crate::common::ts_format::option")]
+    pub revoked_at: Option<DateTime<Utc>>,
+    #[serde(with = "crate::common::ts_format")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<ApiKey> for ApiKeyDto {
+    fn from(key: ApiKey) -> Self {
+        Self {
+            id: key.id,
+            name: key.name,
+            prefix: key.prefix,
+            scopes: key.scopes,
+            expires_at: key.expires_at,
+            last_used_at: key.last_used_at,
+            revoked_at: key.revoked_at,
+            created_at: key.created_at,
+        }
+    }
+}
+
+/// A newly created key. `key` is shown only in this response; send it in the `X-Api-Key` header.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreatedApiKeyDto {
+    pub key: String,
+    pub api_key: ApiKeyDto,
+}
+
+/// Requires at least one scope, each of them one of `API_KEY_SCOPES`.
+fn validate_scopes(scopes: &[String]) -> Result<(), ValidationError> {
+    if scopes.is_empty() {
+        return Err(
+            ValidationError::new("scopes").with_message("At least one scope is required".into())
+        );
+    }
+    if let Some(unknown) = scopes
+        .iter()
+        .find(|scope| !API_KEY_SCOPES.contains(&scope.as_str()))
+    {
+        return Err(ValidationError::new("scopes")
+            .with_message(format!("Unknown scope `{unknown}`").into()));
+    }
+    Ok(())
+}
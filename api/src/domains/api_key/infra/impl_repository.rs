@@ -0,0 +1,102 @@
+use crate::common::authz::Role;
+use crate::domains::api_key::domain::{
+    model::{ApiKey, ApiKeyOwner, NewApiKey},
+    repository::ApiKeyRepository,
+};
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+pub struct ApiKeyRepo;
+
+#[async_trait]
+impl ApiKeyRepository for ApiKeyRepo {
+    async fn find_by_user_id(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+    ) -> Result<Vec<ApiKey>, sqlx::Error> {
+        let keys = sqlx::query_as!(
+            ApiKey,
+            r#"
+            SELECT id, name, prefix, scopes, expires_at, last_used_at, revoked_at, created_at
+            FROM api_keys
+            WHERE user_id = $1
+            ORDER BY created_at DESC, id DESC
+            "#,
+            user_id
+        )
+        .fetch_all(&pool)
+        .await?;
+        Ok(keys)
+    }
+
+    async fn create(&self, pool: PgPool, key: NewApiKey) -> Result<ApiKey, sqlx::Error> {
+        let created = sqlx::query_as!(
+            ApiKey,
+            r#"
+            INSERT INTO api_keys (user_id, name, prefix, key_hash, scopes, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, name, prefix, scopes, expires_at, last_used_at, revoked_at, created_at
+            "#,
+            key.user_id,
+            key.name,
+            key.prefix,
+            key.key_hash,
+            &key.scopes,
+            key.expires_at
+        )
+        .fetch_one(&pool)
+        .await?;
+        Ok(created)
+    }
+
+    async fn revoke(&self, pool: PgPool, user_id: i32, key_id: i32) -> Result<bool, sqlx::Error> {
+        let res = sqlx::query!(
+            r#"
+            UPDATE api_keys
+            SET revoked_at = COALESCE(revoked_at, now())
+            WHERE id = $1 AND user_id = $2
+            "#,
+            key_id,
+            user_id
+        )
+        .execute(&pool)
+        .await?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn find_active_by_hash(
+        &self,
+        pool: PgPool,
+        key_hash: &str,
+    ) -> Result<Option<ApiKeyOwner>, sqlx::Error> {
+        let owner = sqlx::query_as!(
+            ApiKeyOwner,
+            r#"
+            SELECT k.id as key_id, u.id as user_id, u.role as "role: Role",
+                   u.email_verified_at IS NOT NULL as "email_verified!",
+                   k.scopes, k.last_used_at
+            FROM api_keys k
+            INNER JOIN users u ON u.id = k.user_id
+            WHERE k.key_hash = $1
+              AND k.revoked_at IS NULL
+              AND (k.expires_at IS NULL OR k.expires_at > now())
+            "#,
+            key_hash
+        )
+        .fetch_optional(&pool)
+        .await?;
+        Ok(owner)
+    }
+
+    async fn touch_last_used(&self, pool: PgPool, key_id: i32) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE api_keys SET last_used_at = now() WHERE id = $1"#,
+            key_id
+        )
+        .execute(&pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{
+    common::{authz::AuthUser, config::Config, error::AppError, hash_util},
+    domains::api_key::{
+        domain::{model::NewApiKey, repository::ApiKeyRepository, service::ApiKeyServiceTrait},
+        dto::api_key_dto::{ApiKeyDto, CreateApiKeyDto, CreatedApiKeyDto},
+        infra::impl_repository::ApiKeyRepo,
+    },
+};
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use rand::{distr::Alphanumeric, Rng};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+/// Marks our keys so they are easy to recognise, e.g. by secret scanners.
+const KEY_PREFIX: &str = "fzk_";
+
+/// `last_used_at` is only written when it is older than this, to avoid a write per request.
+const LAST_USED_RESOLUTION_SECS: i64 = 60;
+
+/// Service struct for handling API key operations
+/// such as creating, listing, revoking and authenticating keys.
+/// It uses a repository pattern to abstract the data access layer.
+#[derive(Clone)]
+pub struct ApiKeyService {
+    pub pool: PgPool,
+    pub repo: Arc<dyn ApiKeyRepository + Send + Sync>,
+    pub config: Config,
+}
+
+#[async_trait]
+impl ApiKeyServiceTrait for ApiKeyService {
+    /// constructor for the service.
+    fn create_service(pool: PgPool, config: Config) -> Arc<dyn ApiKeyServiceTrait> {
+        Arc::new(Self {
+            pool,
+            repo: Arc::new(ApiKeyRepo {}),
+            config,
+        })
+    }
+
+    async fn list_keys(&self, user_id: i32) -> Result<Vec<ApiKeyDto>, AppError> {
+        match self.repo.find_by_user_id(self.pool.clone(), user_id).await {
+            Ok(keys) => Ok(keys.into_iter().map(ApiKeyDto::from).collect()),
+            Err(err) => {
+                tracing::error!("Error fetching API keys: {err}");
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
+
+    /// Keys look like `fzk_<8 characters>_<43 characters>`; the part before the second
+    /// underscore is stored as the public prefix, the whole key only as a SHA-256 hash.
+    async fn create_key(
+        &self,
+        user_id: i32,
+        payload: CreateApiKeyDto,
+    ) -> Result<CreatedApiKeyDto, AppError> {
+        let key_id: String = rand::rng()
+            .sample_iter(&Alphanumeric)
+            .take(8)
+            .map(char::from)
+            .collect();
+        let prefix = format!("{KEY_PREFIX}{key_id}");
+        let key = format!("{prefix}_{}", hash_util::generate_token());
+
+        let mut scopes = payload.scopes;
+        scopes.sort();
+        scopes.dedup();
+
+        let new_key = NewApiKey {
+            user_id,
+            name: payload.name,
+            prefix,
+            key_hash: hash_util::hash_token(&key),
+            scopes,
+            expires_at: payload
+                .expires_in_days
+                .map(|days| Utc::now() + Duration::days(days)),
+        };
+
+        match self.repo.create(self.pool.clone(), new_key).await {
+            Ok(api_key) => {
+                tracing::info!("Created API key {} for user {user_id}", api_key.id);
+                Ok(CreatedApiKeyDto {
+                    key,
+                    api_key: api_key.into(),
+                })
+            }
+            Err(err) => {
+                tracing::error!("Error creating API key: {err}");
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
+
+    async fn revoke_key(&self, user_id: i32, key_id: i32) -> Result<(), AppError> {
+        match self.repo.revoke(self.pool.clone(), user_id, key_id).await {
+            Ok(true) => {
+                tracing::info!("Revoked API key {key_id} of user {user_id}");
+                Ok(())
+            }
+            Ok(false) => Err(AppError::NotFound("API key not found".into())),
+            Err(err) => {
+                tracing::error!("Error revoking API key: {err}");
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
+
+    /// The key acts with the owner's current role, so demoting a user also limits their keys.
+    async fn authenticate(&self, key: &str, required_scope: &str) -> Result<AuthUser, AppError> {
+        if !key.starts_with(KEY_PREFIX) {
+            return Err(AppError::InvalidToken);
+        }
+
+        let owner = self
+            .repo
+            .find_active_by_hash(self.pool.clone(), &hash_util::hash_token(key))
+            .await
+            .map_err(|err| {
+                tracing::error!("Error looking up API key: {err}");
+                AppError::DatabaseError(err)
+            })?
+            .ok_or(AppError::InvalidToken)?;
+
+        if !owner.scopes.iter().any(|scope| scope == required_scope) {
+            return Err(AppError::Forbidden);
+        }
+        if !owner.email_verified && self.config.email_verification_policy.blocks_login() {
+            return Err(AppError::EmailNotVerified);
+        }
+
+        let stale_before = Utc::now() - Duration::seconds(LAST_USED_RESOLUTION_SECS);
+        if owner
+            .last_used_at
+            .is_none_or(|last_used_at| last_used_at < stale_before)
+        {
+            // Failing to record the use must not fail the request.
+            if let Err(err) = self
+                .repo
+                .touch_last_used(self.pool.clone(), owner.key_id)
+                .await
+            {
+                tracing::error!("Error recording API key use: {err}");
+            }
+        }
+
+        Ok(AuthUser {
+            user_id: owner.user_id,
+            role: owner.role,
+            email_verified: owner.email_verified,
+            session_id: None,
+        })
+    }
+}
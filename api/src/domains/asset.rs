@@ -0,0 +1,23 @@
+mod api {
+    mod handlers;
+    pub mod routes;
+}
+
+mod domain {
+    pub mod model;
+    pub mod repository;
+    pub mod service;
+}
+
+pub mod dto {
+    pub mod asset_dto;
+}
+
+mod infra {
+    mod impl_repository;
+    pub mod impl_service;
+}
+
+pub use api::routes::{asset_routes, AssetApiDoc};
+pub use domain::service::{AssetServiceTrait, DownloadAccess};
+pub use infra::impl_service::AssetService;
@@ -0,0 +1,136 @@
+use crate::{
+    common::{
+        app_state::AppState,
+        authz::AuthUser,
+        dto::RestApiResponse,
+        error::AppError,
+        multipart_helper::{parse_multipart, UploadOptions},
+    },
+    domains::asset::dto::asset_dto::{
+        AssetDto, CreateSignedUrlDto, SignedUrlDto, UploadAssetsForm,
+    },
+};
+
+use axum::{
+    extract::{Multipart, Path, State},
+    response::IntoResponse,
+    Json,
+};
+
+use validator::Validate;
+
+fn parse_asset_id(id: &str) -> Result<i32, AppError> {
+    id.parse()
+        .map_err(|_| AppError::ValidationError("Invalid asset id".into()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/asset",
+    responses((status = 200, description = "List the current user's private files", body = [AssetDto])),
+    tag = "Assets"
+)]
+pub async fn get_assets(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let assets = state.asset_service.list_assets(auth_user.user_id).await?;
+    Ok(RestApiResponse::success(assets))
+}
+
+#[utoipa::path(
+    post,
+    path = "/asset",
+    request_body(content = UploadAssetsForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Store private files owned by the current user", body = [AssetDto]),
+        (status = 400, description = "No files, or a file was rejected")
+    ),
+    tag = "Assets"
+)]
+pub async fn upload_assets(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    multipart: Multipart,
+) -> Result<impl IntoResponse, AppError> {
+    let form = parse_multipart(multipart, &UploadOptions::private(&state)).await?;
+    let assets = state
+        .asset_service
+        .add_assets(auth_user.user_id, form.files)
+        .await?;
+    Ok(RestApiResponse::success(assets))
+}
+
+#[utoipa::path(
+    get,
+    path = "/asset/{id}",
+    responses(
+        (status = 200, description = "Get a private file's details", body = AssetDto),
+        (status = 403, description = "The file belongs to another user"),
+        (status = 404, description = "Asset not found")
+    ),
+    tag = "Assets"
+)]
+pub async fn get_asset(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let asset = state
+        .asset_service
+        .get_asset(auth_user, parse_asset_id(&id)?)
+        .await?;
+    Ok(RestApiResponse::success(asset))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/asset/{id}",
+    responses(
+        (status = 200, description = "Delete a private file"),
+        (status = 403, description = "The file belongs to another user"),
+        (status = 404, description = "Asset not found")
+    ),
+    tag = "Assets"
+)]
+pub async fn delete_asset(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    state
+        .asset_service
+        .delete_asset(auth_user, parse_asset_id(&id)?)
+        .await?;
+    Ok(RestApiResponse::success_with_message("Asset deleted", ()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/asset/{id}/signed-url",
+    request_body = CreateSignedUrlDto,
+    responses(
+        (status = 200, description = "Create a URL that serves the file without authentication until it expires", body = SignedUrlDto),
+        (status = 400, description = "Invalid expiry"),
+        (status = 403, description = "The file belongs to another user"),
+        (status = 404, description = "Asset not found")
+    ),
+    tag = "Assets"
+)]
+pub async fn create_signed_url(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<String>,
+    Json(payload): Json<CreateSignedUrlDto>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    let signed = state
+        .asset_service
+        .create_signed_url(auth_user, parse_asset_id(&id)?, payload)
+        .await?;
+    Ok(RestApiResponse::success(signed))
+}
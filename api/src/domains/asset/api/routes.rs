@@ -0,0 +1,63 @@
+use super::handlers::*;
+use crate::{
+    common::app_state::AppState,
+    domains::asset::dto::asset_dto::{
+        AssetDto, CreateSignedUrlDto, SignedUrlDto, UploadAssetsForm,
+    },
+};
+
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    OpenApi,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        get_assets,
+        upload_assets,
+        get_asset,
+        delete_asset,
+        create_signed_url,
+    ),
+    components(schemas(AssetDto, UploadAssetsForm, CreateSignedUrlDto, SignedUrlDto)),
+    tags(
+        (name = "Assets", description = "Private files and signed URLs to share them")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    modifiers(&AssetApiDoc)
+)]
+/// This struct is used to generate OpenAPI documentation for the asset routes.
+pub struct AssetApiDoc;
+
+impl utoipa::Modify for AssetApiDoc {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.as_mut().unwrap();
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .description(Some("Input your `<your‑jwt>`"))
+                    .build(),
+            ),
+        )
+    }
+}
+
+/// Every user manages their own private files; admins may access anyone's by id.
+/// No API key scope covers these routes.
+pub fn asset_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_assets).post(upload_assets))
+        .route("/{id}", get(get_asset).delete(delete_asset))
+        .route("/{id}/signed-url", post(create_signed_url))
+}
@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+
+/// A file in the private asset storage and the user it belongs to.
+#[derive(Debug, Clone, FromRow)]
+pub struct PrivateAsset {
+    pub id: i32,
+    pub owner_id: i32,
+    pub storage_key: String,
+    pub original_name: String,
+    pub content_type: String,
+    pub size: i64,
+    pub sha256: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Data required to record the owner of an uploaded file.
+#[derive(Debug, Clone)]
+pub struct NewPrivateAsset {
+    pub owner_id: i32,
+    pub storage_key: String,
+    pub original_name: String,
+    pub content_type: String,
+    pub size: i64,
+    pub sha256: String,
+}
@@ -0,0 +1,36 @@
+//! This module defines the `AssetRepository` trait, which abstracts
+//! the database operations related to private asset ownership.
+
+use super::model::{NewPrivateAsset, PrivateAsset};
+
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+#[async_trait]
+/// Trait representing repository-level operations for private assets.
+pub trait AssetRepository: Send + Sync {
+    /// Retrieves every asset of the user, newest first.
+    async fn find_by_owner(
+        &self,
+        pool: PgPool,
+        owner_id: i32,
+    ) -> Result<Vec<PrivateAsset>, sqlx::Error>;
+
+    async fn find_by_id(&self, pool: PgPool, id: i32) -> Result<Option<PrivateAsset>, sqlx::Error>;
+
+    async fn find_by_key(
+        &self,
+        pool: PgPool,
+        storage_key: &str,
+    ) -> Result<Option<PrivateAsset>, sqlx::Error>;
+
+    /// Stores the assets in one statement and returns them in the given order.
+    async fn create_many(
+        &self,
+        pool: PgPool,
+        assets: Vec<NewPrivateAsset>,
+    ) -> Result<Vec<PrivateAsset>, sqlx::Error>;
+
+    /// Deletes an asset. Returns `false` if there is none with that id.
+    async fn delete(&self, pool: PgPool, id: i32) -> Result<bool, sqlx::Error>;
+}
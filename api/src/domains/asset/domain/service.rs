@@ -0,0 +1,72 @@
+//! This module defines the `AssetServiceTrait` responsible for private asset ownership,
+//! for deciding who may download a private asset and for signing asset URLs.
+
+use crate::{
+    common::{
+        authz::AuthUser,
+        config::Config,
+        error::AppError,
+        multipart_helper::UploadedFile,
+        signed_url::{SignedUrlQuery, UrlSigner},
+        storage::Storage,
+    },
+    domains::asset::dto::asset_dto::{AssetDto, CreateSignedUrlDto, SignedUrlDto},
+};
+
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+/// How a request for a private asset proves it may read the file.
+pub enum DownloadAccess<'a> {
+    /// The caller authenticated with a Bearer token or API key.
+    User(AuthUser),
+    /// The URL carries a signature.
+    Signed(&'a SignedUrlQuery),
+}
+
+#[async_trait]
+/// Trait defining business operations for private assets.
+pub trait AssetServiceTrait: Send + Sync {
+    /// constructor for the service.
+    fn create_service(
+        pool: PgPool,
+        config: Config,
+        storage: Arc<dyn Storage>,
+        signer: Arc<UrlSigner>,
+    ) -> Arc<dyn AssetServiceTrait>
+    where
+        Self: Sized;
+
+    /// Lists the assets of a user, newest first.
+    async fn list_assets(&self, owner_id: i32) -> Result<Vec<AssetDto>, AppError>;
+
+    /// Records the user as the owner of files stored in the private storage. The files are
+    /// deleted again if they cannot be recorded.
+    async fn add_assets(
+        &self,
+        owner_id: i32,
+        files: Vec<UploadedFile>,
+    ) -> Result<Vec<AssetDto>, AppError>;
+
+    /// Retrieves an asset of the caller; admins may retrieve any asset.
+    async fn get_asset(&self, user: AuthUser, id: i32) -> Result<AssetDto, AppError>;
+
+    /// Deletes an asset of the caller and its file; admins may delete any asset.
+    async fn delete_asset(&self, user: AuthUser, id: i32) -> Result<(), AppError>;
+
+    /// Signs a URL for an asset of the caller; admins may sign URLs for any asset.
+    async fn create_signed_url(
+        &self,
+        user: AuthUser,
+        id: i32,
+        payload: CreateSignedUrlDto,
+    ) -> Result<SignedUrlDto, AppError>;
+
+    /// Fails unless the request may download the private file stored under `key`.
+    async fn authorize_download(
+        &self,
+        key: &str,
+        access: DownloadAccess<'_>,
+    ) -> Result<(), AppError>;
+}
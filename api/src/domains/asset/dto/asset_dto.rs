@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::domains::asset::domain::model::PrivateAsset;
+
+/// Longest lifetime of a signed URL, matching the limit of S3 presigned URLs.
+pub const MAX_SIGNED_URL_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AssetDto {
+    pub id: i32,
+    pub owner_id: i32,
+    #[schema(example = "invoice-2025-06.pdf")]
+    pub original_name: String,
+    #[schema(example = "application/pdf")]
+    pub content_type: String,
+    pub size: i64,
+    /// Hex-encoded SHA-256 of the contents.
+    pub sha256: String,
+    /// URL the file is served under to its owner and admins, with a Bearer token.
+    #[schema(example = "/assets/private/3f/3f2a9c0d.pdf")]
+    pub url: String,
+    #[serde(with = "crate::common::ts_format")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl AssetDto {
+    pub fn new(asset: PrivateAsset, base_url: &str) -> Self {
+        Self {
+            id: asset.id,
+            owner_id: asset.owner_id,
+            original_name: asset.original_name,
+            content_type: asset.content_type,
+            size: asset.size,
+            sha256: asset.sha256,
+            url: format!("{}/{}", base_url.trim_end_matches('/'), asset.storage_key),
+            created_at: asset.created_at,
+        }
+    }
+}
+
+/// Multipart form of an upload; every file part is stored. Only used for documentation.
+#[derive(ToSchema)]
+pub struct UploadAssetsForm {
+    #[schema(value_type = Vec<String>, format = Binary)]
+    pub files: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema, Validate)]
+pub struct CreateSignedUrlDto {
+    /// Lifetime of the URL in seconds; `ASSET_SIGNED_URL_TTL_SECS` when omitted.
+    #[validate(range(
+        min = 1,
+        max = MAX_SIGNED_URL_TTL_SECS,
+        message = "Expiry must be between 1 second and 7 days"
+    ))]
+    #[schema(example = 3600)]
+    pub expires_in_secs: Option<i64>,
+}
+
+/// A URL that serves the file without authentication until it expires.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SignedUrlDto {
+    #[schema(example = "/assets/private/3f/3f2a9c0d.pdf?expires=1750000000&signature=…")]
+    pub url: String,
+    #[serde(with = "crate::common::ts_format")]
+    pub expires_at: DateTime<Utc>,
+}
@@ -0,0 +1,116 @@
+use crate::domains::asset::domain::{
+    model::{NewPrivateAsset, PrivateAsset},
+    repository::AssetRepository,
+};
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+pub struct AssetRepo;
+
+#[async_trait]
+impl AssetRepository for AssetRepo {
+    async fn find_by_owner(
+        &self,
+        pool: PgPool,
+        owner_id: i32,
+    ) -> Result<Vec<PrivateAsset>, sqlx::Error> {
+        let assets = sqlx::query_as!(
+            PrivateAsset,
+            r#"
+            SELECT id, owner_id, storage_key, original_name, content_type, size, sha256, created_at
+            FROM private_assets
+            WHERE owner_id = $1
+            ORDER BY created_at DESC, id DESC
+            "#,
+            owner_id
+        )
+        .fetch_all(&pool)
+        .await?;
+        Ok(assets)
+    }
+
+    async fn find_by_id(&self, pool: PgPool, id: i32) -> Result<Option<PrivateAsset>, sqlx::Error> {
+        let asset = sqlx::query_as!(
+            PrivateAsset,
+            r#"
+            SELECT id, owner_id, storage_key, original_name, content_type, size, sha256, created_at
+            FROM private_assets
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&pool)
+        .await?;
+        Ok(asset)
+    }
+
+    async fn find_by_key(
+        &self,
+        pool: PgPool,
+        storage_key: &str,
+    ) -> Result<Option<PrivateAsset>, sqlx::Error> {
+        let asset = sqlx::query_as!(
+            PrivateAsset,
+            r#"
+            SELECT id, owner_id, storage_key, original_name, content_type, size, sha256, created_at
+            FROM private_assets
+            WHERE storage_key = $1
+            "#,
+            storage_key
+        )
+        .fetch_optional(&pool)
+        .await?;
+        Ok(asset)
+    }
+
+    async fn create_many(
+        &self,
+        pool: PgPool,
+        assets: Vec<NewPrivateAsset>,
+    ) -> Result<Vec<PrivateAsset>, sqlx::Error> {
+        let mut owner_ids = Vec::with_capacity(assets.len());
+        let mut storage_keys = Vec::with_capacity(assets.len());
+        let mut original_names = Vec::with_capacity(assets.len());
+        let mut content_types = Vec::with_capacity(assets.len());
+        let mut sizes = Vec::with_capacity(assets.len());
+        let mut hashes = Vec::with_capacity(assets.len());
+        for asset in assets {
+            owner_ids.push(asset.owner_id);
+            storage_keys.push(asset.storage_key);
+            original_names.push(asset.original_name);
+            content_types.push(asset.content_type);
+            sizes.push(asset.size);
+            hashes.push(asset.sha256);
+        }
+
+        let mut created = sqlx::query_as!(
+            PrivateAsset,
+            r#"
+            INSERT INTO private_assets
+                (owner_id, storage_key, original_name, content_type, size, sha256)
+            SELECT * FROM UNNEST(
+                $1::int[], $2::varchar[], $3::varchar[], $4::varchar[], $5::bigint[], $6::char(64)[]
+            )
+            RETURNING id, owner_id, storage_key, original_name, content_type, size, sha256, created_at
+            "#,
+            &owner_ids,
+            &storage_keys,
+            &original_names,
+            &content_types,
+            &sizes,
+            &hashes
+        )
+        .fetch_all(&pool)
+        .await?;
+        // Identities are assigned in insertion order.
+        created.sort_by_key(|asset| asset.id);
+        Ok(created)
+    }
+
+    async fn delete(&self, pool: PgPool, id: i32) -> Result<bool, sqlx::Error> {
+        let res = sqlx::query!(r#"DELETE FROM private_assets WHERE id = $1"#, id)
+            .execute(&pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+}
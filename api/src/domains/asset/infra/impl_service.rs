@@ -0,0 +1,199 @@
+use crate::{
+    common::{
+        authz::{AuthUser, ADMIN},
+        config::Config,
+        error::AppError,
+        multipart_helper::{remove_files, UploadedFile},
+        signed_url::UrlSigner,
+        storage::Storage,
+    },
+    domains::asset::{
+        domain::{
+            model::{NewPrivateAsset, PrivateAsset},
+            repository::AssetRepository,
+            service::{AssetServiceTrait, DownloadAccess},
+        },
+        dto::asset_dto::{AssetDto, CreateSignedUrlDto, SignedUrlDto, MAX_SIGNED_URL_TTL_SECS},
+        infra::impl_repository::AssetRepo,
+    },
+};
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+/// Service struct for handling private asset operations
+/// such as recording uploads, checking downloads and signing URLs.
+/// It uses a repository pattern to abstract the data access layer.
+#[derive(Clone)]
+pub struct AssetService {
+    pub pool: PgPool,
+    pub repo: Arc<dyn AssetRepository + Send + Sync>,
+    pub config: Config,
+    /// The private asset storage.
+    pub storage: Arc<dyn Storage>,
+    pub signer: Arc<UrlSigner>,
+}
+
+impl AssetService {
+    fn to_dto(&self, asset: PrivateAsset) -> AssetDto {
+        AssetDto::new(asset, &self.config.assets_private_url)
+    }
+
+    /// Retrieves an asset, provided the caller owns it or is an admin.
+    async fn find_accessible(&self, user: AuthUser, id: i32) -> Result<PrivateAsset, AppError> {
+        let asset = self
+            .repo
+            .find_by_id(self.pool.clone(), id)
+            .await
+            .map_err(|err| {
+                tracing::error!("Error retrieving asset: {err}");
+                AppError::DatabaseError(err)
+            })?
+            .ok_or_else(|| AppError::NotFound("Asset not found".into()))?;
+        user.require_self_or_any_role(asset.owner_id, ADMIN)?;
+        Ok(asset)
+    }
+}
+
+#[async_trait]
+impl AssetServiceTrait for AssetService {
+    /// constructor for the service.
+    fn create_service(
+        pool: PgPool,
+        config: Config,
+        storage: Arc<dyn Storage>,
+        signer: Arc<UrlSigner>,
+    ) -> Arc<dyn AssetServiceTrait> {
+        Arc::new(Self {
+            pool,
+            repo: Arc::new(AssetRepo {}),
+            config,
+            storage,
+            signer,
+        })
+    }
+
+    async fn list_assets(&self, owner_id: i32) -> Result<Vec<AssetDto>, AppError> {
+        match self.repo.find_by_owner(self.pool.clone(), owner_id).await {
+            Ok(assets) => Ok(assets.into_iter().map(|asset| self.to_dto(asset)).collect()),
+            Err(err) => {
+                tracing::error!("Error fetching assets: {err}");
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
+
+    async fn add_assets(
+        &self,
+        owner_id: i32,
+        files: Vec<UploadedFile>,
+    ) -> Result<Vec<AssetDto>, AppError> {
+        if files.is_empty() {
+            return Err(AppError::ValidationError("No files were uploaded".into()));
+        }
+
+        let new_assets = files
+            .iter()
+            .map(|file| NewPrivateAsset {
+                owner_id,
+                storage_key: file.key.clone(),
+                original_name: file.original_name.clone(),
+                content_type: file.content_type.clone(),
+                size: file.size as i64,
+                sha256: file.sha256.clone(),
+            })
+            .collect();
+
+        match self.repo.create_many(self.pool.clone(), new_assets).await {
+            Ok(assets) => {
+                tracing::info!("Stored {} private assets of user {owner_id}", assets.len());
+                Ok(assets.into_iter().map(|asset| self.to_dto(asset)).collect())
+            }
+            Err(err) => {
+                tracing::error!("Error recording assets: {err}");
+                remove_files(self.storage.as_ref(), &files).await;
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
+
+    async fn get_asset(&self, user: AuthUser, id: i32) -> Result<AssetDto, AppError> {
+        let asset = self.find_accessible(user, id).await?;
+        Ok(self.to_dto(asset))
+    }
+
+    /// The file is removed after the row, so a failure to remove it only leaves an
+    /// unreachable file behind.
+    async fn delete_asset(&self, user: AuthUser, id: i32) -> Result<(), AppError> {
+        let asset = self.find_accessible(user, id).await?;
+
+        match self.repo.delete(self.pool.clone(), id).await {
+            Ok(true) => {}
+            Ok(false) => return Err(AppError::NotFound("Asset not found".into())),
+            Err(err) => {
+                tracing::error!("Error deleting asset: {err}");
+                return Err(AppError::DatabaseError(err));
+            }
+        }
+        if let Err(err) = self.storage.delete(&asset.storage_key).await {
+            tracing::error!("Error removing asset file {}: {err}", asset.storage_key);
+        }
+        tracing::info!("Deleted asset {id} of user {}", asset.owner_id);
+        Ok(())
+    }
+
+    async fn create_signed_url(
+        &self,
+        user: AuthUser,
+        id: i32,
+        payload: CreateSignedUrlDto,
+    ) -> Result<SignedUrlDto, AppError> {
+        let asset = self.find_accessible(user, id).await?;
+
+        let ttl_secs = payload
+            .expires_in_secs
+            .unwrap_or(self.config.asset_signed_url_ttl_secs)
+            .min(MAX_SIGNED_URL_TTL_SECS);
+        let expires_at = Utc::now() + Duration::seconds(ttl_secs);
+        let url = self.signer.signed_url(
+            &self.config.assets_private_url,
+            &asset.storage_key,
+            expires_at.timestamp(),
+        );
+        Ok(SignedUrlDto { url, expires_at })
+    }
+
+    /// Files without a recorded owner, e.g. those stored before ownership was tracked,
+    /// are only served to admins.
+    async fn authorize_download(
+        &self,
+        key: &str,
+        access: DownloadAccess<'_>,
+    ) -> Result<(), AppError> {
+        let user = match access {
+            DownloadAccess::Signed(query) => {
+                return if self.signer.verify(key, query, Utc::now().timestamp()) {
+                    Ok(())
+                } else {
+                    Err(AppError::InvalidSignature)
+                };
+            }
+            DownloadAccess::User(user) => user,
+        };
+
+        let asset = self
+            .repo
+            .find_by_key(self.pool.clone(), key)
+            .await
+            .map_err(|err| {
+                tracing::error!("Error retrieving asset: {err}");
+                AppError::DatabaseError(err)
+            })?;
+        match asset {
+            Some(asset) => user.require_self_or_any_role(asset.owner_id, ADMIN),
+            None if user.has_any_role(ADMIN) => Ok(()),
+            None => Err(AppError::NotFound("Asset not found".into())),
+        }
+    }
+}
@@ -16,9 +16,12 @@ pub mod dto {
 mod infra {
     mod impl_repository;
     pub mod impl_service;
+    mod session_cache;
 }
 
 // Re-export commonly used items for convenience
-pub use api::routes::{user_auth_routes, UserAuthApiDoc};
+pub use api::routes::{
+    user_auth_private_routes, user_auth_routes, well_known_routes, UserAuthApiDoc,
+};
 pub use domain::service::AuthServiceTrait;
 pub use infra::impl_service::AuthService;
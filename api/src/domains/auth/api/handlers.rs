@@ -1,45 +1,481 @@
 use crate::{
     common::{
         app_state::AppState,
+        authz::AuthUser,
+        client_ip::ClientInfo,
         dto::RestApiResponse,
         error::AppError,
         jwt::{AuthBody, AuthPayload},
     },
-    domains::auth::dto::auth_dto::AuthUserDto,
+    domains::auth::dto::auth_dto::{
+        ChangePasswordDto, ConfirmTotpDto, DisableTotpDto, ForgotPasswordDto, LoginOutcome,
+        OidcAuthorizationDto, OidcCallbackDto, RecoveryCodesDto, RefreshTokenDto,
+        ResendVerificationDto, ResetPasswordDto, SessionDto, SignupDto, TotpEnrollmentDto,
+        TwoFactorLoginDto, VerifyEmailDto,
+    },
+};
+use axum::extract::{Path, State};
+use axum::{
+    response::{IntoResponse, Response},
+    Json,
 };
-use axum::extract::State;
-use axum::{response::IntoResponse, Json};
+use uuid::Uuid;
+use validator::Validate;
 
-/// this function creates a router for creating user authentication registration
-/// it will create a new user in the database
+/// this function creates a router for signing up
+/// it will create the user with its credentials, email a verification link and return a JWT token
+/// unless a verified email is required to log in
 #[utoipa::path(
     post,
-    path = "/auth/register",
-    request_body = AuthUserDto,
-    responses((status = 200, description = "Create user authentication", body = AuthUserDto)),
+    path = "/auth/signup",
+    request_body = SignupDto,
+    responses(
+        (status = 200, description = "Sign up and log in; no tokens are returned while a verified email is required to log in", body = AuthBody),
+        (status = 400, description = "Invalid input or password does not meet the policy"),
+        (status = 409, description = "Username is already taken")
+    ),
     tag = "UserAuth"
 )]
-pub async fn create_user_auth(
+pub async fn signup(
     State(state): State<AppState>,
-    Json(payload): Json<AuthUserDto>,
+    client: ClientInfo,
+    Json(payload): Json<SignupDto>,
 ) -> Result<impl IntoResponse, AppError> {
-    state.auth_service.create_user_auth(payload).await?;
-    Ok(RestApiResponse::success(()))
+    payload.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    match state.auth_service.signup(payload, client).await? {
+        Some(auth_body) => Ok(RestApiResponse::success(Some(auth_body))),
+        None => Ok(RestApiResponse::success_with_message(
+            "Account created, verify your email address to log in",
+            None,
+        )),
+    }
 }
 
 /// this function creates a router for login user
-/// it will return a JWT token if the user is authenticated
+/// it will return a JWT token if the user is authenticated,
+/// or a challenge token if the account has two-factor login enabled
 #[utoipa::path(
     post,
     path = "/auth/login",
     request_body = AuthPayload,
-    responses((status = 200, description = "Login user", body = AuthBody)),
+    responses(
+        (status = 200, description = "Login user; accounts with two-factor login get a challenge token to complete at /auth/login/2fa", body = AuthBody),
+        (status = 401, description = "Unknown user or wrong password"),
+        (status = 403, description = "Email address must be verified before logging in"),
+        (status = 429, description = "Too many failed attempts for the username or client IP")
+    ),
     tag = "UserAuth"
 )]
 pub async fn login_user(
     State(state): State<AppState>,
+    client: ClientInfo,
     Json(payload): Json<AuthPayload>,
+) -> Result<Response, AppError> {
+    match state.auth_service.login_user(payload, client).await? {
+        LoginOutcome::Authenticated(auth_body) => {
+            Ok(RestApiResponse::success(auth_body).into_response())
+        }
+        LoginOutcome::TwoFactorRequired(challenge) => Ok(RestApiResponse::success_with_message(
+            "Two-factor authentication required",
+            challenge,
+        )
+        .into_response()),
+    }
+}
+
+/// this function creates a router for the second step of a two-factor login
+/// it will return a JWT token if the TOTP or recovery code is valid
+#[utoipa::path(
+    post,
+    path = "/auth/login/2fa",
+    request_body = TwoFactorLoginDto,
+    responses(
+        (status = 200, description = "Complete a two-factor login", body = AuthBody),
+        (status = 400, description = "Neither or both of code and recovery code were given"),
+        (status = 401, description = "Wrong code, or the challenge is invalid, expired or out of attempts")
+    ),
+    tag = "UserAuth"
+)]
+pub async fn login_two_factor(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(payload): Json<TwoFactorLoginDto>,
+) -> Result<impl IntoResponse, AppError> {
+    let auth_body = state.auth_service.login_two_factor(payload, client).await?;
+    Ok(RestApiResponse::success(auth_body))
+}
+
+/// this function creates a router for refreshing tokens
+/// it will return a new token pair if the refresh token is valid
+#[utoipa::path(
+    post,
+    path = "/auth/refresh",
+    request_body = RefreshTokenDto,
+    responses(
+        (status = 200, description = "Rotate the refresh token and issue a new access token", body = AuthBody),
+        (status = 401, description = "Refresh token is invalid, expired, revoked or reused"),
+        (status = 403, description = "Email address must be verified before logging in")
+    ),
+    tag = "UserAuth"
+)]
+pub async fn refresh_token(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(payload): Json<RefreshTokenDto>,
+) -> Result<impl IntoResponse, AppError> {
+    let auth_body = state.auth_service.refresh_token(payload, client).await?;
+    Ok(RestApiResponse::success(auth_body))
+}
+
+/// this function creates a router for logout user
+/// it will revoke the refresh token and the tokens rotated from the same login
+#[utoipa::path(
+    post,
+    path = "/auth/logout",
+    request_body = RefreshTokenDto,
+    responses((status = 200, description = "Logout user")),
+    tag = "UserAuth"
+)]
+pub async fn logout_user(
+    State(state): State<AppState>,
+    Json(payload): Json<RefreshTokenDto>,
+) -> Result<impl IntoResponse, AppError> {
+    state.auth_service.logout_user(payload).await?;
+    Ok(RestApiResponse::success(()))
+}
+
+/// this function creates a router for changing the password of the signed-in user
+/// it will end every session and return a new token pair
+#[utoipa::path(
+    post,
+    path = "/auth/change-password",
+    request_body = ChangePasswordDto,
+    responses(
+        (status = 200, description = "Change password", body = AuthBody),
+        (status = 401, description = "Current password is wrong")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "UserAuth"
+)]
+pub async fn change_password(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<ChangePasswordDto>,
 ) -> Result<impl IntoResponse, AppError> {
-    let auth_body = state.auth_service.login_user(payload).await?;
+    payload.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    let auth_body = state
+        .auth_service
+        .change_password(auth_user.user_id, payload, client)
+        .await?;
     Ok(RestApiResponse::success(auth_body))
 }
+
+/// this function creates a router for listing the sessions of the signed-in user
+/// it returns every device that is still signed in, flagging the current one
+#[utoipa::path(
+    get,
+    path = "/auth/sessions",
+    responses((status = 200, description = "List active sessions", body = [SessionDto])),
+    security(("bearer_auth" = [])),
+    tag = "UserAuth"
+)]
+pub async fn get_sessions(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let sessions = state
+        .auth_service
+        .list_sessions(auth_user.user_id, auth_user.session_id)
+        .await?;
+    Ok(RestApiResponse::success(sessions))
+}
+
+/// this function creates a router for signing out one session of the signed-in user
+/// its refresh token stops working and its access tokens are rejected
+#[utoipa::path(
+    delete,
+    path = "/auth/sessions/{id}",
+    responses(
+        (status = 200, description = "Revoke a session"),
+        (status = 404, description = "Session not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "UserAuth"
+)]
+pub async fn revoke_session(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let session_id: Uuid = id
+        .parse()
+        .map_err(|_| AppError::ValidationError("Invalid session id".into()))?;
+
+    state
+        .auth_service
+        .revoke_session(auth_user.user_id, session_id)
+        .await?;
+    Ok(RestApiResponse::success_with_message("Session revoked", ()))
+}
+
+/// this function creates a router for signing out every session of the signed-in user
+/// including the one making the request
+#[utoipa::path(
+    delete,
+    path = "/auth/sessions",
+    responses((status = 200, description = "Revoke all sessions")),
+    security(("bearer_auth" = [])),
+    tag = "UserAuth"
+)]
+pub async fn revoke_all_sessions(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    state
+        .auth_service
+        .revoke_all_sessions(auth_user.user_id)
+        .await?;
+    Ok(RestApiResponse::success_with_message(
+        "All sessions revoked",
+        (),
+    ))
+}
+
+/// this function creates a router for starting a login at the external identity provider
+/// it returns the authorization URL to send the user to
+#[utoipa::path(
+    get,
+    path = "/auth/oidc/authorize",
+    responses(
+        (status = 200, description = "Start an OpenID Connect login", body = OidcAuthorizationDto),
+        (status = 404, description = "OIDC login is not configured")
+    ),
+    tag = "UserAuth"
+)]
+pub async fn oidc_authorize(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let authorization = state.auth_service.oidc_authorize().await?;
+    Ok(RestApiResponse::success(authorization))
+}
+
+/// this function creates a router for completing a login at the external identity provider
+/// it will return a JWT token, or a challenge token if the account has two-factor login enabled
+#[utoipa::path(
+    post,
+    path = "/auth/oidc/callback",
+    request_body = OidcCallbackDto,
+    responses(
+        (status = 200, description = "Complete an OpenID Connect login; accounts with two-factor login get a challenge token to complete at /auth/login/2fa", body = AuthBody),
+        (status = 400, description = "Login state is invalid or expired, or the provider shared no email address"),
+        (status = 401, description = "The provider rejected the code or returned an invalid ID token"),
+        (status = 404, description = "OIDC login is not configured")
+    ),
+    tag = "UserAuth"
+)]
+pub async fn oidc_callback(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(payload): Json<OidcCallbackDto>,
+) -> Result<Response, AppError> {
+    match state.auth_service.oidc_callback(payload, client).await? {
+        LoginOutcome::Authenticated(auth_body) => {
+            Ok(RestApiResponse::success(auth_body).into_response())
+        }
+        LoginOutcome::TwoFactorRequired(challenge) => Ok(RestApiResponse::success_with_message(
+            "Two-factor authentication required",
+            challenge,
+        )
+        .into_response()),
+    }
+}
+
+/// this function creates a router for enrolling a TOTP authenticator
+/// it returns a new secret and its otpauth URI, pending confirmation
+#[utoipa::path(
+    post,
+    path = "/auth/2fa/enroll",
+    responses(
+        (status = 200, description = "Start TOTP enrollment", body = TotpEnrollmentDto),
+        (status = 409, description = "Two-factor authentication is already enabled")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "UserAuth"
+)]
+pub async fn enroll_totp(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let enrollment = state.auth_service.enroll_totp(auth_user.user_id).await?;
+    Ok(RestApiResponse::success(enrollment))
+}
+
+/// this function creates a router for confirming TOTP enrollment
+/// it enables two-factor login and returns the recovery codes once
+#[utoipa::path(
+    post,
+    path = "/auth/2fa/confirm",
+    request_body = ConfirmTotpDto,
+    responses(
+        (status = 200, description = "Enable two-factor login", body = RecoveryCodesDto),
+        (status = 400, description = "Invalid code or no pending enrollment"),
+        (status = 409, description = "Two-factor authentication is already enabled")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "UserAuth"
+)]
+pub async fn confirm_totp(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<ConfirmTotpDto>,
+) -> Result<impl IntoResponse, AppError> {
+    let recovery_codes = state
+        .auth_service
+        .confirm_totp(auth_user.user_id, payload)
+        .await?;
+    Ok(RestApiResponse::success(recovery_codes))
+}
+
+/// this function creates a router for turning two-factor login off
+#[utoipa::path(
+    post,
+    path = "/auth/2fa/disable",
+    request_body = DisableTotpDto,
+    responses(
+        (status = 200, description = "Disable two-factor login"),
+        (status = 400, description = "Neither a password nor a code was given"),
+        (status = 401, description = "Current password or code is wrong"),
+        (status = 429, description = "Too many wrong codes")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "UserAuth"
+)]
+pub async fn disable_totp(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<DisableTotpDto>,
+) -> Result<impl IntoResponse, AppError> {
+    state
+        .auth_service
+        .disable_totp(auth_user.user_id, payload)
+        .await?;
+    Ok(RestApiResponse::success(()))
+}
+
+/// this function creates a router for requesting a password reset
+/// it responds the same way whether or not the email is registered
+#[utoipa::path(
+    post,
+    path = "/auth/forgot-password",
+    request_body = ForgotPasswordDto,
+    responses(
+        (status = 200, description = "Reset email sent if the address is registered"),
+        (status = 429, description = "Too many requests from this IP")
+    ),
+    tag = "UserAuth"
+)]
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(payload): Json<ForgotPasswordDto>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    state.auth_service.forgot_password(payload, client).await?;
+    Ok(RestApiResponse::success_with_message(
+        "If the address is registered, a reset link has been sent",
+        (),
+    ))
+}
+
+/// this function creates a router for resetting a password
+/// it will set the new password if the reset token is valid
+#[utoipa::path(
+    post,
+    path = "/auth/reset-password",
+    request_body = ResetPasswordDto,
+    responses(
+        (status = 200, description = "Reset password"),
+        (status = 400, description = "Reset token is invalid, expired or already used")
+    ),
+    tag = "UserAuth"
+)]
+pub async fn reset_password(
+    State(state): State<AppState>,
+    Json(payload): Json<ResetPasswordDto>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    state.auth_service.reset_password(payload).await?;
+    Ok(RestApiResponse::success(()))
+}
+
+/// this function creates a router for verifying an email address
+/// it will mark the address as verified if the verification token is valid
+#[utoipa::path(
+    post,
+    path = "/auth/verify-email",
+    request_body = VerifyEmailDto,
+    responses(
+        (status = 200, description = "Verify email address; refresh the access token to pick it up"),
+        (status = 400, description = "Verification token is invalid, expired, already used or for an old address")
+    ),
+    tag = "UserAuth"
+)]
+pub async fn verify_email(
+    State(state): State<AppState>,
+    Json(payload): Json<VerifyEmailDto>,
+) -> Result<impl IntoResponse, AppError> {
+    state.auth_service.verify_email(payload).await?;
+    Ok(RestApiResponse::success(()))
+}
+
+/// this function creates a router for resending the email verification link
+/// it responds the same way whether or not the email is registered
+#[utoipa::path(
+    post,
+    path = "/auth/resend-verification",
+    request_body = ResendVerificationDto,
+    responses((status = 200, description = "Verification email sent if the address is registered and unverified")),
+    tag = "UserAuth"
+)]
+pub async fn resend_verification(
+    State(state): State<AppState>,
+    Json(payload): Json<ResendVerificationDto>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    state.auth_service.resend_verification(payload).await?;
+    Ok(RestApiResponse::success_with_message(
+        "If the address is registered and unverified, a verification link has been sent",
+        (),
+    ))
+}
+
+/// this function creates a router for the JSON Web Key Set
+/// it returns the public keys that access tokens may be signed with
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    responses((status = 200, description = "Public keys for verifying access tokens, as a JWK Set")),
+    tag = "UserAuth"
+)]
+pub async fn jwks(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.jwt_keys.jwks())
+}
@@ -1,33 +1,117 @@
 use crate::common::app_state::AppState;
-use axum::{routing::post, Router};
+use axum::{
+    routing::{delete, get, post},
+    Router,
+};
 
 use super::handlers;
 
-use utoipa::OpenApi;
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    OpenApi,
+};
 
 /// Import the necessary modules for OpenAPI documentation generation
 #[derive(OpenApi)]
 #[openapi(
     paths(
         super::handlers::login_user,
-        super::handlers::create_user_auth,
+        super::handlers::login_two_factor,
+        super::handlers::get_sessions,
+        super::handlers::revoke_session,
+        super::handlers::revoke_all_sessions,
+        super::handlers::oidc_authorize,
+        super::handlers::oidc_callback,
+        super::handlers::signup,
+        super::handlers::refresh_token,
+        super::handlers::logout_user,
+        super::handlers::change_password,
+        super::handlers::enroll_totp,
+        super::handlers::confirm_totp,
+        super::handlers::disable_totp,
+        super::handlers::forgot_password,
+        super::handlers::reset_password,
+        super::handlers::verify_email,
+        super::handlers::resend_verification,
+        super::handlers::jwks,
     ),
     components(schemas(
-        crate::domains::auth::dto::auth_dto::AuthUserDto,
+        crate::domains::auth::dto::auth_dto::SignupDto,
+        crate::domains::auth::dto::auth_dto::RefreshTokenDto,
+        crate::domains::auth::dto::auth_dto::ChangePasswordDto,
+        crate::domains::auth::dto::auth_dto::ForgotPasswordDto,
+        crate::domains::auth::dto::auth_dto::ResetPasswordDto,
+        crate::domains::auth::dto::auth_dto::VerifyEmailDto,
+        crate::domains::auth::dto::auth_dto::ResendVerificationDto,
+        crate::domains::auth::dto::auth_dto::TwoFactorChallengeDto,
+        crate::domains::auth::dto::auth_dto::TwoFactorLoginDto,
+        crate::domains::auth::dto::auth_dto::TotpEnrollmentDto,
+        crate::domains::auth::dto::auth_dto::ConfirmTotpDto,
+        crate::domains::auth::dto::auth_dto::RecoveryCodesDto,
+        crate::domains::auth::dto::auth_dto::DisableTotpDto,
+        crate::domains::auth::dto::auth_dto::SessionDto,
+        crate::domains::auth::dto::auth_dto::OidcAuthorizationDto,
+        crate::domains::auth::dto::auth_dto::OidcCallbackDto,
         crate::common::jwt::AuthPayload,
         crate::common::jwt::AuthBody,
     )),
     tags(
         (name = "UserAuth", description = "User authentication endpoints")
-    )
+    ),
+    modifiers(&UserAuthApiDoc)
 )]
 /// This struct is used to generate OpenAPI documentation for the user authentication routes.
 pub struct UserAuthApiDoc;
 
+impl utoipa::Modify for UserAuthApiDoc {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.as_mut().unwrap();
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .description(Some("Input your `<your‑jwt>`"))
+                    .build(),
+            ),
+        )
+    }
+}
+
 /// This function creates a router for the user authentication routes.
 /// It defines the routes and their corresponding handlers.
 pub fn user_auth_routes() -> Router<AppState> {
     Router::new()
         .route("/login", post(handlers::login_user))
-        .route("/register", post(handlers::create_user_auth))
+        .route("/login/2fa", post(handlers::login_two_factor))
+        .route("/oidc/authorize", get(handlers::oidc_authorize))
+        .route("/oidc/callback", post(handlers::oidc_callback))
+        .route("/signup", post(handlers::signup))
+        .route("/refresh", post(handlers::refresh_token))
+        .route("/logout", post(handlers::logout_user))
+        .route("/forgot-password", post(handlers::forgot_password))
+        .route("/reset-password", post(handlers::reset_password))
+        .route("/verify-email", post(handlers::verify_email))
+        .route("/resend-verification", post(handlers::resend_verification))
+}
+
+/// This function creates a router for the authentication routes that require a signed-in user.
+pub fn user_auth_private_routes() -> Router<AppState> {
+    Router::new()
+        .route("/change-password", post(handlers::change_password))
+        .route(
+            "/sessions",
+            get(handlers::get_sessions).delete(handlers::revoke_all_sessions),
+        )
+        .route("/sessions/{id}", delete(handlers::revoke_session))
+        .route("/2fa/enroll", post(handlers::enroll_totp))
+        .route("/2fa/confirm", post(handlers::confirm_totp))
+        .route("/2fa/disable", post(handlers::disable_totp))
+}
+
+/// This function creates a router for the `/.well-known` discovery documents.
+/// It publishes the public keys clients use to verify access tokens.
+pub fn well_known_routes() -> Router<AppState> {
+    Router::new().route("/jwks.json", get(handlers::jwks))
 }
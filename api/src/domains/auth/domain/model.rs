@@ -1,8 +1,15 @@
 //! This module defines the `UserAuth` model used for representing
-//! authentication data tied to a user.
+//! authentication data tied to a user, its login sessions, the refresh, password reset
+//! and email verification tokens issued to it, its TOTP second factor,
+//! linked external identities, and the failed login counters used to throttle
+//! brute-force attempts.
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+use crate::common::authz::Role;
 
 /// Represents a user's authentication information, including hashed password.
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -10,3 +17,254 @@ pub struct UserAuth {
     pub user_id: i32,
     pub password_hash: String,
 }
+
+/// Data required to insert a new user row at signup.
+#[derive(Debug, Clone)]
+pub struct NewUser {
+    pub username: String,
+    pub email: String,
+}
+
+/// Stored credentials of a user together with the role and email verification state granted at login.
+#[derive(Debug, Clone, FromRow)]
+pub struct UserCredentials {
+    pub user_id: i32,
+    pub password_hash: String,
+    pub role: Role,
+    pub email_verified: bool,
+}
+
+/// Role and email verification state of a user, as carried in access tokens.
+#[derive(Debug, Clone, FromRow)]
+pub struct UserStatus {
+    pub user_id: i32,
+    pub role: Role,
+    pub email_verified: bool,
+}
+
+/// A stored refresh token together with the role and email verification state of its owner.
+#[derive(Debug, Clone, FromRow)]
+pub struct RefreshToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub family_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub role: Role,
+    pub email_verified: bool,
+}
+
+/// A login session of a user, shown so they can recognise and revoke it.
+#[derive(Debug, Clone, FromRow)]
+pub struct Session {
+    pub id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip: String,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+}
+
+/// Data required to record a new login session.
+#[derive(Debug, Clone)]
+pub struct NewSession {
+    pub id: Uuid,
+    pub user_id: i32,
+    pub user_agent: Option<String>,
+    pub ip: String,
+}
+
+/// Data required to store a new refresh token.
+#[derive(Debug, Clone)]
+pub struct NewRefreshToken {
+    pub user_id: i32,
+    pub family_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Account details needed to email a user.
+#[derive(Debug, Clone, FromRow)]
+pub struct UserContact {
+    pub user_id: i32,
+    pub username: String,
+    pub email: String,
+}
+
+/// A stored password reset token.
+#[derive(Debug, Clone, FromRow)]
+pub struct PasswordResetToken {
+    pub user_id: i32,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+/// Data required to store a new password reset token.
+#[derive(Debug, Clone)]
+pub struct NewPasswordResetToken {
+    pub user_id: i32,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A stored email verification token.
+/// `email_matches` is false once the user has changed the address the token was sent to.
+#[derive(Debug, Clone, FromRow)]
+pub struct EmailVerificationToken {
+    pub user_id: i32,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub email_matches: bool,
+}
+
+/// Data required to store a new email verification token.
+#[derive(Debug, Clone)]
+pub struct NewEmailVerificationToken {
+    pub user_id: i32,
+    pub email: String,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// The TOTP secret of a user. Two-factor login is enabled once `confirmed_at` is set.
+#[derive(Debug, Clone, FromRow)]
+pub struct UserTotp {
+    pub secret: String,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub last_used_step: Option<i64>,
+}
+
+/// An unused, Argon2-hashed TOTP recovery code.
+#[derive(Debug, Clone, FromRow)]
+pub struct RecoveryCode {
+    pub id: i32,
+    pub code_hash: String,
+}
+
+/// A stored login challenge, pending the second factor.
+#[derive(Debug, Clone, FromRow)]
+pub struct LoginChallenge {
+    pub id: i32,
+    pub user_id: i32,
+    pub expires_at: DateTime<Utc>,
+    pub attempts: i32,
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+/// Data required to store a new login challenge.
+#[derive(Debug, Clone)]
+pub struct NewLoginChallenge {
+    pub user_id: i32,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A pending OpenID Connect login, taken when the provider redirects back.
+#[derive(Debug, Clone, FromRow)]
+pub struct OidcLoginState {
+    pub nonce: String,
+    pub code_verifier: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Data required to store a new pending OpenID Connect login.
+#[derive(Debug, Clone)]
+pub struct NewOidcLoginState {
+    pub state_hash: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Data required to link an external identity to a user.
+#[derive(Debug, Clone)]
+pub struct NewUserIdentity {
+    pub user_id: i32,
+    pub issuer: String,
+    pub subject: String,
+    pub email: Option<String>,
+}
+
+/// What a failed login counter is keyed by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginScope {
+    Username,
+    Ip,
+    /// Wrong TOTP or recovery codes, keyed by user ID, across all of the user's challenges.
+    SecondFactor,
+    /// Password reset requests, keyed by client IP.
+    PasswordReset,
+}
+
+impl LoginScope {
+    /// Returns the string stored in the `login_failures.scope` column.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoginScope::Username => "username",
+            LoginScope::Ip => "ip",
+            LoginScope::SecondFactor => "second_factor",
+            LoginScope::PasswordReset => "password_reset",
+        }
+    }
+
+    /// Whether a successful login clears the scope's counter.
+    /// Counters shared between accounts, such as a client IP's, only expire with their window,
+    /// so logging into an account one controls does not lift a lockout earned on others.
+    pub fn clears_on_success(&self) -> bool {
+        match self {
+            LoginScope::Username | LoginScope::SecondFactor => true,
+            LoginScope::Ip | LoginScope::PasswordReset => false,
+        }
+    }
+}
+
+/// The counters a password login is throttled by: its username and its client IP.
+/// A failed login counts against both; a successful one clears those that `clears_on_success`.
+pub fn login_counters<'a>(username: &'a str, ip: &'a str) -> [(LoginScope, &'a str); 2] {
+    [(LoginScope::Username, username), (LoginScope::Ip, ip)]
+}
+
+/// Returns how long to lock out a username or IP after its latest failed login, if at all.
+///
+/// The first lockout starts once `failures` reaches `max_failures` and lasts `base_secs`;
+/// every further failure doubles it, up to `max_secs`.
+pub fn lockout_secs(
+    failures: i32,
+    max_failures: i32,
+    base_secs: i64,
+    max_secs: i64,
+) -> Option<i64> {
+    if failures < max_failures {
+        return None;
+    }
+
+    let doublings = (failures - max_failures).min(32) as u32;
+    Some(base_secs.saturating_mul(1i64 << doublings).min(max_secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lockout_backs_off_exponentially() {
+        assert_eq!(lockout_secs(1, 5, 30, 900), None);
+        assert_eq!(lockout_secs(4, 5, 30, 900), None);
+        assert_eq!(lockout_secs(5, 5, 30, 900), Some(30));
+        assert_eq!(lockout_secs(6, 5, 30, 900), Some(60));
+        assert_eq!(lockout_secs(8, 5, 30, 900), Some(240));
+        assert_eq!(lockout_secs(10, 5, 30, 900), Some(900));
+        assert_eq!(lockout_secs(1_000, 5, 30, 900), Some(900));
+    }
+
+    #[test]
+    fn test_successful_login_keeps_ip_counter() {
+        let cleared: Vec<_> = login_counters("alice", "203.0.113.7")
+            .into_iter()
+            .filter(|(scope, _)| scope.clears_on_success())
+            .collect();
+        assert_eq!(cleared, [(LoginScope::Username, "alice")]);
+        assert!(LoginScope::SecondFactor.clears_on_success());
+        assert!(!LoginScope::PasswordReset.clears_on_success());
+    }
+}
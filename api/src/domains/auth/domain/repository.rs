@@ -1,22 +1,110 @@
 //! This module defines the `UserAuthRepository` trait, which provides an abstraction
 //! over database operations related to user authentication records.
 
-use super::model::UserAuth;
+use super::model::{
+    EmailVerificationToken, LoginChallenge, LoginScope, NewEmailVerificationToken,
+    NewLoginChallenge, NewOidcLoginState, NewPasswordResetToken, NewRefreshToken, NewSession,
+    NewUser, NewUserIdentity, OidcLoginState, PasswordResetToken, RecoveryCode, RefreshToken,
+    Session, UserAuth, UserContact, UserCredentials, UserStatus, UserTotp,
+};
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
 
 #[async_trait]
 /// Trait representing the repository contract for user authentication data.
 /// Enables decoupling of business logic from direct database interaction.
 pub trait UserAuthRepository: Send + Sync {
     /// Finds a user authentication record by the user's username.
-    /// Returns `Ok(Some(UserAuth))` if found, or `Ok(None)` if not found.
+    /// Returns `Ok(Some(UserCredentials))` if found, or `Ok(None)` if not found.
     async fn find_by_user_name(
         &self,
         pool: PgPool,
         user_name: String,
-    ) -> Result<Option<UserAuth>, sqlx::Error>;
+    ) -> Result<Option<UserCredentials>, sqlx::Error>;
+
+    /// Finds a user authentication record by the user's ID.
+    async fn find_by_user_id(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+    ) -> Result<Option<UserCredentials>, sqlx::Error>;
+
+    /// Finds the role and email verification state of a user.
+    async fn find_user_status(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+    ) -> Result<Option<UserStatus>, sqlx::Error>;
+
+    /// Returns whether the username is taken.
+    async fn username_exists(&self, pool: PgPool, username: &str) -> Result<bool, sqlx::Error>;
+
+    /// Finds the users whose verified email address is the given one.
+    async fn find_verified_user_ids_by_email(
+        &self,
+        pool: PgPool,
+        email: &str,
+    ) -> Result<Vec<i32>, sqlx::Error>;
+
+    /// Finds the user an external identity is linked to.
+    async fn find_identity_user_id(
+        &self,
+        pool: PgPool,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<Option<i32>, sqlx::Error>;
+
+    /// Links an external identity to a user using a transaction.
+    async fn create_user_identity(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        identity: NewUserIdentity,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Stores a pending OpenID Connect login, dropping expired ones.
+    async fn create_oidc_login_state(
+        &self,
+        pool: PgPool,
+        state: NewOidcLoginState,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Removes and returns a pending OpenID Connect login, so each state is used once.
+    async fn take_oidc_login_state(
+        &self,
+        pool: PgPool,
+        state_hash: &str,
+    ) -> Result<Option<OidcLoginState>, sqlx::Error>;
+
+    /// Finds the username and email address of a user.
+    async fn find_contact_by_user_id(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+    ) -> Result<Option<UserContact>, sqlx::Error>;
+
+    /// Finds the users with credentials registered under the given email address.
+    async fn find_contacts_by_email(
+        &self,
+        pool: PgPool,
+        email: &str,
+    ) -> Result<Vec<UserContact>, sqlx::Error>;
+
+    /// Finds the users with credentials and an unverified email registered under the given address.
+    async fn find_unverified_contacts_by_email(
+        &self,
+        pool: PgPool,
+        email: &str,
+    ) -> Result<Vec<UserContact>, sqlx::Error>;
+
+    /// Inserts a new user row using a transaction and returns its ID.
+    async fn create_user(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user: NewUser,
+    ) -> Result<i32, sqlx::Error>;
 
     /// Inserts a new user authentication record into the database using a transaction.
     async fn create(
@@ -24,4 +112,285 @@ pub trait UserAuthRepository: Send + Sync {
         tx: &mut Transaction<'_, Postgres>,
         user_auth: UserAuth,
     ) -> Result<(), sqlx::Error>;
+
+    /// Stores a new refresh token using a transaction.
+    async fn create_refresh_token(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        token: NewRefreshToken,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Finds a refresh token by its hash and locks it for the rest of the transaction.
+    async fn find_refresh_token_for_update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        token_hash: &str,
+    ) -> Result<Option<RefreshToken>, sqlx::Error>;
+
+    /// Marks a refresh token as used after it has been rotated.
+    async fn mark_refresh_token_used(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Revokes every token of a refresh token family that is not revoked yet.
+    async fn revoke_refresh_token_family(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        family_id: Uuid,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Revokes every refresh token of the user, ending all of their sessions.
+    async fn revoke_user_refresh_tokens(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Records a new login session using a transaction.
+    async fn create_session(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        session: NewSession,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Updates when and from where the session was last seen.
+    async fn touch_session(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        session_id: Uuid,
+        ip: &str,
+        user_agent: Option<&str>,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Retrieves the unrevoked sessions of the user, most recently seen first.
+    async fn find_active_sessions(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+    ) -> Result<Vec<Session>, sqlx::Error>;
+
+    /// Returns whether the session exists and has not been revoked.
+    async fn is_session_active(&self, pool: PgPool, session_id: Uuid) -> Result<bool, sqlx::Error>;
+
+    /// Revokes a session of the user. Returns `false` if the user has no such active session.
+    async fn revoke_session(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        session_id: Uuid,
+    ) -> Result<bool, sqlx::Error>;
+
+    /// Revokes every active session of the user and returns their IDs.
+    async fn revoke_user_sessions(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<Vec<Uuid>, sqlx::Error>;
+
+    /// Replaces the password hash of the user.
+    async fn update_password_hash(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        password_hash: &str,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Stores a new password reset token using a transaction.
+    async fn create_password_reset_token(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        token: NewPasswordResetToken,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Finds a password reset token by its hash and locks it for the rest of the transaction.
+    async fn find_password_reset_token_for_update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        token_hash: &str,
+    ) -> Result<Option<PasswordResetToken>, sqlx::Error>;
+
+    /// Marks every unused password reset token of the user as used.
+    async fn invalidate_password_reset_tokens(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Returns when the latest password reset token of the user was issued, if ever.
+    async fn find_latest_password_reset_at(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error>;
+
+    /// Stores a new email verification token using a transaction.
+    async fn create_email_verification_token(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        token: NewEmailVerificationToken,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Finds an email verification token by its hash and locks it for the rest of the transaction.
+    async fn find_email_verification_token_for_update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        token_hash: &str,
+    ) -> Result<Option<EmailVerificationToken>, sqlx::Error>;
+
+    /// Marks every unused email verification token of the user as used.
+    async fn invalidate_email_verification_tokens(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Returns when the latest email verification token of the user was issued, if ever.
+    async fn find_latest_email_verification_at(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error>;
+
+    /// Marks the email address of the user as verified.
+    async fn mark_email_verified(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Finds the TOTP secret of the user, if one was enrolled.
+    async fn find_totp(&self, pool: PgPool, user_id: i32) -> Result<Option<UserTotp>, sqlx::Error>;
+
+    /// Finds the TOTP secret of the user and locks it for the rest of the transaction.
+    async fn find_totp_for_update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<Option<UserTotp>, sqlx::Error>;
+
+    /// Stores a new unconfirmed TOTP secret, replacing a previous unconfirmed one.
+    async fn upsert_pending_totp(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+        secret: &str,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Enables two-factor login, remembering the time step of the confirming code.
+    async fn confirm_totp(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        step: i64,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Remembers the time step of the last accepted code so it cannot be replayed.
+    async fn update_totp_last_used_step(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        step: i64,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Removes the TOTP secret and recovery codes of the user.
+    async fn delete_totp(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Replaces the recovery codes of the user with the given hashes.
+    async fn replace_recovery_codes(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        code_hashes: &[String],
+    ) -> Result<(), sqlx::Error>;
+
+    /// Finds the unused recovery codes of the user and locks them for the rest of the transaction.
+    async fn find_unused_recovery_codes_for_update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<Vec<RecoveryCode>, sqlx::Error>;
+
+    /// Marks a recovery code as used.
+    async fn mark_recovery_code_used(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Stores a new login challenge.
+    async fn create_login_challenge(
+        &self,
+        pool: PgPool,
+        challenge: NewLoginChallenge,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Finds a login challenge by its hash and locks it for the rest of the transaction.
+    async fn find_login_challenge_for_update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        token_hash: &str,
+    ) -> Result<Option<LoginChallenge>, sqlx::Error>;
+
+    /// Counts a wrong code against a login challenge.
+    async fn record_login_challenge_attempt(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Marks a login challenge as used once the second factor was accepted.
+    async fn mark_login_challenge_used(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Returns the latest active lockout of the username or the IP, if any.
+    async fn find_login_lockout(
+        &self,
+        pool: PgPool,
+        username: &str,
+        ip: &str,
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error>;
+
+    /// Returns the active lockout of the scope and subject, if any.
+    async fn find_scope_lockout(
+        &self,
+        pool: PgPool,
+        scope: LoginScope,
+        subject: &str,
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error>;
+
+    /// Counts a failed login and returns the number of failures within the window.
+    /// Counters whose last failure is older than the window start over.
+    async fn record_login_failure(
+        &self,
+        pool: PgPool,
+        scope: LoginScope,
+        subject: &str,
+        window_secs: i64,
+    ) -> Result<i32, sqlx::Error>;
+
+    /// Locks the username or IP out of logging in until the given time.
+    async fn lock_login(
+        &self,
+        pool: PgPool,
+        scope: LoginScope,
+        subject: &str,
+        locked_until: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Forgets the failed logins of the username or IP.
+    async fn clear_login_failures(
+        &self,
+        pool: PgPool,
+        scope: LoginScope,
+        subject: &str,
+    ) -> Result<(), sqlx::Error>;
 }
@@ -1,16 +1,27 @@
 //! This module defines the authentication service trait used to abstract
-//! user login and registration logic.
+//! user login, two-factor authentication, registration, email verification
+//! and password management logic.
 
 use std::sync::Arc;
 
 use sqlx::PgPool;
+use uuid::Uuid;
 
 use crate::{
     common::{
+        client_ip::ClientInfo,
+        config::Config,
         error::AppError,
-        jwt::{AuthBody, AuthPayload},
+        jwt::{AuthBody, AuthPayload, JwtKeys},
+        mailer::Mailer,
+        oidc::OidcClient,
+    },
+    domains::auth::dto::auth_dto::{
+        ChangePasswordDto, ConfirmTotpDto, DisableTotpDto, ForgotPasswordDto, LoginOutcome,
+        OidcAuthorizationDto, OidcCallbackDto, RecoveryCodesDto, RefreshTokenDto,
+        ResendVerificationDto, ResetPasswordDto, SessionDto, SignupDto, TotpEnrollmentDto,
+        TwoFactorLoginDto, VerifyEmailDto,
     },
-    domains::auth::dto::auth_dto::AuthUserDto,
 };
 
 #[async_trait::async_trait]
@@ -18,13 +29,113 @@ use crate::{
 /// Implementors are responsible for handling user creation and login logic.
 pub trait AuthServiceTrait: Send + Sync {
     /// constructor for the service.
-    fn create_service(pool: PgPool) -> Arc<dyn AuthServiceTrait>
+    fn create_service(
+        pool: PgPool,
+        config: Config,
+        jwt_keys: Arc<JwtKeys>,
+        mailer: Arc<dyn Mailer>,
+        oidc: Option<Arc<OidcClient>>,
+    ) -> Arc<dyn AuthServiceTrait>
     where
         Self: Sized;
 
-    /// Registers a new user authentication entry.
-    async fn create_user_auth(&self, auth_user: AuthUserDto) -> Result<(), AppError>;
+    /// Creates a user with credentials and emails a verification link.
+    /// The user is signed in right away unless the policy requires a verified email to log in,
+    /// in which case `None` is returned.
+    async fn signup(
+        &self,
+        payload: SignupDto,
+        client: ClientInfo,
+    ) -> Result<Option<AuthBody>, AppError>;
+
+    /// Authenticates a user and returns a JWT token payload on success,
+    /// or a challenge token when the account has two-factor login enabled.
+    /// Failed attempts are throttled per username and per client IP.
+    async fn login_user(
+        &self,
+        auth_payload: AuthPayload,
+        client: ClientInfo,
+    ) -> Result<LoginOutcome, AppError>;
+
+    /// Completes a two-factor login with a TOTP or recovery code.
+    async fn login_two_factor(
+        &self,
+        payload: TwoFactorLoginDto,
+        client: ClientInfo,
+    ) -> Result<AuthBody, AppError>;
+
+    /// Starts a login at the external OpenID Connect provider.
+    async fn oidc_authorize(&self) -> Result<OidcAuthorizationDto, AppError>;
+
+    /// Completes a login at the external provider, linking or creating the user on first use.
+    async fn oidc_callback(
+        &self,
+        payload: OidcCallbackDto,
+        client: ClientInfo,
+    ) -> Result<LoginOutcome, AppError>;
+
+    /// Generates a new TOTP secret for the user, pending confirmation.
+    async fn enroll_totp(&self, user_id: i32) -> Result<TotpEnrollmentDto, AppError>;
+
+    /// Enables two-factor login once a code from the enrolled secret checks out
+    /// and returns fresh recovery codes.
+    async fn confirm_totp(
+        &self,
+        user_id: i32,
+        payload: ConfirmTotpDto,
+    ) -> Result<RecoveryCodesDto, AppError>;
+
+    /// Turns two-factor login off after verifying the current password or a second factor.
+    async fn disable_totp(&self, user_id: i32, payload: DisableTotpDto) -> Result<(), AppError>;
+
+    /// Exchanges a refresh token for a new token pair, rotating the refresh token.
+    async fn refresh_token(
+        &self,
+        payload: RefreshTokenDto,
+        client: ClientInfo,
+    ) -> Result<AuthBody, AppError>;
+
+    /// Ends the session of the refresh token, revoking every token rotated from the same login.
+    async fn logout_user(&self, payload: RefreshTokenDto) -> Result<(), AppError>;
+
+    /// Lists the active sessions of the user, flagging the current one.
+    async fn list_sessions(
+        &self,
+        user_id: i32,
+        current_session_id: Option<Uuid>,
+    ) -> Result<Vec<SessionDto>, AppError>;
+
+    /// Ends one session of the user, signing that device out.
+    async fn revoke_session(&self, user_id: i32, session_id: Uuid) -> Result<(), AppError>;
+
+    /// Ends every session of the user, including the current one.
+    async fn revoke_all_sessions(&self, user_id: i32) -> Result<(), AppError>;
+
+    /// Returns whether access tokens of the session are still accepted.
+    async fn is_session_active(&self, session_id: Uuid) -> Result<bool, AppError>;
+
+    /// Changes the password after verifying the current one.
+    /// Every session is ended and a new one is started for the caller.
+    async fn change_password(
+        &self,
+        user_id: i32,
+        payload: ChangePasswordDto,
+        client: ClientInfo,
+    ) -> Result<AuthBody, AppError>;
+
+    /// Emails a single-use password reset token to the accounts registered under the address.
+    async fn forgot_password(
+        &self,
+        payload: ForgotPasswordDto,
+        client: ClientInfo,
+    ) -> Result<(), AppError>;
+
+    /// Sets a new password using a reset token and ends every session of the user.
+    async fn reset_password(&self, payload: ResetPasswordDto) -> Result<(), AppError>;
+
+    /// Marks the email address of the user as verified using an emailed token.
+    async fn verify_email(&self, payload: VerifyEmailDto) -> Result<(), AppError>;
 
-    /// Authenticates a user and returns a JWT token payload on success.
-    async fn login_user(&self, auth_payload: AuthPayload) -> Result<AuthBody, AppError>;
+    /// Emails a new verification link to the unverified accounts registered under the address.
+    async fn resend_verification(&self, payload: ResendVerificationDto) -> Result<(), AppError>;
 }
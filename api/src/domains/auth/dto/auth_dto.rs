@@ -1,9 +1,171 @@
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
+use uuid::Uuid;
 use validator::Validate;
 
+use crate::{
+    common::{jwt::AuthBody, password_policy::validate_password},
+    domains::auth::domain::model::Session,
+};
+
+/// Request body for creating an account together with its credentials.
 #[derive(Debug, Deserialize, ToSchema, Validate)]
-pub struct AuthUserDto {
-    pub user_id: i32,
+pub struct SignupDto {
+    #[validate(length(
+        min = 1,
+        max = 64,
+        message = "Username must be between 1 and 64 characters"
+    ))]
+    #[schema(example = "alice")]
+    pub username: String,
+    #[validate(email(message = "Invalid email format"))]
+    #[schema(example = "alice@example.com")]
+    pub email: String,
+    #[validate(custom(function = "validate_password"))]
     pub password: String,
 }
+
+/// Request body carrying a refresh token, used to renew or revoke a session.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RefreshTokenDto {
+    pub refresh_token: String,
+}
+
+/// Request body for changing the password of the signed-in user.
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct ChangePasswordDto {
+    pub current_password: String,
+    #[validate(custom(function = "validate_password"))]
+    pub new_password: String,
+}
+
+/// Request body for requesting a password reset email.
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct ForgotPasswordDto {
+    #[validate(email(message = "Invalid email format"))]
+    #[schema(example = "alice@example.com")]
+    pub email: String,
+}
+
+/// Request body for setting a new password with an emailed reset token.
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct ResetPasswordDto {
+    pub token: String,
+    #[validate(custom(function = "validate_password"))]
+    pub new_password: String,
+}
+
+/// Request body for verifying an email address with an emailed token.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct VerifyEmailDto {
+    pub token: String,
+}
+
+/// Request body for sending a new email verification link.
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct ResendVerificationDto {
+    #[validate(email(message = "Invalid email format"))]
+    #[schema(example = "alice@example.com")]
+    pub email: String,
+}
+
+/// Result of the password step of a login: either the tokens,
+/// or a challenge to complete with a second factor.
+#[derive(Debug)]
+pub enum LoginOutcome {
+    Authenticated(AuthBody),
+    TwoFactorRequired(TwoFactorChallengeDto),
+}
+
+/// Returned instead of tokens when the account has two-factor login enabled.
+/// `expires_in` is the lifetime of the challenge token in seconds.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TwoFactorChallengeDto {
+    pub challenge_token: String,
+    pub expires_in: i64,
+}
+
+/// Request body completing a login with a TOTP code or, failing that, a recovery code.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TwoFactorLoginDto {
+    pub challenge_token: String,
+    #[schema(example = "123456")]
+    pub code: Option<String>,
+    #[schema(example = "k3v9q-7xwmz")]
+    pub recovery_code: Option<String>,
+}
+
+/// TOTP secret to add to an authenticator app, directly or as a QR code of the URI.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TotpEnrollmentDto {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+/// Request body confirming TOTP enrollment with a code from the authenticator app.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ConfirmTotpDto {
+    #[schema(example = "123456")]
+    pub code: String,
+}
+
+/// Recovery codes, shown once when two-factor login is enabled.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RecoveryCodesDto {
+    pub recovery_codes: Vec<String>,
+}
+
+/// Request body for turning two-factor login off, proven with the current password,
+/// a TOTP code or a recovery code. Accounts created through OIDC have no password.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DisableTotpDto {
+    pub current_password: Option<String>,
+    #[schema(example = "123456")]
+    pub code: Option<String>,
+    #[schema(example = "k3v9q-7xwmz")]
+    pub recovery_code: Option<String>,
+}
+
+/// Where to send the user to sign in with the external identity provider.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OidcAuthorizationDto {
+    pub authorization_url: String,
+}
+
+/// Request body with the parameters the identity provider redirected back with.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct OidcCallbackDto {
+    pub code: String,
+    pub state: String,
+}
+
+/// An active login session of the signed-in user.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SessionDto {
+    pub id: Uuid,
+    pub user_agent: Option<String>,
+    /// Client IP at login or at the last token refresh.
+    pub ip: String,
+    #[serde(with = "crate::common::ts_format")]
+    pub created_at: DateTime<Utc>,
+    /// When the session logged in or last refreshed its tokens.
+    #[serde(with = "crate::common::ts_format")]
+    pub last_seen_at: DateTime<Utc>,
+    /// Whether this is the session of the access token used for the request.
+    pub current: bool,
+}
+
+impl SessionDto {
+    /// Builds the DTO, flagging the session the request was made with.
+    pub fn from_session(session: Session, current_session_id: Option<Uuid>) -> Self {
+        Self {
+            id: session.id,
+            user_agent: session.user_agent,
+            ip: session.ip,
+            created_at: session.created_at,
+            last_seen_at: session.last_seen_at,
+            current: current_session_id == Some(session.id),
+        }
+    }
+}
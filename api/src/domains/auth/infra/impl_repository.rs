@@ -1,7 +1,15 @@
+use crate::common::authz::Role;
+use crate::domains::auth::domain::model::{
+    EmailVerificationToken, LoginChallenge, LoginScope, NewEmailVerificationToken,
+    NewLoginChallenge, NewOidcLoginState, NewPasswordResetToken, NewRefreshToken, NewSession,
+    NewUser, NewUserIdentity, OidcLoginState, PasswordResetToken, RecoveryCode, RefreshToken,
+    Session, UserAuth, UserContact, UserCredentials, UserStatus, UserTotp,
+};
+use crate::domains::auth::domain::repository::UserAuthRepository;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use sqlx::{PgPool, Postgres, Transaction};
-use crate::domains::auth::domain::model::UserAuth;
-use crate::domains::auth::domain::repository::UserAuthRepository;
+use uuid::Uuid;
 
 pub struct UserAuthRepo;
 
@@ -11,11 +19,12 @@ impl UserAuthRepository for UserAuthRepo {
         &self,
         pool: PgPool,
         user_name: String,
-    ) -> Result<Option<UserAuth>, sqlx::Error> {
+    ) -> Result<Option<UserCredentials>, sqlx::Error> {
         let result = sqlx::query_as!(
-            UserAuth,
+            UserCredentials,
             r#"
-            SELECT ua.user_id, ua.password_hash
+            SELECT ua.user_id, ua.password_hash, u.role as "role: Role",
+                   u.email_verified_at IS NOT NULL as "email_verified!"
             FROM user_auth ua
             JOIN users u ON ua.user_id = u.id
             WHERE u.username = $1
@@ -28,6 +37,242 @@ impl UserAuthRepository for UserAuthRepo {
         Ok(result)
     }
 
+    async fn find_by_user_id(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+    ) -> Result<Option<UserCredentials>, sqlx::Error> {
+        let result = sqlx::query_as!(
+            UserCredentials,
+            r#"
+            SELECT ua.user_id, ua.password_hash, u.role as "role: Role",
+                   u.email_verified_at IS NOT NULL as "email_verified!"
+            FROM user_auth ua
+            JOIN users u ON ua.user_id = u.id
+            WHERE ua.user_id = $1
+            "#,
+            user_id
+        )
+        .fetch_optional(&pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn find_user_status(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+    ) -> Result<Option<UserStatus>, sqlx::Error> {
+        let status = sqlx::query_as!(
+            UserStatus,
+            r#"
+            SELECT id as user_id, role as "role: Role",
+                   email_verified_at IS NOT NULL as "email_verified!"
+            FROM users
+            WHERE id = $1
+            "#,
+            user_id
+        )
+        .fetch_optional(&pool)
+        .await?;
+
+        Ok(status)
+    }
+
+    async fn username_exists(&self, pool: PgPool, username: &str) -> Result<bool, sqlx::Error> {
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS (SELECT 1 FROM users WHERE username = $1) as "exists!""#,
+            username
+        )
+        .fetch_one(&pool)
+        .await?;
+
+        Ok(exists)
+    }
+
+    async fn find_verified_user_ids_by_email(
+        &self,
+        pool: PgPool,
+        email: &str,
+    ) -> Result<Vec<i32>, sqlx::Error> {
+        let user_ids = sqlx::query_scalar!(
+            r#"
+            SELECT id
+            FROM users
+            WHERE lower(email) = lower($1) AND email_verified_at IS NOT NULL
+            ORDER BY id
+            "#,
+            email
+        )
+        .fetch_all(&pool)
+        .await?;
+
+        Ok(user_ids)
+    }
+
+    async fn find_identity_user_id(
+        &self,
+        pool: PgPool,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<Option<i32>, sqlx::Error> {
+        let user_id = sqlx::query_scalar!(
+            r#"SELECT user_id FROM user_identities WHERE issuer = $1 AND subject = $2"#,
+            issuer,
+            subject
+        )
+        .fetch_optional(&pool)
+        .await?;
+
+        Ok(user_id)
+    }
+
+    async fn create_user_identity(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        identity: NewUserIdentity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO user_identities (user_id, issuer, subject, email)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            identity.user_id,
+            identity.issuer,
+            identity.subject,
+            identity.email
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn create_oidc_login_state(
+        &self,
+        pool: PgPool,
+        state: NewOidcLoginState,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(r#"DELETE FROM oidc_login_states WHERE expires_at < now()"#)
+            .execute(&pool)
+            .await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO oidc_login_states (state_hash, nonce, code_verifier, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            state.state_hash,
+            state.nonce,
+            state.code_verifier,
+            state.expires_at
+        )
+        .execute(&pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn take_oidc_login_state(
+        &self,
+        pool: PgPool,
+        state_hash: &str,
+    ) -> Result<Option<OidcLoginState>, sqlx::Error> {
+        let state = sqlx::query_as!(
+            OidcLoginState,
+            r#"
+            DELETE FROM oidc_login_states
+            WHERE state_hash = $1
+            RETURNING nonce, code_verifier, expires_at
+            "#,
+            state_hash
+        )
+        .fetch_optional(&pool)
+        .await?;
+
+        Ok(state)
+    }
+
+    async fn find_contact_by_user_id(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+    ) -> Result<Option<UserContact>, sqlx::Error> {
+        let contact = sqlx::query_as!(
+            UserContact,
+            r#"SELECT id as user_id, username, email FROM users WHERE id = $1"#,
+            user_id
+        )
+        .fetch_optional(&pool)
+        .await?;
+
+        Ok(contact)
+    }
+
+    async fn find_contacts_by_email(
+        &self,
+        pool: PgPool,
+        email: &str,
+    ) -> Result<Vec<UserContact>, sqlx::Error> {
+        let contacts = sqlx::query_as!(
+            UserContact,
+            r#"
+            SELECT u.id as user_id, u.username, u.email
+            FROM users u
+            JOIN user_auth ua ON ua.user_id = u.id
+            WHERE lower(u.email) = lower($1)
+            ORDER BY u.id
+            "#,
+            email
+        )
+        .fetch_all(&pool)
+        .await?;
+
+        Ok(contacts)
+    }
+
+    async fn find_unverified_contacts_by_email(
+        &self,
+        pool: PgPool,
+        email: &str,
+    ) -> Result<Vec<UserContact>, sqlx::Error> {
+        let contacts = sqlx::query_as!(
+            UserContact,
+            r#"
+            SELECT u.id as user_id, u.username, u.email
+            FROM users u
+            JOIN user_auth ua ON ua.user_id = u.id
+            WHERE lower(u.email) = lower($1) AND u.email_verified_at IS NULL
+            ORDER BY u.id
+            "#,
+            email
+        )
+        .fetch_all(&pool)
+        .await?;
+
+        Ok(contacts)
+    }
+
+    async fn create_user(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user: NewUser,
+    ) -> Result<i32, sqlx::Error> {
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO users (username, email)
+            VALUES ($1, $2)
+            RETURNING id
+            "#,
+            user.username,
+            user.email
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        Ok(inserted.id)
+    }
+
     async fn create(
         &self,
         tx: &mut Transaction<'_, Postgres>,
@@ -46,4 +291,771 @@ impl UserAuthRepository for UserAuthRepo {
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    async fn create_refresh_token(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        token: NewRefreshToken,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO refresh_tokens (user_id, family_id, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            token.user_id,
+            token.family_id,
+            token.token_hash,
+            token.expires_at
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_refresh_token_for_update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        token_hash: &str,
+    ) -> Result<Option<RefreshToken>, sqlx::Error> {
+        let token = sqlx::query_as!(
+            RefreshToken,
+            r#"
+            SELECT rt.id, rt.user_id, rt.family_id, rt.expires_at, rt.used_at, rt.revoked_at,
+                   u.role as "role: Role", u.email_verified_at IS NOT NULL as "email_verified!"
+            FROM refresh_tokens rt
+            JOIN users u ON rt.user_id = u.id
+            WHERE rt.token_hash = $1
+            FOR UPDATE OF rt
+            "#,
+            token_hash
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        Ok(token)
+    }
+
+    async fn mark_refresh_token_used(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE refresh_tokens SET used_at = now() WHERE id = $1"#,
+            id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_refresh_token_family(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        family_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = now()
+            WHERE family_id = $1 AND revoked_at IS NULL
+            "#,
+            family_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_user_refresh_tokens(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = now()
+            WHERE user_id = $1 AND revoked_at IS NULL
+            "#,
+            user_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn create_session(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        session: NewSession,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO sessions (id, user_id, user_agent, ip)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            session.id,
+            session.user_id,
+            session.user_agent,
+            session.ip
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn touch_session(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        session_id: Uuid,
+        ip: &str,
+        user_agent: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE sessions
+            SET last_seen_at = now(), ip = $2, user_agent = COALESCE($3, user_agent)
+            WHERE id = $1
+            "#,
+            session_id,
+            ip,
+            user_agent
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_active_sessions(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+    ) -> Result<Vec<Session>, sqlx::Error> {
+        let sessions = sqlx::query_as!(
+            Session,
+            r#"
+            SELECT id, user_agent, ip, created_at, last_seen_at
+            FROM sessions
+            WHERE user_id = $1 AND revoked_at IS NULL
+            ORDER BY last_seen_at DESC
+            "#,
+            user_id
+        )
+        .fetch_all(&pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    async fn is_session_active(&self, pool: PgPool, session_id: Uuid) -> Result<bool, sqlx::Error> {
+        let active = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM sessions WHERE id = $1 AND revoked_at IS NULL
+            ) as "active!"
+            "#,
+            session_id
+        )
+        .fetch_one(&pool)
+        .await?;
+
+        Ok(active)
+    }
+
+    async fn revoke_session(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        session_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let res = sqlx::query!(
+            r#"
+            UPDATE sessions
+            SET revoked_at = now()
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+            "#,
+            session_id,
+            user_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn revoke_user_sessions(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let ids = sqlx::query_scalar!(
+            r#"
+            UPDATE sessions
+            SET revoked_at = now()
+            WHERE user_id = $1 AND revoked_at IS NULL
+            RETURNING id
+            "#,
+            user_id
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+
+        Ok(ids)
+    }
+
+    async fn update_password_hash(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        password_hash: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE user_auth SET password_hash = $2 WHERE user_id = $1"#,
+            user_id,
+            password_hash
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn create_password_reset_token(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        token: NewPasswordResetToken,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO password_reset_tokens (user_id, token_hash, expires_at)
+            VALUES ($1, $2, $3)
+            "#,
+            token.user_id,
+            token.token_hash,
+            token.expires_at
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_password_reset_token_for_update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        token_hash: &str,
+    ) -> Result<Option<PasswordResetToken>, sqlx::Error> {
+        let token = sqlx::query_as!(
+            PasswordResetToken,
+            r#"
+            SELECT user_id, expires_at, used_at
+            FROM password_reset_tokens
+            WHERE token_hash = $1
+            FOR UPDATE
+            "#,
+            token_hash
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        Ok(token)
+    }
+
+    async fn invalidate_password_reset_tokens(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE password_reset_tokens
+            SET used_at = now()
+            WHERE user_id = $1 AND used_at IS NULL
+            "#,
+            user_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_latest_password_reset_at(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        let created_at = sqlx::query_scalar!(
+            r#"SELECT max(created_at) FROM password_reset_tokens WHERE user_id = $1"#,
+            user_id
+        )
+        .fetch_one(&pool)
+        .await?;
+
+        Ok(created_at)
+    }
+    async fn create_email_verification_token(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        token: NewEmailVerificationToken,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO email_verification_tokens (user_id, email, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            token.user_id,
+            token.email,
+            token.token_hash,
+            token.expires_at
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_email_verification_token_for_update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        token_hash: &str,
+    ) -> Result<Option<EmailVerificationToken>, sqlx::Error> {
+        let token = sqlx::query_as!(
+            EmailVerificationToken,
+            r#"
+            SELECT evt.user_id, evt.expires_at, evt.used_at,
+                   evt.email = u.email as "email_matches!"
+            FROM email_verification_tokens evt
+            JOIN users u ON evt.user_id = u.id
+            WHERE evt.token_hash = $1
+            FOR UPDATE OF evt
+            "#,
+            token_hash
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        Ok(token)
+    }
+
+    async fn invalidate_email_verification_tokens(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE email_verification_tokens
+            SET used_at = now()
+            WHERE user_id = $1 AND used_at IS NULL
+            "#,
+            user_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_latest_email_verification_at(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        let created_at = sqlx::query_scalar!(
+            r#"SELECT max(created_at) FROM email_verification_tokens WHERE user_id = $1"#,
+            user_id
+        )
+        .fetch_one(&pool)
+        .await?;
+
+        Ok(created_at)
+    }
+
+    async fn mark_email_verified(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE users
+            SET email_verified_at = now()
+            WHERE id = $1 AND email_verified_at IS NULL
+            "#,
+            user_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_totp(&self, pool: PgPool, user_id: i32) -> Result<Option<UserTotp>, sqlx::Error> {
+        let totp = sqlx::query_as!(
+            UserTotp,
+            r#"SELECT secret, confirmed_at, last_used_step FROM user_totp WHERE user_id = $1"#,
+            user_id
+        )
+        .fetch_optional(&pool)
+        .await?;
+
+        Ok(totp)
+    }
+
+    async fn find_totp_for_update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<Option<UserTotp>, sqlx::Error> {
+        let totp = sqlx::query_as!(
+            UserTotp,
+            r#"
+            SELECT secret, confirmed_at, last_used_step
+            FROM user_totp
+            WHERE user_id = $1
+            FOR UPDATE
+            "#,
+            user_id
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        Ok(totp)
+    }
+
+    async fn upsert_pending_totp(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+        secret: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO user_totp (user_id, secret)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE
+            SET secret = EXCLUDED.secret,
+                last_used_step = NULL,
+                created_at = now()
+            WHERE user_totp.confirmed_at IS NULL
+            "#,
+            user_id,
+            secret
+        )
+        .execute(&pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn confirm_totp(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        step: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE user_totp
+            SET confirmed_at = now(), last_used_step = $2
+            WHERE user_id = $1
+            "#,
+            user_id,
+            step
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn update_totp_last_used_step(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        step: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE user_totp SET last_used_step = $2 WHERE user_id = $1"#,
+            user_id,
+            step
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_totp(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"DELETE FROM totp_recovery_codes WHERE user_id = $1"#,
+            user_id
+        )
+        .execute(&mut **tx)
+        .await?;
+        sqlx::query!(r#"DELETE FROM user_totp WHERE user_id = $1"#, user_id)
+            .execute(&mut **tx)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn replace_recovery_codes(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        code_hashes: &[String],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"DELETE FROM totp_recovery_codes WHERE user_id = $1"#,
+            user_id
+        )
+        .execute(&mut **tx)
+        .await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO totp_recovery_codes (user_id, code_hash)
+            SELECT $1, code_hash FROM UNNEST($2::varchar[]) AS code_hash
+            "#,
+            user_id,
+            code_hashes
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_unused_recovery_codes_for_update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<Vec<RecoveryCode>, sqlx::Error> {
+        let codes = sqlx::query_as!(
+            RecoveryCode,
+            r#"
+            SELECT id, code_hash
+            FROM totp_recovery_codes
+            WHERE user_id = $1 AND used_at IS NULL
+            ORDER BY id
+            FOR UPDATE
+            "#,
+            user_id
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+
+        Ok(codes)
+    }
+
+    async fn mark_recovery_code_used(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE totp_recovery_codes SET used_at = now() WHERE id = $1"#,
+            id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn create_login_challenge(
+        &self,
+        pool: PgPool,
+        challenge: NewLoginChallenge,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO login_challenges (user_id, token_hash, expires_at)
+            VALUES ($1, $2, $3)
+            "#,
+            challenge.user_id,
+            challenge.token_hash,
+            challenge.expires_at
+        )
+        .execute(&pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_login_challenge_for_update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        token_hash: &str,
+    ) -> Result<Option<LoginChallenge>, sqlx::Error> {
+        let challenge = sqlx::query_as!(
+            LoginChallenge,
+            r#"
+            SELECT id, user_id, expires_at, attempts, used_at
+            FROM login_challenges
+            WHERE token_hash = $1
+            FOR UPDATE
+            "#,
+            token_hash
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        Ok(challenge)
+    }
+
+    async fn record_login_challenge_attempt(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE login_challenges SET attempts = attempts + 1 WHERE id = $1"#,
+            id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn mark_login_challenge_used(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE login_challenges SET used_at = now() WHERE id = $1"#,
+            id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_login_lockout(
+        &self,
+        pool: PgPool,
+        username: &str,
+        ip: &str,
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        let locked_until = sqlx::query_scalar!(
+            r#"
+            SELECT max(locked_until)
+            FROM login_failures
+            WHERE locked_until > now()
+              AND ((scope = 'username' AND subject = $1) OR (scope = 'ip' AND subject = $2))
+            "#,
+            username,
+            ip
+        )
+        .fetch_one(&pool)
+        .await?;
+
+        Ok(locked_until)
+    }
+
+    async fn find_scope_lockout(
+        &self,
+        pool: PgPool,
+        scope: LoginScope,
+        subject: &str,
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        let locked_until = sqlx::query_scalar!(
+            r#"
+            SELECT locked_until AS "locked_until!"
+            FROM login_failures
+            WHERE scope = $1 AND subject = $2 AND locked_until > now()
+            "#,
+            scope.as_str(),
+            subject
+        )
+        .fetch_optional(&pool)
+        .await?;
+
+        Ok(locked_until)
+    }
+
+    async fn record_login_failure(
+        &self,
+        pool: PgPool,
+        scope: LoginScope,
+        subject: &str,
+        window_secs: i64,
+    ) -> Result<i32, sqlx::Error> {
+        let failures = sqlx::query_scalar!(
+            r#"
+            INSERT INTO login_failures (scope, subject, failures, last_failure_at)
+            VALUES ($1, $2, 1, now())
+            ON CONFLICT (scope, subject) DO UPDATE
+            SET failures = CASE
+                    WHEN login_failures.last_failure_at < now() - make_interval(secs => $3)
+                    THEN 1
+                    ELSE login_failures.failures + 1
+                END,
+                last_failure_at = now()
+            RETURNING failures
+            "#,
+            scope.as_str(),
+            subject,
+            window_secs as f64
+        )
+        .fetch_one(&pool)
+        .await?;
+
+        Ok(failures)
+    }
+
+    async fn lock_login(
+        &self,
+        pool: PgPool,
+        scope: LoginScope,
+        subject: &str,
+        locked_until: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE login_failures
+            SET locked_until = $3
+            WHERE scope = $1 AND subject = $2
+            "#,
+            scope.as_str(),
+            subject,
+            locked_until
+        )
+        .execute(&pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn clear_login_failures(
+        &self,
+        pool: PgPool,
+        scope: LoginScope,
+        subject: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"DELETE FROM login_failures WHERE scope = $1 AND subject = $2"#,
+            scope.as_str(),
+            subject
+        )
+        .execute(&pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -2,18 +2,39 @@ use std::sync::Arc;
 
 use crate::{
     common::{
+        authz::Role,
+        client_ip::ClientInfo,
+        config::Config,
         error::AppError,
         hash_util,
-        jwt::{make_jwt_token, AuthBody, AuthPayload},
+        jwt::{make_jwt_token, AuthBody, AuthPayload, JwtKeys},
+        mailer::{EmailMessage, Mailer},
+        oidc::{IdTokenClaims, OidcClient, OidcError},
+        password_policy, totp,
     },
     domains::auth::{
-        domain::{model::UserAuth, repository::UserAuthRepository, service::AuthServiceTrait},
-        dto::auth_dto::AuthUserDto,
-        infra::impl_repository::UserAuthRepo,
+        domain::{
+            model::{
+                lockout_secs, login_counters, LoginScope, NewEmailVerificationToken,
+                NewLoginChallenge, NewOidcLoginState, NewPasswordResetToken, NewRefreshToken,
+                NewSession, NewUser, NewUserIdentity, UserAuth, UserContact, UserStatus,
+            },
+            repository::UserAuthRepository,
+            service::AuthServiceTrait,
+        },
+        dto::auth_dto::{
+            ChangePasswordDto, ConfirmTotpDto, DisableTotpDto, ForgotPasswordDto, LoginOutcome,
+            OidcAuthorizationDto, OidcCallbackDto, RecoveryCodesDto, RefreshTokenDto,
+            ResendVerificationDto, ResetPasswordDto, SessionDto, SignupDto, TotpEnrollmentDto,
+            TwoFactorChallengeDto, TwoFactorLoginDto, VerifyEmailDto,
+        },
+        infra::{impl_repository::UserAuthRepo, session_cache::SessionCache},
     },
 };
 
-use sqlx::PgPool;
+use chrono::{Duration, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
 
 /// Service for handling user authentication
 /// and authorization logic.
@@ -21,67 +42,1597 @@ use sqlx::PgPool;
 pub struct AuthService {
     pool: PgPool,
     repo: Arc<dyn UserAuthRepository + Send + Sync>,
+    config: Config,
+    jwt_keys: Arc<JwtKeys>,
+    mailer: Arc<dyn Mailer>,
+    oidc: Option<Arc<OidcClient>>,
+    sessions: Arc<SessionCache>,
+}
+
+impl AuthService {
+    /// Records a new session for the client and issues the first refresh token of its family.
+    /// Returns the session ID together with the raw refresh token.
+    async fn open_session(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        client: ClientInfo,
+    ) -> Result<(Uuid, String), AppError> {
+        let session = NewSession {
+            id: Uuid::new_v4(),
+            user_id,
+            user_agent: client.user_agent,
+            ip: client.ip.to_string(),
+        };
+        let session_id = session.id;
+
+        self.repo.create_session(tx, session).await.map_err(|err| {
+            tracing::error!("Error creating session: {err}");
+            AppError::DatabaseError(err)
+        })?;
+        let refresh_token = self.issue_refresh_token(tx, user_id, session_id).await?;
+
+        Ok((session_id, refresh_token))
+    }
+
+    /// Revokes a session of the user together with its refresh tokens.
+    /// Returns `false` if the user has no such active session.
+    async fn end_session(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        session_id: Uuid,
+    ) -> Result<bool, AppError> {
+        let db_error = |err: sqlx::Error| {
+            tracing::error!("Error revoking session: {err}");
+            AppError::DatabaseError(err)
+        };
+
+        let revoked = self
+            .repo
+            .revoke_session(tx, user_id, session_id)
+            .await
+            .map_err(db_error)?;
+        self.repo
+            .revoke_refresh_token_family(tx, session_id)
+            .await
+            .map_err(db_error)?;
+
+        Ok(revoked)
+    }
+
+    /// Generates a refresh token in the given family, stores its hash and returns the raw token.
+    async fn issue_refresh_token(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        family_id: Uuid,
+    ) -> Result<String, AppError> {
+        let refresh_token = hash_util::generate_token();
+        let token = NewRefreshToken {
+            user_id,
+            family_id,
+            token_hash: hash_util::hash_token(&refresh_token),
+            expires_at: Utc::now() + Duration::seconds(self.config.jwt_refresh_token_ttl_secs),
+        };
+
+        self.repo
+            .create_refresh_token(tx, token)
+            .await
+            .map_err(|err| {
+                tracing::error!("Error creating refresh token: {err}");
+                AppError::DatabaseError(err)
+            })?;
+
+        Ok(refresh_token)
+    }
+
+    /// Counts a failed login against the username and the client IP,
+    /// locking either out once it reaches its limit.
+    async fn record_login_failure(&self, username: &str, ip: &str) -> Result<(), AppError> {
+        let limits = [
+            self.config.login_max_failures_per_username,
+            self.config.login_max_failures_per_ip,
+        ];
+        for ((scope, subject), max_failures) in login_counters(username, ip).into_iter().zip(limits)
+        {
+            self.count_failure(scope, subject, max_failures).await?;
+        }
+        Ok(())
+    }
+
+    /// Counts a wrong second-factor code against the user, whichever challenge it came with,
+    /// locking two-factor login out once it reaches its limit.
+    async fn record_second_factor_failure(&self, user_id: i32) -> Result<(), AppError> {
+        self.count_failure(
+            LoginScope::SecondFactor,
+            &user_id.to_string(),
+            self.config.login_max_second_factor_failures,
+        )
+        .await
+    }
+
+    /// Fails with `TooManyAttempts` while the subject is locked out.
+    async fn check_lockout(&self, scope: LoginScope, subject: &str) -> Result<(), AppError> {
+        let locked_until = self
+            .repo
+            .find_scope_lockout(self.pool.clone(), scope, subject)
+            .await
+            .map_err(AppError::DatabaseError)?;
+        match locked_until {
+            Some(locked_until) => Err(AppError::TooManyAttempts {
+                retry_after_secs: (locked_until - Utc::now()).num_seconds().max(1),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Counts a failure against the subject and locks it out with exponential backoff
+    /// once it reaches `max_failures`.
+    async fn count_failure(
+        &self,
+        scope: LoginScope,
+        subject: &str,
+        max_failures: i32,
+    ) -> Result<(), AppError> {
+        let failures = self
+            .repo
+            .record_login_failure(
+                self.pool.clone(),
+                scope,
+                subject,
+                self.config.login_failure_window_secs,
+            )
+            .await
+            .map_err(|err| {
+                tracing::error!("Error recording failed login: {err}");
+                AppError::DatabaseError(err)
+            })?;
+
+        let Some(secs) = lockout_secs(
+            failures,
+            max_failures,
+            self.config.login_lockout_base_secs,
+            self.config.login_lockout_max_secs,
+        ) else {
+            return Ok(());
+        };
+
+        tracing::warn!(
+            "Locking out {} {subject} for {secs}s after {failures} failures",
+            scope.as_str()
+        );
+        self.repo
+            .lock_login(
+                self.pool.clone(),
+                scope,
+                subject,
+                Utc::now() + Duration::seconds(secs),
+            )
+            .await
+            .map_err(|err| {
+                tracing::error!("Error locking out login: {err}");
+                AppError::DatabaseError(err)
+            })
+    }
+
+    /// Replaces the user's password hash and ends all of their sessions and pending resets.
+    /// Returns the IDs of the ended sessions, to be dropped from the cache once committed.
+    async fn replace_password(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        new_password: &str,
+    ) -> Result<Vec<Uuid>, AppError> {
+        let password_hash =
+            hash_util::hash_password(new_password).map_err(|_| AppError::InternalError)?;
+
+        let db_error = |err: sqlx::Error| {
+            tracing::error!("Error updating password: {err}");
+            AppError::DatabaseError(err)
+        };
+
+        self.repo
+            .update_password_hash(tx, user_id, &password_hash)
+            .await
+            .map_err(db_error)?;
+        self.repo
+            .revoke_user_refresh_tokens(tx, user_id)
+            .await
+            .map_err(db_error)?;
+        let session_ids = self
+            .repo
+            .revoke_user_sessions(tx, user_id)
+            .await
+            .map_err(db_error)?;
+        self.repo
+            .invalidate_password_reset_tokens(tx, user_id)
+            .await
+            .map_err(db_error)?;
+
+        Ok(session_ids)
+    }
+
+    /// Creates a reset token for the account, replacing any outstanding one, and emails it.
+    async fn send_password_reset(&self, contact: UserContact) -> Result<(), AppError> {
+        let reset_token = hash_util::generate_token();
+        let token = NewPasswordResetToken {
+            user_id: contact.user_id,
+            token_hash: hash_util::hash_token(&reset_token),
+            expires_at: Utc::now() + Duration::seconds(self.config.password_reset_token_ttl_secs),
+        };
+
+        let mut tx = self.pool.begin().await?;
+        if let Err(err) = self
+            .repo
+            .invalidate_password_reset_tokens(&mut tx, contact.user_id)
+            .await
+        {
+            tracing::error!("Error invalidating password reset tokens: {err}");
+            tx.rollback().await?;
+            return Err(AppError::DatabaseError(err));
+        }
+        if let Err(err) = self.repo.create_password_reset_token(&mut tx, token).await {
+            tracing::error!("Error creating password reset token: {err}");
+            tx.rollback().await?;
+            return Err(AppError::DatabaseError(err));
+        }
+        tx.commit().await?;
+
+        let message = EmailMessage {
+            to: contact.email,
+            subject: "Reset your password".into(),
+            body: format!(
+                "Hi {},\n\nUse the link below to choose a new password. \
+                 It expires in {} minutes and can only be used once.\n\n{}?token={}\n\n\
+                 If you did not ask for this, you can ignore this email.",
+                contact.username,
+                self.config.password_reset_token_ttl_secs / 60,
+                self.config.password_reset_url,
+                reset_token
+            ),
+        };
+        self.mailer.send(message).await
+    }
+
+    /// Emails a password reset to each account registered under the address, skipping
+    /// accounts that were sent one within the resend interval.
+    async fn send_password_resets(&self, email: &str) -> Result<(), AppError> {
+        let contacts = self
+            .repo
+            .find_contacts_by_email(self.pool.clone(), email)
+            .await
+            .map_err(|err| {
+                tracing::error!("Error retrieving users by email: {err}");
+                AppError::DatabaseError(err)
+            })?;
+
+        let resend_after =
+            Utc::now() - Duration::seconds(self.config.password_reset_resend_interval_secs);
+        for contact in contacts {
+            let user_id = contact.user_id;
+            let last_sent_at = self
+                .repo
+                .find_latest_password_reset_at(self.pool.clone(), user_id)
+                .await
+                .map_err(|err| {
+                    tracing::error!("Error retrieving password reset tokens: {err}");
+                    AppError::DatabaseError(err)
+                })?;
+            if last_sent_at.is_some_and(|sent_at| sent_at > resend_after) {
+                tracing::warn!("Skipping password reset for user {user_id}: rate limited");
+                continue;
+            }
+
+            if let Err(err) = self.send_password_reset(contact).await {
+                tracing::error!("Error sending password reset to user {user_id}: {err}");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creates a verification token for the account's current address,
+    /// replacing any outstanding one, and emails it.
+    async fn send_email_verification(&self, contact: UserContact) -> Result<(), AppError> {
+        let verification_token = hash_util::generate_token();
+        let token = NewEmailVerificationToken {
+            user_id: contact.user_id,
+            email: contact.email.clone(),
+            token_hash: hash_util::hash_token(&verification_token),
+            expires_at: Utc::now()
+                + Duration::seconds(self.config.email_verification_token_ttl_secs),
+        };
+
+        let mut tx = self.pool.begin().await?;
+        if let Err(err) = self
+            .repo
+            .invalidate_email_verification_tokens(&mut tx, contact.user_id)
+            .await
+        {
+            tracing::error!("Error invalidating email verification tokens: {err}");
+            tx.rollback().await?;
+            return Err(AppError::DatabaseError(err));
+        }
+        if let Err(err) = self
+            .repo
+            .create_email_verification_token(&mut tx, token)
+            .await
+        {
+            tracing::error!("Error creating email verification token: {err}");
+            tx.rollback().await?;
+            return Err(AppError::DatabaseError(err));
+        }
+        tx.commit().await?;
+
+        let message = EmailMessage {
+            to: contact.email,
+            subject: "Verify your email address".into(),
+            body: format!(
+                "Hi {},\n\nPlease confirm your email address using the link below. \
+                 It expires in {} hours.\n\n{}?token={}\n\n\
+                 If you did not create an account, you can ignore this email.",
+                contact.username,
+                self.config.email_verification_token_ttl_secs / 3600,
+                self.config.email_verification_url,
+                verification_token
+            ),
+        };
+        self.mailer.send(message).await
+    }
+
+    /// Starts a new session for the user and issues its first token pair.
+    async fn start_session(
+        &self,
+        user: &UserStatus,
+        client: ClientInfo,
+    ) -> Result<AuthBody, AppError> {
+        let mut tx = self.pool.begin().await?;
+        let (session_id, refresh_token) =
+            match self.open_session(&mut tx, user.user_id, client).await {
+                Ok(opened) => opened,
+                Err(err) => {
+                    tx.rollback().await?;
+                    return Err(err);
+                }
+            };
+        tx.commit().await?;
+
+        let token = make_jwt_token(
+            &self.jwt_keys,
+            &user.user_id,
+            user.role,
+            user.email_verified,
+            session_id,
+            self.config.jwt_access_token_ttl_secs,
+        )
+        .map_err(|_| AppError::InternalError)?;
+
+        Ok(AuthBody::new(
+            token,
+            refresh_token,
+            self.config.jwt_access_token_ttl_secs,
+        ))
+    }
+
+    /// Finishes a login once the first factor checked out: applies the email verification policy
+    /// and either starts a session or asks for the second factor.
+    async fn complete_login(
+        &self,
+        user: UserStatus,
+        client: ClientInfo,
+    ) -> Result<LoginOutcome, AppError> {
+        self.check_login_allowed(user.email_verified)?;
+
+        let user_totp = self
+            .repo
+            .find_totp(self.pool.clone(), user.user_id)
+            .await
+            .map_err(AppError::DatabaseError)?;
+        if user_totp.is_some_and(|user_totp| user_totp.confirmed_at.is_some()) {
+            self.check_lockout(LoginScope::SecondFactor, &user.user_id.to_string())
+                .await?;
+            let challenge = self.create_login_challenge(user.user_id).await?;
+            return Ok(LoginOutcome::TwoFactorRequired(challenge));
+        }
+
+        let auth_body = self.start_session(&user, client).await?;
+        Ok(LoginOutcome::Authenticated(auth_body))
+    }
+
+    /// Returns the OpenID Connect client, failing when OIDC login is not configured.
+    fn oidc_client(&self) -> Result<&OidcClient, AppError> {
+        self.oidc
+            .as_deref()
+            .ok_or_else(|| AppError::NotFound("OIDC login is not configured".into()))
+    }
+
+    /// Links a first-time external identity to a user and returns the user's ID.
+    ///
+    /// An existing account is reused only when both the provider and we have verified
+    /// the address, so an unverified address on either side cannot be used to take it over.
+    /// Otherwise a new account without a password is created.
+    async fn link_identity(&self, claims: &IdTokenClaims) -> Result<i32, AppError> {
+        let email = claims
+            .email
+            .clone()
+            .filter(|email| !email.is_empty())
+            .ok_or_else(|| {
+                AppError::ValidationError(
+                    "The identity provider did not share an email address".into(),
+                )
+            })?;
+
+        let existing = if claims.email_verified {
+            self.repo
+                .find_verified_user_ids_by_email(self.pool.clone(), &email)
+                .await
+                .map_err(AppError::DatabaseError)?
+        } else {
+            Vec::new()
+        };
+        let new_user = match existing.as_slice() {
+            [] => Some(NewUser {
+                username: self.available_username(claims, &email).await?,
+                email: email.clone(),
+            }),
+            [_] => None,
+            _ => {
+                return Err(AppError::Conflict(
+                    "Several accounts use this email address".into(),
+                ))
+            }
+        };
+
+        let mut tx = self.pool.begin().await?;
+
+        let user_id = match new_user {
+            None => existing[0],
+            Some(new_user) => {
+                let user_id = match self.repo.create_user(&mut tx, new_user).await {
+                    Ok(user_id) => user_id,
+                    Err(err) => {
+                        tx.rollback().await?;
+                        return Err(map_signup_error(err));
+                    }
+                };
+                if claims.email_verified {
+                    if let Err(err) = self.repo.mark_email_verified(&mut tx, user_id).await {
+                        tracing::error!("Error marking email as verified: {err}");
+                        tx.rollback().await?;
+                        return Err(AppError::DatabaseError(err));
+                    }
+                }
+                user_id
+            }
+        };
+
+        let identity = NewUserIdentity {
+            user_id,
+            issuer: claims.iss.clone(),
+            subject: claims.sub.clone(),
+            email: Some(email),
+        };
+        if let Err(err) = self.repo.create_user_identity(&mut tx, identity).await {
+            tracing::error!("Error linking external identity: {err}");
+            tx.rollback().await?;
+            return Err(AppError::DatabaseError(err));
+        }
+        tx.commit().await?;
+
+        tracing::info!(
+            "Linked identity {} of {} to user {user_id}",
+            claims.sub,
+            claims.iss
+        );
+        Ok(user_id)
+    }
+
+    /// Derives a free username from the provider's suggestion or the email address.
+    async fn available_username(
+        &self,
+        claims: &IdTokenClaims,
+        email: &str,
+    ) -> Result<String, AppError> {
+        let suggestion = claims
+            .preferred_username
+            .as_deref()
+            .unwrap_or_else(|| email.split('@').next().unwrap_or_default());
+        let mut base: String = suggestion
+            .chars()
+            .filter(|c| c.is_alphanumeric() || matches!(c, '.' | '_' | '-'))
+            .take(56)
+            .collect();
+        if base.is_empty() {
+            base = "user".into();
+        }
+
+        let mut username = base.clone();
+        for _ in 0..5 {
+            let taken = self
+                .repo
+                .username_exists(self.pool.clone(), &username)
+                .await
+                .map_err(AppError::DatabaseError)?;
+            if !taken {
+                return Ok(username);
+            }
+            username = format!("{base}-{:04x}", rand::random::<u16>());
+        }
+
+        Err(AppError::Conflict("Could not find a free username".into()))
+    }
+
+    /// Stores a login challenge for the user and returns its raw token.
+    async fn create_login_challenge(
+        &self,
+        user_id: i32,
+    ) -> Result<TwoFactorChallengeDto, AppError> {
+        let challenge_token = hash_util::generate_token();
+        let challenge = NewLoginChallenge {
+            user_id,
+            token_hash: hash_util::hash_token(&challenge_token),
+            expires_at: Utc::now() + Duration::seconds(self.config.login_challenge_ttl_secs),
+        };
+
+        self.repo
+            .create_login_challenge(self.pool.clone(), challenge)
+            .await
+            .map_err(|err| {
+                tracing::error!("Error creating login challenge: {err}");
+                AppError::DatabaseError(err)
+            })?;
+
+        Ok(TwoFactorChallengeDto {
+            challenge_token,
+            expires_in: self.config.login_challenge_ttl_secs,
+        })
+    }
+
+    /// Checks a TOTP code or a recovery code of the user, consuming it when it is accepted.
+    async fn verify_second_factor(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        code: Option<&str>,
+        recovery_code: Option<&str>,
+    ) -> Result<bool, sqlx::Error> {
+        if let Some(code) = code {
+            let Some(user_totp) = self.repo.find_totp_for_update(tx, user_id).await? else {
+                return Ok(false);
+            };
+            if user_totp.confirmed_at.is_none() {
+                return Ok(false);
+            }
+            let step = totp::verify(
+                &user_totp.secret,
+                code,
+                Utc::now().timestamp(),
+                user_totp.last_used_step,
+            );
+            let Some(step) = step else {
+                return Ok(false);
+            };
+            self.repo
+                .update_totp_last_used_step(tx, user_id, step)
+                .await?;
+            return Ok(true);
+        }
+
+        let Some(recovery_code) = recovery_code else {
+            return Ok(false);
+        };
+        let recovery_code = totp::normalize_recovery_code(recovery_code);
+        let codes = self
+            .repo
+            .find_unused_recovery_codes_for_update(tx, user_id)
+            .await?;
+        for code in codes {
+            if hash_util::verify_password(&code.code_hash, &recovery_code) {
+                self.repo.mark_recovery_code_used(tx, code.id).await?;
+                tracing::info!("Recovery code used by user {user_id}");
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Fails with `EmailNotVerified` when the policy keeps unverified users from signing in.
+    fn check_login_allowed(&self, email_verified: bool) -> Result<(), AppError> {
+        if self.config.email_verification_policy.blocks_login() && !email_verified {
+            return Err(AppError::EmailNotVerified);
+        }
+        Ok(())
+    }
+}
+
+/// Maps a failed user insert to a client error when the username is taken.
+fn map_signup_error(err: sqlx::Error) -> AppError {
+    if let Some(db_err) = err.as_database_error() {
+        if db_err.is_unique_violation() {
+            return AppError::Conflict("Username is already taken".into());
+        }
+    }
+    tracing::error!("Error creating user: {err}");
+    AppError::DatabaseError(err)
+}
+
+/// Maps a failed exchange with the identity provider to a client or server error.
+fn map_oidc_error(err: OidcError) -> AppError {
+    match err {
+        OidcError::CodeRejected(_) | OidcError::InvalidIdToken(_) => {
+            tracing::warn!("OIDC login rejected: {err}");
+            AppError::InvalidToken
+        }
+        _ => {
+            tracing::error!("Error talking to the identity provider: {err}");
+            AppError::InternalError
+        }
+    }
 }
 
 /// Implementation of the AuthService
 #[async_trait::async_trait]
 impl AuthServiceTrait for AuthService {
     /// constructor for the service.
-    fn create_service(pool: PgPool) -> Arc<dyn AuthServiceTrait> {
+    fn create_service(
+        pool: PgPool,
+        config: Config,
+        jwt_keys: Arc<JwtKeys>,
+        mailer: Arc<dyn Mailer>,
+        oidc: Option<Arc<OidcClient>>,
+    ) -> Arc<dyn AuthServiceTrait> {
+        let session_cache_ttl =
+            std::time::Duration::from_secs(config.session_cache_ttl_secs as u64);
         Arc::new(Self {
             pool,
             repo: Arc::new(UserAuthRepo {}),
+            config,
+            jwt_keys,
+            mailer,
+            oidc,
+            sessions: Arc::new(SessionCache::new(session_cache_ttl)),
         })
     }
 
-    /// It hashes the password and stores it in the database.
-    async fn create_user_auth(&self, auth_user: AuthUserDto) -> Result<(), AppError> {
-        let mut tx = self.pool.begin().await?;
+    /// Creates the user row, its credentials and the first refresh token in one transaction,
+    /// so an account never exists without a password and the user is signed in right away.
+    /// When the policy requires a verified email to log in, no tokens are issued.
+    /// The verification email is sent after the commit; delivery failures are only logged
+    /// since the user can ask for a new link.
+    async fn signup(
+        &self,
+        payload: SignupDto,
+        client: ClientInfo,
+    ) -> Result<Option<AuthBody>, AppError> {
+        if password_policy::contains_username(&payload.password, &payload.username) {
+            return Err(AppError::ValidationError(
+                "Password must not contain the username".into(),
+            ));
+        }
 
         let password_hash =
-            hash_util::hash_password(&auth_user.password).map_err(|_| AppError::InternalError)?;
+            hash_util::hash_password(&payload.password).map_err(|_| AppError::InternalError)?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let new_user = NewUser {
+            username: payload.username.clone(),
+            email: payload.email.clone(),
+        };
+        let user_id = match self.repo.create_user(&mut tx, new_user).await {
+            Ok(user_id) => user_id,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(map_signup_error(err));
+            }
+        };
 
         let user_auth = UserAuth {
-            user_id: auth_user.user_id,
+            user_id,
             password_hash,
         };
+        if let Err(err) = self.repo.create(&mut tx, user_auth).await {
+            tracing::error!("Error creating user auth: {err}");
+            tx.rollback().await?;
+            return Err(AppError::DatabaseError(err));
+        }
 
-        match self.repo.create(&mut tx, user_auth).await {
-            Ok(()) => {
-                tx.commit().await?;
-                Ok(())
-            }
-            Err(err) => {
-                tracing::error!("Error creating user auth: {err}");
-                tx.rollback().await?;
-                Err(AppError::DatabaseError(err))
+        let session = if self.config.email_verification_policy.blocks_login() {
+            None
+        } else {
+            match self.open_session(&mut tx, user_id, client).await {
+                Ok(opened) => Some(opened),
+                Err(err) => {
+                    tx.rollback().await?;
+                    return Err(err);
+                }
             }
+        };
+        tx.commit().await?;
+
+        let contact = UserContact {
+            user_id,
+            username: payload.username,
+            email: payload.email,
+        };
+        if let Err(err) = self.send_email_verification(contact).await {
+            tracing::error!("Error sending email verification to user {user_id}: {err}");
         }
+
+        let Some((session_id, refresh_token)) = session else {
+            return Ok(None);
+        };
+        let access_token = make_jwt_token(
+            &self.jwt_keys,
+            &user_id,
+            Role::default(),
+            false,
+            session_id,
+            self.config.jwt_access_token_ttl_secs,
+        )
+        .map_err(|_| AppError::InternalError)?;
+
+        Ok(Some(AuthBody::new(
+            access_token,
+            refresh_token,
+            self.config.jwt_access_token_ttl_secs,
+        )))
     }
 
     /// Authenticates a user by checking the provided credentials
     /// against the stored credentials in the database.
-    /// If the credentials are valid, it generates a JWT token for the user.
+    /// If the credentials are valid, it generates a JWT token for the user
+    /// and starts a new refresh token family.
     /// If the credentials are invalid, it returns an error.
-    async fn login_user(&self, auth_payload: AuthPayload) -> Result<AuthBody, AppError> {
+    ///
+    /// Unknown users and wrong passwords are rejected alike, after the same Argon2 work,
+    /// so responses do not reveal which usernames exist. Failures are counted per username
+    /// and per client IP, and either is locked out with exponential backoff at its limit;
+    /// a successful login clears the username's counter, while the IP's expires on its own.
+    /// Users with an unverified email are turned away after the password check
+    /// when the policy requires verification to log in.
+    /// Accounts with two-factor login enabled get a short-lived challenge token instead,
+    /// to be exchanged together with a TOTP code at `login_two_factor`.
+    async fn login_user(
+        &self,
+        auth_payload: AuthPayload,
+        client: ClientInfo,
+    ) -> Result<LoginOutcome, AppError> {
         if auth_payload.client_id.is_empty() || auth_payload.client_secret.is_empty() {
             return Err(AppError::MissingCredentials);
         }
 
+        // Counters are keyed by what the client sent, capped to the column size.
+        let username: String = auth_payload.client_id.chars().take(255).collect();
+        let ip = client.ip.to_string();
+
+        let locked_until = self
+            .repo
+            .find_login_lockout(self.pool.clone(), &username, &ip)
+            .await
+            .map_err(AppError::DatabaseError)?;
+        if let Some(locked_until) = locked_until {
+            return Err(AppError::TooManyAttempts {
+                retry_after_secs: (locked_until - Utc::now()).num_seconds().max(1),
+            });
+        }
+
         let user_auth = self
             .repo
             .find_by_user_name(self.pool.clone(), auth_payload.client_id.clone())
             .await
             .map_err(AppError::DatabaseError)?;
 
-        let user_auth = user_auth.ok_or(AppError::UserNotFound)?;
+        let verified = match &user_auth {
+            Some(user_auth) => {
+                hash_util::verify_password(&user_auth.password_hash, &auth_payload.client_secret)
+            }
+            None => {
+                hash_util::verify_dummy_password(&auth_payload.client_secret);
+                false
+            }
+        };
 
-        if !hash_util::verify_password(&user_auth.password_hash, &auth_payload.client_secret) {
+        let Some(user_auth) = user_auth.filter(|_| verified) else {
+            self.record_login_failure(&username, &ip).await?;
             return Err(AppError::WrongCredentials);
+        };
+
+        for (scope, subject) in login_counters(&username, &ip)
+            .into_iter()
+            .filter(|(scope, _)| scope.clears_on_success())
+        {
+            self.repo
+                .clear_login_failures(self.pool.clone(), scope, subject)
+                .await
+                .map_err(AppError::DatabaseError)?;
+        }
+        let user = UserStatus {
+            user_id: user_auth.user_id,
+            role: user_auth.role,
+            email_verified: user_auth.email_verified,
+        };
+        self.complete_login(user, client).await
+    }
+
+    /// Consumes the login challenge once the second factor checks out.
+    /// Each wrong code counts against the challenge, which is rejected after too many attempts,
+    /// and against the user, whose two-factor login is locked out with exponential backoff
+    /// at its limit, so fresh challenges do not allow guessing any faster.
+    async fn login_two_factor(
+        &self,
+        payload: TwoFactorLoginDto,
+        client: ClientInfo,
+    ) -> Result<AuthBody, AppError> {
+        if payload.code.is_some() == payload.recovery_code.is_some() {
+            return Err(AppError::ValidationError(
+                "Provide either a code or a recovery code".into(),
+            ));
         }
 
-        let token = make_jwt_token(&user_auth.user_id).map_err(|_| AppError::InternalError)?;
+        let token_hash = hash_util::hash_token(&payload.challenge_token);
+        let mut tx = self.pool.begin().await?;
+
+        let challenge = match self
+            .repo
+            .find_login_challenge_for_update(&mut tx, &token_hash)
+            .await
+        {
+            Ok(Some(challenge))
+                if challenge.used_at.is_none()
+                    && challenge.expires_at > Utc::now()
+                    && challenge.attempts < self.config.login_challenge_max_attempts =>
+            {
+                challenge
+            }
+            Ok(_) => {
+                tx.rollback().await?;
+                return Err(AppError::InvalidToken);
+            }
+            Err(err) => {
+                tracing::error!("Error retrieving login challenge: {err}");
+                tx.rollback().await?;
+                return Err(AppError::DatabaseError(err));
+            }
+        };
+
+        if let Err(err) = self
+            .check_lockout(LoginScope::SecondFactor, &challenge.user_id.to_string())
+            .await
+        {
+            tx.rollback().await?;
+            return Err(err);
+        }
+
+        let accepted = match self
+            .verify_second_factor(
+                &mut tx,
+                challenge.user_id,
+                payload.code.as_deref(),
+                payload.recovery_code.as_deref(),
+            )
+            .await
+        {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                tracing::error!("Error verifying second factor: {err}");
+                tx.rollback().await?;
+                return Err(AppError::DatabaseError(err));
+            }
+        };
+
+        let result = if accepted {
+            self.repo
+                .mark_login_challenge_used(&mut tx, challenge.id)
+                .await
+        } else {
+            self.repo
+                .record_login_challenge_attempt(&mut tx, challenge.id)
+                .await
+        };
+        if let Err(err) = result {
+            tracing::error!("Error updating login challenge: {err}");
+            tx.rollback().await?;
+            return Err(AppError::DatabaseError(err));
+        }
+        tx.commit().await?;
+
+        if !accepted {
+            self.record_second_factor_failure(challenge.user_id).await?;
+            return Err(AppError::WrongCredentials);
+        }
+        self.repo
+            .clear_login_failures(
+                self.pool.clone(),
+                LoginScope::SecondFactor,
+                &challenge.user_id.to_string(),
+            )
+            .await
+            .map_err(AppError::DatabaseError)?;
+
+        let user = self
+            .repo
+            .find_user_status(self.pool.clone(), challenge.user_id)
+            .await
+            .map_err(AppError::DatabaseError)?
+            .ok_or(AppError::UserNotFound)?;
+        self.check_login_allowed(user.email_verified)?;
+
+        self.start_session(&user, client).await
+    }
+
+    /// The state, nonce and PKCE verifier are kept server-side, keyed by a hash of the state,
+    /// until the provider redirects back.
+    async fn oidc_authorize(&self) -> Result<OidcAuthorizationDto, AppError> {
+        let request = self
+            .oidc_client()?
+            .authorization_request()
+            .await
+            .map_err(map_oidc_error)?;
+
+        let state = NewOidcLoginState {
+            state_hash: hash_util::hash_token(&request.state),
+            nonce: request.nonce,
+            code_verifier: request.code_verifier,
+            expires_at: Utc::now() + Duration::seconds(self.config.oidc_state_ttl_secs),
+        };
+        self.repo
+            .create_oidc_login_state(self.pool.clone(), state)
+            .await
+            .map_err(|err| {
+                tracing::error!("Error storing OIDC login state: {err}");
+                AppError::DatabaseError(err)
+            })?;
+
+        Ok(OidcAuthorizationDto {
+            authorization_url: request.url,
+        })
+    }
+
+    /// Exchanges the code for a verified ID token, then signs in the linked user
+    /// the same way a password login does, including the second factor if enabled.
+    async fn oidc_callback(
+        &self,
+        payload: OidcCallbackDto,
+        client: ClientInfo,
+    ) -> Result<LoginOutcome, AppError> {
+        let oidc = self.oidc_client()?;
+
+        let state = self
+            .repo
+            .take_oidc_login_state(self.pool.clone(), &hash_util::hash_token(&payload.state))
+            .await
+            .map_err(AppError::DatabaseError)?
+            .filter(|state| state.expires_at > Utc::now())
+            .ok_or_else(|| AppError::ValidationError("Invalid or expired login state".into()))?;
+
+        let claims = oidc
+            .exchange_code(&payload.code, &state.code_verifier, &state.nonce)
+            .await
+            .map_err(map_oidc_error)?;
+
+        let linked_user_id = self
+            .repo
+            .find_identity_user_id(self.pool.clone(), &claims.iss, &claims.sub)
+            .await
+            .map_err(AppError::DatabaseError)?;
+        let user_id = match linked_user_id {
+            Some(user_id) => user_id,
+            None => self.link_identity(&claims).await?,
+        };
+
+        let user = self
+            .repo
+            .find_user_status(self.pool.clone(), user_id)
+            .await
+            .map_err(AppError::DatabaseError)?
+            .ok_or(AppError::UserNotFound)?;
+        self.complete_login(user, client).await
+    }
+
+    /// Replaces a pending secret on every call; an enabled second factor
+    /// has to be disabled before a new one can be enrolled.
+    async fn enroll_totp(&self, user_id: i32) -> Result<TotpEnrollmentDto, AppError> {
+        let contact = self
+            .repo
+            .find_contact_by_user_id(self.pool.clone(), user_id)
+            .await
+            .map_err(AppError::DatabaseError)?
+            .ok_or(AppError::UserNotFound)?;
+
+        let user_totp = self
+            .repo
+            .find_totp(self.pool.clone(), user_id)
+            .await
+            .map_err(AppError::DatabaseError)?;
+        if user_totp.is_some_and(|user_totp| user_totp.confirmed_at.is_some()) {
+            return Err(AppError::Conflict(
+                "Two-factor authentication is already enabled".into(),
+            ));
+        }
+
+        let secret = totp::generate_secret();
+        self.repo
+            .upsert_pending_totp(self.pool.clone(), user_id, &secret)
+            .await
+            .map_err(|err| {
+                tracing::error!("Error storing TOTP secret: {err}");
+                AppError::DatabaseError(err)
+            })?;
+
+        Ok(TotpEnrollmentDto {
+            otpauth_uri: totp::otpauth_uri(&secret, &self.config.totp_issuer, &contact.username),
+            secret,
+        })
+    }
+
+    /// Recovery codes are returned once in plain text and only stored as Argon2 hashes.
+    async fn confirm_totp(
+        &self,
+        user_id: i32,
+        payload: ConfirmTotpDto,
+    ) -> Result<RecoveryCodesDto, AppError> {
+        let recovery_codes: Vec<String> = (0..totp::RECOVERY_CODE_COUNT)
+            .map(|_| totp::generate_recovery_code())
+            .collect();
+        let code_hashes = recovery_codes
+            .iter()
+            .map(|code| hash_util::hash_password(&totp::normalize_recovery_code(code)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| AppError::InternalError)?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let user_totp = match self.repo.find_totp_for_update(&mut tx, user_id).await {
+            Ok(Some(user_totp)) if user_totp.confirmed_at.is_none() => user_totp,
+            Ok(Some(_)) => {
+                tx.rollback().await?;
+                return Err(AppError::Conflict(
+                    "Two-factor authentication is already enabled".into(),
+                ));
+            }
+            Ok(None) => {
+                tx.rollback().await?;
+                return Err(AppError::ValidationError(
+                    "Two-factor authentication has not been enrolled".into(),
+                ));
+            }
+            Err(err) => {
+                tracing::error!("Error retrieving TOTP secret: {err}");
+                tx.rollback().await?;
+                return Err(AppError::DatabaseError(err));
+            }
+        };
+
+        let Some(step) = totp::verify(
+            &user_totp.secret,
+            &payload.code,
+            Utc::now().timestamp(),
+            user_totp.last_used_step,
+        ) else {
+            tx.rollback().await?;
+            return Err(AppError::ValidationError("Invalid code".into()));
+        };
+
+        if let Err(err) = self.repo.confirm_totp(&mut tx, user_id, step).await {
+            tracing::error!("Error confirming TOTP: {err}");
+            tx.rollback().await?;
+            return Err(AppError::DatabaseError(err));
+        }
+        if let Err(err) = self
+            .repo
+            .replace_recovery_codes(&mut tx, user_id, &code_hashes)
+            .await
+        {
+            tracing::error!("Error storing recovery codes: {err}");
+            tx.rollback().await?;
+            return Err(AppError::DatabaseError(err));
+        }
+        tx.commit().await?;
+
+        Ok(RecoveryCodesDto { recovery_codes })
+    }
+
+    /// Removes the secret and the recovery codes; succeeds when two-factor login is already off.
+    /// A TOTP or recovery code proves the request as well as the password does, so accounts
+    /// without one can turn it off too. Wrong codes count like those at `login_two_factor`.
+    async fn disable_totp(&self, user_id: i32, payload: DisableTotpDto) -> Result<(), AppError> {
+        if let Some(current_password) = &payload.current_password {
+            let user_auth = self
+                .repo
+                .find_by_user_id(self.pool.clone(), user_id)
+                .await
+                .map_err(AppError::DatabaseError)?
+                .ok_or(AppError::UserNotFound)?;
+            if !hash_util::verify_password(&user_auth.password_hash, current_password) {
+                return Err(AppError::WrongCredentials);
+            }
+        } else if payload.code.is_some() || payload.recovery_code.is_some() {
+            self.check_lockout(LoginScope::SecondFactor, &user_id.to_string())
+                .await?;
+        } else {
+            return Err(AppError::ValidationError(
+                "current_password, code or recovery_code is required".into(),
+            ));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        if payload.current_password.is_none() {
+            let accepted = match self
+                .verify_second_factor(
+                    &mut tx,
+                    user_id,
+                    payload.code.as_deref(),
+                    payload.recovery_code.as_deref(),
+                )
+                .await
+            {
+                Ok(accepted) => accepted,
+                Err(err) => {
+                    tracing::error!("Error verifying second factor: {err}");
+                    tx.rollback().await?;
+                    return Err(AppError::DatabaseError(err));
+                }
+            };
+            if !accepted {
+                tx.rollback().await?;
+                self.record_second_factor_failure(user_id).await?;
+                return Err(AppError::WrongCredentials);
+            }
+        }
+        if let Err(err) = self.repo.delete_totp(&mut tx, user_id).await {
+            tracing::error!("Error removing TOTP: {err}");
+            tx.rollback().await?;
+            return Err(AppError::DatabaseError(err));
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Rotates a refresh token: the presented token is marked as used and a new one
+    /// from the same family is issued alongside a fresh access token.
+    /// Presenting a token that was already used or revoked means it may have leaked,
+    /// so the whole session is revoked and the client has to log in again.
+    /// The session's last seen time and client details are updated on every refresh.
+    async fn refresh_token(
+        &self,
+        payload: RefreshTokenDto,
+        client: ClientInfo,
+    ) -> Result<AuthBody, AppError> {
+        let token_hash = hash_util::hash_token(&payload.refresh_token);
+        let mut tx = self.pool.begin().await?;
+
+        let token = match self
+            .repo
+            .find_refresh_token_for_update(&mut tx, &token_hash)
+            .await
+        {
+            Ok(Some(token)) => token,
+            Ok(None) => {
+                tx.rollback().await?;
+                return Err(AppError::InvalidToken);
+            }
+            Err(err) => {
+                tracing::error!("Error retrieving refresh token: {err}");
+                tx.rollback().await?;
+                return Err(AppError::DatabaseError(err));
+            }
+        };
+
+        if token.used_at.is_some() || token.revoked_at.is_some() {
+            tracing::warn!(
+                "Refresh token reuse detected for user {}, revoking session {}",
+                token.user_id,
+                token.family_id
+            );
+            if let Err(err) = self
+                .end_session(&mut tx, token.user_id, token.family_id)
+                .await
+            {
+                tx.rollback().await?;
+                return Err(err);
+            }
+            tx.commit().await?;
+            self.sessions.revoke(&[token.family_id]);
+            return Err(AppError::InvalidToken);
+        }
+
+        if token.expires_at <= Utc::now() {
+            tx.rollback().await?;
+            return Err(AppError::InvalidToken);
+        }
+        if let Err(err) = self.check_login_allowed(token.email_verified) {
+            tx.rollback().await?;
+            return Err(err);
+        }
+
+        if let Err(err) = self.repo.mark_refresh_token_used(&mut tx, token.id).await {
+            tracing::error!("Error updating refresh token: {err}");
+            tx.rollback().await?;
+            return Err(AppError::DatabaseError(err));
+        }
+        if let Err(err) = self
+            .repo
+            .touch_session(
+                &mut tx,
+                token.family_id,
+                &client.ip.to_string(),
+                client.user_agent.as_deref(),
+            )
+            .await
+        {
+            tracing::error!("Error updating session: {err}");
+            tx.rollback().await?;
+            return Err(AppError::DatabaseError(err));
+        }
+
+        let refresh_token = match self
+            .issue_refresh_token(&mut tx, token.user_id, token.family_id)
+            .await
+        {
+            Ok(refresh_token) => refresh_token,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(err);
+            }
+        };
+        tx.commit().await?;
+
+        let access_token = make_jwt_token(
+            &self.jwt_keys,
+            &token.user_id,
+            token.role,
+            token.email_verified,
+            token.family_id,
+            self.config.jwt_access_token_ttl_secs,
+        )
+        .map_err(|_| AppError::InternalError)?;
+
+        Ok(AuthBody::new(
+            access_token,
+            refresh_token,
+            self.config.jwt_access_token_ttl_secs,
+        ))
+    }
+
+    /// Ends the session of the presented token, revoking its refresh token family.
+    /// Unknown tokens are ignored so logging out is idempotent.
+    async fn logout_user(&self, payload: RefreshTokenDto) -> Result<(), AppError> {
+        let token_hash = hash_util::hash_token(&payload.refresh_token);
+        let mut tx = self.pool.begin().await?;
+
+        let token = match self
+            .repo
+            .find_refresh_token_for_update(&mut tx, &token_hash)
+            .await
+        {
+            Ok(token) => token,
+            Err(err) => {
+                tracing::error!("Error retrieving refresh token: {err}");
+                tx.rollback().await?;
+                return Err(AppError::DatabaseError(err));
+            }
+        };
+
+        if let Some(token) = &token {
+            if let Err(err) = self
+                .end_session(&mut tx, token.user_id, token.family_id)
+                .await
+            {
+                tx.rollback().await?;
+                return Err(err);
+            }
+        }
+
+        tx.commit().await?;
+        if let Some(token) = token {
+            self.sessions.revoke(&[token.family_id]);
+        }
+        Ok(())
+    }
+
+    async fn list_sessions(
+        &self,
+        user_id: i32,
+        current_session_id: Option<Uuid>,
+    ) -> Result<Vec<SessionDto>, AppError> {
+        let sessions = self
+            .repo
+            .find_active_sessions(self.pool.clone(), user_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("Error fetching sessions: {err}");
+                AppError::DatabaseError(err)
+            })?;
+
+        Ok(sessions
+            .into_iter()
+            .map(|session| SessionDto::from_session(session, current_session_id))
+            .collect())
+    }
+
+    async fn revoke_session(&self, user_id: i32, session_id: Uuid) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+        let revoked = match self.end_session(&mut tx, user_id, session_id).await {
+            Ok(revoked) => revoked,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(err);
+            }
+        };
+        if !revoked {
+            tx.rollback().await?;
+            return Err(AppError::NotFound("Session not found".into()));
+        }
+        tx.commit().await?;
+
+        self.sessions.revoke(&[session_id]);
+        tracing::info!("Revoked session {session_id} of user {user_id}");
+        Ok(())
+    }
+
+    async fn revoke_all_sessions(&self, user_id: i32) -> Result<(), AppError> {
+        let db_error = |err: sqlx::Error| {
+            tracing::error!("Error revoking sessions: {err}");
+            AppError::DatabaseError(err)
+        };
+
+        let mut tx = self.pool.begin().await?;
+        let session_ids = match self.repo.revoke_user_sessions(&mut tx, user_id).await {
+            Ok(session_ids) => session_ids,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(db_error(err));
+            }
+        };
+        if let Err(err) = self.repo.revoke_user_refresh_tokens(&mut tx, user_id).await {
+            tx.rollback().await?;
+            return Err(db_error(err));
+        }
+        tx.commit().await?;
+
+        self.sessions.revoke(&session_ids);
+        tracing::info!("Revoked {} sessions of user {user_id}", session_ids.len());
+        Ok(())
+    }
+
+    /// Answers from the in-memory cache when possible, so most requests skip the database.
+    async fn is_session_active(&self, session_id: Uuid) -> Result<bool, AppError> {
+        if let Some(active) = self.sessions.get(session_id) {
+            return Ok(active);
+        }
+
+        let active = self
+            .repo
+            .is_session_active(self.pool.clone(), session_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("Error checking session: {err}");
+                AppError::DatabaseError(err)
+            })?;
+        self.sessions.insert(session_id, active);
+        Ok(active)
+    }
+
+    /// Verifies the current password, stores the new one and starts a new session.
+    async fn change_password(
+        &self,
+        user_id: i32,
+        payload: ChangePasswordDto,
+        client: ClientInfo,
+    ) -> Result<AuthBody, AppError> {
+        let user_auth = self
+            .repo
+            .find_by_user_id(self.pool.clone(), user_id)
+            .await
+            .map_err(AppError::DatabaseError)?
+            .ok_or(AppError::UserNotFound)?;
+
+        if !hash_util::verify_password(&user_auth.password_hash, &payload.current_password) {
+            return Err(AppError::WrongCredentials);
+        }
+        if payload.new_password == payload.current_password {
+            return Err(AppError::ValidationError(
+                "New password must differ from the current one".into(),
+            ));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let ended_session_ids = match self
+            .replace_password(&mut tx, user_id, &payload.new_password)
+            .await
+        {
+            Ok(session_ids) => session_ids,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(err);
+            }
+        };
+        let (session_id, refresh_token) = match self.open_session(&mut tx, user_id, client).await {
+            Ok(opened) => opened,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(err);
+            }
+        };
+        tx.commit().await?;
+        self.sessions.revoke(&ended_session_ids);
+
+        let access_token = make_jwt_token(
+            &self.jwt_keys,
+            &user_id,
+            user_auth.role,
+            user_auth.email_verified,
+            session_id,
+            self.config.jwt_access_token_ttl_secs,
+        )
+        .map_err(|_| AppError::InternalError)?;
+
+        Ok(AuthBody::new(
+            access_token,
+            refresh_token,
+            self.config.jwt_access_token_ttl_secs,
+        ))
+    }
+
+    /// Succeeds without waiting for the lookup or the email, so neither the response nor its
+    /// timing reveals whether the address is registered; failures are logged instead.
+    /// Requests are counted per client IP, which is locked out with exponential backoff
+    /// at its limit, and each account gets at most one email per resend interval.
+    async fn forgot_password(
+        &self,
+        payload: ForgotPasswordDto,
+        client: ClientInfo,
+    ) -> Result<(), AppError> {
+        let ip = client.ip.to_string();
+        self.check_lockout(LoginScope::PasswordReset, &ip).await?;
+        self.count_failure(
+            LoginScope::PasswordReset,
+            &ip,
+            self.config.password_reset_max_per_ip,
+        )
+        .await?;
+
+        let service = self.clone();
+        tokio::spawn(async move {
+            if let Err(err) = service.send_password_resets(&payload.email).await {
+                tracing::error!("Error sending password resets: {err}");
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Consumes the reset token and replaces the password in the same transaction.
+    async fn reset_password(&self, payload: ResetPasswordDto) -> Result<(), AppError> {
+        let token_hash = hash_util::hash_token(&payload.token);
+        let mut tx = self.pool.begin().await?;
+
+        let token = match self
+            .repo
+            .find_password_reset_token_for_update(&mut tx, &token_hash)
+            .await
+        {
+            Ok(Some(token)) if token.used_at.is_none() && token.expires_at > Utc::now() => token,
+            Ok(_) => {
+                tx.rollback().await?;
+                return Err(AppError::ValidationError(
+                    "Invalid or expired reset token".into(),
+                ));
+            }
+            Err(err) => {
+                tracing::error!("Error retrieving password reset token: {err}");
+                tx.rollback().await?;
+                return Err(AppError::DatabaseError(err));
+            }
+        };
+
+        let ended_session_ids = match self
+            .replace_password(&mut tx, token.user_id, &payload.new_password)
+            .await
+        {
+            Ok(session_ids) => session_ids,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(err);
+            }
+        };
+        tx.commit().await?;
+        self.sessions.revoke(&ended_session_ids);
+
+        Ok(())
+    }
+
+    /// Consumes the verification token and marks the address as verified.
+    /// Tokens sent to an address the user has since changed are rejected.
+    /// Access tokens carry the verification state, so clients refresh afterwards to pick it up.
+    async fn verify_email(&self, payload: VerifyEmailDto) -> Result<(), AppError> {
+        let token_hash = hash_util::hash_token(&payload.token);
+        let mut tx = self.pool.begin().await?;
+
+        let token = match self
+            .repo
+            .find_email_verification_token_for_update(&mut tx, &token_hash)
+            .await
+        {
+            Ok(Some(token))
+                if token.used_at.is_none()
+                    && token.email_matches
+                    && token.expires_at > Utc::now() =>
+            {
+                token
+            }
+            Ok(_) => {
+                tx.rollback().await?;
+                return Err(AppError::ValidationError(
+                    "Invalid or expired verification token".into(),
+                ));
+            }
+            Err(err) => {
+                tracing::error!("Error retrieving email verification token: {err}");
+                tx.rollback().await?;
+                return Err(AppError::DatabaseError(err));
+            }
+        };
+
+        if let Err(err) = self
+            .repo
+            .invalidate_email_verification_tokens(&mut tx, token.user_id)
+            .await
+        {
+            tracing::error!("Error invalidating email verification tokens: {err}");
+            tx.rollback().await?;
+            return Err(AppError::DatabaseError(err));
+        }
+        if let Err(err) = self.repo.mark_email_verified(&mut tx, token.user_id).await {
+            tracing::error!("Error marking email as verified: {err}");
+            tx.rollback().await?;
+            return Err(AppError::DatabaseError(err));
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Always succeeds so the response does not reveal whether the address is registered.
+    /// Accounts that were sent a link within the resend interval are skipped.
+    async fn resend_verification(&self, payload: ResendVerificationDto) -> Result<(), AppError> {
+        let contacts = self
+            .repo
+            .find_unverified_contacts_by_email(self.pool.clone(), &payload.email)
+            .await
+            .map_err(|err| {
+                tracing::error!("Error retrieving users by email: {err}");
+                AppError::DatabaseError(err)
+            })?;
+
+        let resend_after =
+            Utc::now() - Duration::seconds(self.config.email_verification_resend_interval_secs);
+        for contact in contacts {
+            let user_id = contact.user_id;
+            let last_sent_at = self
+                .repo
+                .find_latest_email_verification_at(self.pool.clone(), user_id)
+                .await
+                .map_err(|err| {
+                    tracing::error!("Error retrieving email verification tokens: {err}");
+                    AppError::DatabaseError(err)
+                })?;
+            if last_sent_at.is_some_and(|sent_at| sent_at > resend_after) {
+                tracing::warn!(
+                    "Skipping email verification resend for user {user_id}: rate limited"
+                );
+                continue;
+            }
+
+            if let Err(err) = self.send_email_verification(contact).await {
+                tracing::error!("Error sending email verification to user {user_id}: {err}");
+            }
+        }
 
-        Ok(AuthBody::new(token))
+        Ok(())
     }
 }
@@ -0,0 +1,79 @@
+//! In-memory cache of session checks, so authenticating a request does not need
+//! a database round trip every time.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use uuid::Uuid;
+
+/// Upper bound on cached sessions; expired entries are dropped once it is reached.
+const MAX_ENTRIES: usize = 100_000;
+
+/// Remembers for a short time whether a session is active.
+/// Revocations through this instance are recorded right away; revocations elsewhere
+/// are picked up once the cached entry expires.
+pub struct SessionCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<Uuid, (bool, Instant)>>,
+}
+
+impl SessionCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns whether the session is active, if it was checked within the TTL.
+    pub fn get(&self, session_id: Uuid) -> Option<bool> {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries
+            .get(&session_id)
+            .filter(|(_, checked_at)| checked_at.elapsed() < self.ttl)
+            .map(|(active, _)| *active)
+    }
+
+    /// Records the result of checking the session against the database.
+    pub fn insert(&self, session_id: Uuid, active: bool) {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        if entries.len() >= MAX_ENTRIES {
+            entries.retain(|_, (_, checked_at)| checked_at.elapsed() < self.ttl);
+            if entries.len() >= MAX_ENTRIES {
+                entries.clear();
+            }
+        }
+        entries.insert(session_id, (active, Instant::now()));
+    }
+
+    /// Records that the sessions were revoked, so their tokens are rejected immediately.
+    pub fn revoke(&self, session_ids: &[Uuid]) {
+        for session_id in session_ids {
+            self.insert(*session_id, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_expire_and_revocations_apply_immediately() {
+        let cache = SessionCache::new(Duration::from_millis(50));
+        let session_id = Uuid::new_v4();
+        assert_eq!(cache.get(session_id), None);
+
+        cache.insert(session_id, true);
+        assert_eq!(cache.get(session_id), Some(true));
+
+        cache.revoke(&[session_id]);
+        assert_eq!(cache.get(session_id), Some(false));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.get(session_id), None);
+    }
+}
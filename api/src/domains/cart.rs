@@ -0,0 +1,23 @@
+mod api {
+    mod handlers;
+    pub mod routes;
+}
+
+mod domain {
+    pub mod model;
+    pub mod repository;
+    pub mod service;
+}
+
+pub mod dto {
+    pub mod cart_dto;
+}
+
+mod infra {
+    mod impl_repository;
+    pub mod impl_service;
+}
+
+pub use api::routes::{cart_routes, CartApiDoc};
+pub use domain::service::CartServiceTrait;
+pub use infra::impl_service::CartService;
@@ -0,0 +1,115 @@
+use crate::{
+    common::{app_state::AppState, dto::RestApiResponse, error::AppError, jwt::Claims},
+    domains::cart::dto::cart_dto::{AddCartItemDto, CartDto, UpdateCartItemDto},
+};
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+
+use validator::Validate;
+
+#[utoipa::path(
+    get,
+    path = "/cart",
+    responses((status = 200, description = "Get the current user's cart", body = CartDto)),
+    tag = "Cart"
+)]
+pub async fn get_cart(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<impl IntoResponse, AppError> {
+    let cart = state.cart_service.get_cart(claims.user_id()?).await?;
+    Ok(RestApiResponse::success(cart))
+}
+
+#[utoipa::path(
+    post,
+    path = "/cart/items",
+    request_body = AddCartItemDto,
+    responses((status = 200, description = "Add a product to the cart", body = CartDto)),
+    tag = "Cart"
+)]
+pub async fn add_cart_item(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<AddCartItemDto>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    let cart = state
+        .cart_service
+        .add_item(claims.user_id()?, payload)
+        .await?;
+    Ok(RestApiResponse::success(cart))
+}
+
+#[utoipa::path(
+    put,
+    path = "/cart/items/{product_id}",
+    request_body = UpdateCartItemDto,
+    responses((status = 200, description = "Update the quantity of a cart item", body = CartDto)),
+    tag = "Cart"
+)]
+pub async fn update_cart_item(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(product_id): Path<String>,
+    Json(payload): Json<UpdateCartItemDto>,
+) -> Result<impl IntoResponse, AppError> {
+    let product_id: i32 = product_id
+        .parse()
+        .map_err(|_| AppError::ValidationError("Invalid product id".into()))?;
+
+    payload.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    let cart = state
+        .cart_service
+        .update_item(claims.user_id()?, product_id, payload)
+        .await?;
+    Ok(RestApiResponse::success(cart))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/cart/items/{product_id}",
+    responses((status = 200, description = "Remove a product from the cart", body = CartDto)),
+    tag = "Cart"
+)]
+pub async fn remove_cart_item(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(product_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let product_id: i32 = product_id
+        .parse()
+        .map_err(|_| AppError::ValidationError("Invalid product id".into()))?;
+
+    let cart = state
+        .cart_service
+        .remove_item(claims.user_id()?, product_id)
+        .await?;
+    Ok(RestApiResponse::success(cart))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/cart",
+    responses((status = 200, description = "Remove every item from the cart", body = CartDto)),
+    tag = "Cart"
+)]
+pub async fn clear_cart(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<impl IntoResponse, AppError> {
+    let cart = state.cart_service.clear_cart(claims.user_id()?).await?;
+    Ok(RestApiResponse::success(cart))
+}
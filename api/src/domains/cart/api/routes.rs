@@ -0,0 +1,61 @@
+use super::handlers::*;
+use crate::{
+    common::app_state::AppState,
+    domains::cart::dto::cart_dto::{AddCartItemDto, CartDto, CartItemDto, UpdateCartItemDto},
+};
+
+use axum::{
+    routing::{delete, get, post, put},
+    Router,
+};
+
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    OpenApi,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        get_cart,
+        add_cart_item,
+        update_cart_item,
+        remove_cart_item,
+        clear_cart,
+    ),
+    components(schemas(CartDto, CartItemDto, AddCartItemDto, UpdateCartItemDto)),
+    tags(
+        (name = "Cart", description = "Shopping cart endpoints")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    modifiers(&CartApiDoc)
+)]
+/// This struct is used to generate OpenAPI documentation for the cart routes.
+pub struct CartApiDoc;
+
+impl utoipa::Modify for CartApiDoc {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.as_mut().unwrap();
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .description(Some("Input your `<your‑jwt>`"))
+                    .build(),
+            ),
+        )
+    }
+}
+
+pub fn cart_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_cart))
+        .route("/", delete(clear_cart))
+        .route("/items", post(add_cart_item))
+        .route("/items/{product_id}", put(update_cart_item))
+        .route("/items/{product_id}", delete(remove_cart_item))
+}
@@ -0,0 +1,15 @@
+use bigdecimal::BigDecimal;
+use sqlx::prelude::FromRow;
+
+/// Most units of one product a cart can hold.
+pub const MAX_ITEM_QUANTITY: i32 = 99;
+
+/// A cart line joined with the product it references.
+#[derive(Debug, Clone, FromRow)]
+pub struct CartItem {
+    pub product_id: i32,
+    pub product_name: String,
+    pub price: BigDecimal,
+    pub discount: BigDecimal,
+    pub quantity: i32,
+}
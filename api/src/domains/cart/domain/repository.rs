@@ -0,0 +1,51 @@
+//! This module defines the `CartRepository` trait, which abstracts
+//! the database operations related to user carts.
+
+use super::model::CartItem;
+
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+#[async_trait]
+/// Trait representing repository-level operations for cart items.
+pub trait CartRepository: Send + Sync {
+    /// Retrieves every item in the user's cart along with its product data.
+    async fn find_by_user_id(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+    ) -> Result<Vec<CartItem>, sqlx::Error>;
+
+    /// Adds a product to the user's cart, increasing the quantity if it is already present,
+    /// up to `MAX_ITEM_QUANTITY`.
+    /// Returns `false` if the product does not exist.
+    async fn add_item(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+        product_id: i32,
+        quantity: i32,
+    ) -> Result<bool, sqlx::Error>;
+
+    /// Sets the quantity of a product already in the user's cart.
+    /// Returns `false` if the product is not in the cart.
+    async fn update_quantity(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+        product_id: i32,
+        quantity: i32,
+    ) -> Result<bool, sqlx::Error>;
+
+    /// Removes a product from the user's cart.
+    /// Returns `false` if the product is not in the cart.
+    async fn remove_item(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+        product_id: i32,
+    ) -> Result<bool, sqlx::Error>;
+
+    /// Removes every item from the user's cart.
+    async fn clear(&self, pool: PgPool, user_id: i32) -> Result<(), sqlx::Error>;
+}
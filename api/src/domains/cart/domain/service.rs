@@ -0,0 +1,39 @@
+//! This module defines the `CartServiceTrait` responsible for cart-related business logic.
+
+use crate::{
+    common::error::AppError,
+    domains::cart::dto::cart_dto::{AddCartItemDto, CartDto, UpdateCartItemDto},
+};
+
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+#[async_trait]
+/// Trait defining business operations for managing a user's shopping cart.
+pub trait CartServiceTrait: Send + Sync {
+    /// constructor for the service.
+    fn create_service(pool: PgPool) -> Arc<dyn CartServiceTrait>
+    where
+        Self: Sized;
+
+    /// Retrieves the user's cart with computed totals.
+    async fn get_cart(&self, user_id: i32) -> Result<CartDto, AppError>;
+
+    /// Adds a product to the user's cart and returns the updated cart.
+    async fn add_item(&self, user_id: i32, payload: AddCartItemDto) -> Result<CartDto, AppError>;
+
+    /// Changes the quantity of a cart item and returns the updated cart.
+    async fn update_item(
+        &self,
+        user_id: i32,
+        product_id: i32,
+        payload: UpdateCartItemDto,
+    ) -> Result<CartDto, AppError>;
+
+    /// Removes a product from the user's cart and returns the updated cart.
+    async fn remove_item(&self, user_id: i32, product_id: i32) -> Result<CartDto, AppError>;
+
+    /// Empties the user's cart.
+    async fn clear_cart(&self, user_id: i32) -> Result<CartDto, AppError>;
+}
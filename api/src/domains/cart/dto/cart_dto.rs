@@ -0,0 +1,120 @@
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+use crate::{
+    common::price_util::{apply_discount, round_money},
+    domains::cart::domain::model::{CartItem, MAX_ITEM_QUANTITY},
+};
+
+/// Validator for cart line quantities: 1 to `MAX_ITEM_QUANTITY` units.
+fn validate_quantity(quantity: i32) -> Result<(), ValidationError> {
+    if !(1..=MAX_ITEM_QUANTITY).contains(&quantity) {
+        return Err(ValidationError::new("range")
+            .with_message(format!("Quantity must be between 1 and {MAX_ITEM_QUANTITY}").into()));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct AddCartItemDto {
+    #[schema(example = 1)]
+    pub product_id: i32,
+    #[validate(custom(function = "validate_quantity"))]
+    #[schema(example = 2)]
+    pub quantity: i32,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct UpdateCartItemDto {
+    #[validate(custom(function = "validate_quantity"))]
+    #[schema(example = 3)]
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CartItemDto {
+    pub product_id: i32,
+    pub product_name: String,
+    pub quantity: i32,
+    pub unit_price: String,
+    pub discount: String,
+    pub discounted_unit_price: String,
+    pub line_total: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CartDto {
+    pub items: Vec<CartItemDto>,
+    pub item_count: i32,
+    /// Sum of undiscounted line prices.
+    pub subtotal: String,
+    /// Amount saved through product discounts.
+    pub discount_total: String,
+    /// Amount payable after discounts.
+    pub total: String,
+}
+
+impl From<Vec<CartItem>> for CartDto {
+    fn from(cart_items: Vec<CartItem>) -> Self {
+        let mut subtotal = BigDecimal::from(0);
+        let mut total = BigDecimal::from(0);
+        let mut item_count = 0;
+
+        let items = cart_items
+            .into_iter()
+            .map(|item| {
+                let quantity = BigDecimal::from(item.quantity);
+                let discounted_unit_price = apply_discount(&item.price, &item.discount);
+                let line_total = round_money(&(&discounted_unit_price * &quantity));
+
+                subtotal += &item.price * &quantity;
+                total += &line_total;
+                item_count += item.quantity;
+
+                CartItemDto {
+                    product_id: item.product_id,
+                    product_name: item.product_name,
+                    quantity: item.quantity,
+                    unit_price: round_money(&item.price).to_string(),
+                    discount: item.discount.to_string(),
+                    discounted_unit_price: discounted_unit_price.to_string(),
+                    line_total: line_total.to_string(),
+                }
+            })
+            .collect();
+
+        let subtotal = round_money(&subtotal);
+        let total = round_money(&total);
+        let discount_total = &subtotal - &total;
+
+        Self {
+            items,
+            item_count,
+            subtotal: subtotal.to_string(),
+            discount_total: discount_total.to_string(),
+            total: total.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantity_limit_is_reported() {
+        assert!(UpdateCartItemDto {
+            quantity: MAX_ITEM_QUANTITY
+        }
+        .validate()
+        .is_ok());
+        for quantity in [0, MAX_ITEM_QUANTITY + 1] {
+            let err = UpdateCartItemDto { quantity }.validate().unwrap_err();
+            assert!(err
+                .to_string()
+                .contains(&format!("between 1 and {MAX_ITEM_QUANTITY}")));
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::domains::cart::domain::{
+    model::{CartItem, MAX_ITEM_QUANTITY},
+    repository::CartRepository,
+};
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+pub struct CartRepo;
+
+#[async_trait]
+impl CartRepository for CartRepo {
+    async fn find_by_user_id(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+    ) -> Result<Vec<CartItem>, sqlx::Error> {
+        let items = sqlx::query_as!(
+            CartItem,
+            r#"
+            SELECT ci.product_id, p.name as product_name, p.price, p.discount, ci.quantity
+            FROM cart_items ci
+            INNER JOIN products p ON ci.product_id = p.id
+            WHERE ci.user_id = $1
+            ORDER BY ci.created_at, ci.product_id
+            "#,
+            user_id
+        )
+        .fetch_all(&pool)
+        .await?;
+        Ok(items)
+    }
+
+    async fn add_item(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+        product_id: i32,
+        quantity: i32,
+    ) -> Result<bool, sqlx::Error> {
+        let res = sqlx::query!(
+            r#"
+            INSERT INTO cart_items (user_id, product_id, quantity)
+            SELECT $1, p.id, $3
+            FROM products p
+            WHERE p.id = $2
+            ON CONFLICT (user_id, product_id)
+            DO UPDATE SET quantity = LEAST(cart_items.quantity + EXCLUDED.quantity, $4),
+                          updated_at = now()
+            "#,
+            user_id,
+            product_id,
+            quantity,
+            MAX_ITEM_QUANTITY
+        )
+        .execute(&pool)
+        .await?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn update_quantity(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+        product_id: i32,
+        quantity: i32,
+    ) -> Result<bool, sqlx::Error> {
+        let res = sqlx::query!(
+            r#"
+            UPDATE cart_items
+            SET quantity = $3,
+                updated_at = now()
+            WHERE user_id = $1 AND product_id = $2
+            "#,
+            user_id,
+            product_id,
+            quantity
+        )
+        .execute(&pool)
+        .await?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn remove_item(
+        &self,
+        pool: PgPool,
+        user_id: i32,
+        product_id: i32,
+    ) -> Result<bool, sqlx::Error> {
+        let res = sqlx::query!(
+            r#"DELETE FROM cart_items WHERE user_id = $1 AND product_id = $2"#,
+            user_id,
+            product_id
+        )
+        .execute(&pool)
+        .await?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn clear(&self, pool: PgPool, user_id: i32) -> Result<(), sqlx::Error> {
+        sqlx::query!(r#"DELETE FROM cart_items WHERE user_id = $1"#, user_id)
+            .execute(&pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test(
+        migrations = false,
+        fixtures(path = "../../../../db-seed", scripts("01-tables"))
+    )]
+    async fn test_add_item_caps_quantity(pool: PgPool) {
+        let user_id = sqlx::query_scalar!(
+            "INSERT INTO users (username, email) VALUES ('cart', 'cart@example.com') RETURNING id"
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let category_id =
+            sqlx::query_scalar!("INSERT INTO categories (name) VALUES ('cart') RETURNING id")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        let product_id = sqlx::query_scalar!(
+            "INSERT INTO products (name, description, price, category_id)
+             VALUES ('cart', '', 1, $1) RETURNING id",
+            category_id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let repo = CartRepo;
+        let mut quantities = Vec::new();
+        for quantity in [60, 60, 1] {
+            repo.add_item(pool.clone(), user_id, product_id, quantity)
+                .await
+                .unwrap();
+            let items = repo.find_by_user_id(pool.clone(), user_id).await.unwrap();
+            quantities.push(items[0].quantity);
+        }
+
+        assert_eq!(quantities, [60, MAX_ITEM_QUANTITY, MAX_ITEM_QUANTITY]);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{
+    common::error::AppError,
+    domains::cart::{
+        domain::{repository::CartRepository, service::CartServiceTrait},
+        dto::cart_dto::{AddCartItemDto, CartDto, UpdateCartItemDto},
+        infra::impl_repository::CartRepo,
+    },
+};
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+/// Service struct for handling cart-related operations
+/// such as adding, updating, removing and listing cart items.
+/// It uses a repository pattern to abstract the data access layer.
+#[derive(Clone)]
+pub struct CartService {
+    pub pool: PgPool,
+    pub repo: Arc<dyn CartRepository + Send + Sync>,
+}
+
+#[async_trait]
+impl CartServiceTrait for CartService {
+    /// constructor for the service.
+    fn create_service(pool: PgPool) -> Arc<dyn CartServiceTrait> {
+        Arc::new(Self {
+            pool,
+            repo: Arc::new(CartRepo {}),
+        })
+    }
+
+    /// Retrieves the user's cart and computes its totals.
+    async fn get_cart(&self, user_id: i32) -> Result<CartDto, AppError> {
+        match self.repo.find_by_user_id(self.pool.clone(), user_id).await {
+            Ok(items) => Ok(CartDto::from(items)),
+            Err(err) => {
+                tracing::error!("Error fetching cart: {err}");
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
+
+    async fn add_item(&self, user_id: i32, payload: AddCartItemDto) -> Result<CartDto, AppError> {
+        match self
+            .repo
+            .add_item(
+                self.pool.clone(),
+                user_id,
+                payload.product_id,
+                payload.quantity,
+            )
+            .await
+        {
+            Ok(true) => self.get_cart(user_id).await,
+            Ok(false) => Err(AppError::NotFound("Product not found".into())),
+            Err(err) => {
+                tracing::error!("Error adding cart item: {err}");
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
+
+    async fn update_item(
+        &self,
+        user_id: i32,
+        product_id: i32,
+        payload: UpdateCartItemDto,
+    ) -> Result<CartDto, AppError> {
+        match self
+            .repo
+            .update_quantity(self.pool.clone(), user_id, product_id, payload.quantity)
+            .await
+        {
+            Ok(true) => self.get_cart(user_id).await,
+            Ok(false) => Err(AppError::NotFound("Cart item not found".into())),
+            Err(err) => {
+                tracing::error!("Error updating cart item: {err}");
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
+
+    async fn remove_item(&self, user_id: i32, product_id: i32) -> Result<CartDto, AppError> {
+        match self
+            .repo
+            .remove_item(self.pool.clone(), user_id, product_id)
+            .await
+        {
+            Ok(true) => self.get_cart(user_id).await,
+            Ok(false) => Err(AppError::NotFound("Cart item not found".into())),
+            Err(err) => {
+                tracing::error!("Error removing cart item: {err}");
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
+
+    async fn clear_cart(&self, user_id: i32) -> Result<CartDto, AppError> {
+        match self.repo.clear(self.pool.clone(), user_id).await {
+            Ok(()) => Ok(CartDto::from(Vec::new())),
+            Err(err) => {
+                tracing::error!("Error clearing cart: {err}");
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
+}
@@ -1,12 +1,18 @@
 use crate::{
-    common::{app_state::AppState, dto::RestApiResponse, error::AppError},
-    domains::category::dto::category_dto::CategoryDto,
+    common::{
+        app_state::AppState,
+        dto::{Page, PageQuery, RestApiResponse},
+        error::AppError,
+    },
+    domains::category::dto::category_dto::{CategoryDto, CreateCategoryDto, UpdateCategoryDto},
 };
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     response::IntoResponse,
+    Json,
 };
+use validator::Validate;
 
 #[utoipa::path(
     get,
@@ -26,10 +32,99 @@ pub async fn get_category_by_id(
 #[utoipa::path(
     get,
     path = "/category",
-    responses((status = 200, description = "List all categories", body = [CategoryDto])),
+    params(PageQuery),
+    responses((status = 200, description = "List categories, sortable by id or name", body = Page<CategoryDto>)),
     tag = "Categories"
 )]
-pub async fn get_categories(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
-    let products = state.category_service.get_categories().await?;
+pub async fn get_categories(
+    State(state): State<AppState>,
+    Query(page): Query<PageQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let products = state.category_service.get_categories(page).await?;
     Ok(RestApiResponse::success(products))
 }
+
+#[utoipa::path(
+    get,
+    path = "/category/tree",
+    responses((status = 200, description = "Get the category hierarchy with product counts", body = [CategoryDto])),
+    tag = "Categories"
+)]
+pub async fn get_category_tree(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let categories = state.category_service.get_category_tree().await?;
+    Ok(RestApiResponse::success(categories))
+}
+
+#[utoipa::path(
+    post,
+    path = "/category",
+    request_body = CreateCategoryDto,
+    responses(
+        (status = 200, description = "Create a category", body = CategoryDto),
+        (status = 409, description = "A category with this name already exists")
+    ),
+    tag = "Categories"
+)]
+pub async fn create_category(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateCategoryDto>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    let category = state.category_service.create_category(payload).await?;
+    Ok(RestApiResponse::success(category))
+}
+
+#[utoipa::path(
+    put,
+    path = "/category/{id}",
+    request_body = UpdateCategoryDto,
+    responses(
+        (status = 200, description = "Rename or move a category", body = CategoryDto),
+        (status = 409, description = "A category with this name already exists")
+    ),
+    tag = "Categories"
+)]
+pub async fn update_category(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateCategoryDto>,
+) -> Result<impl IntoResponse, AppError> {
+    let id: i32 = id
+        .parse()
+        .map_err(|_| AppError::ValidationError("Invalid category id".into()))?;
+
+    payload.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    let category = state.category_service.update_category(id, payload).await?;
+    Ok(RestApiResponse::success(category))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/category/{id}",
+    responses(
+        (status = 200, description = "Category deleted"),
+        (status = 409, description = "Category still has child categories or products")
+    ),
+    tag = "Categories"
+)]
+pub async fn delete_category(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let id: i32 = id
+        .parse()
+        .map_err(|_| AppError::ValidationError("Invalid category id".into()))?;
+
+    let message = state.category_service.delete_category(id).await?;
+    Ok(RestApiResponse::success_with_message(message, ()))
+}
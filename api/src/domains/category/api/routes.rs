@@ -1,7 +1,14 @@
 use super::handlers::*;
-use crate::{common::app_state::AppState, domains::category::dto::category_dto::CategoryDto};
+use crate::{
+    common::{app_state::AppState, authz},
+    domains::category::dto::category_dto::{CategoryDto, CreateCategoryDto, UpdateCategoryDto},
+};
 
-use axum::{routing::get, Router};
+use axum::{
+    middleware,
+    routing::{get, post, put},
+    Router,
+};
 
 use utoipa::{
     openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
@@ -13,8 +20,12 @@ use utoipa::{
     paths(
         get_category_by_id,
         get_categories,
+        get_category_tree,
+        create_category,
+        update_category,
+        delete_category,
     ),
-    components(schemas(CategoryDto)),
+    components(schemas(CategoryDto, CreateCategoryDto, UpdateCategoryDto)),
     tags(
         (name = "Categories", description = "Category management endpoints")
     ),
@@ -43,7 +54,18 @@ impl utoipa::Modify for CategoryApiDoc {
 }
 
 pub fn category_routes() -> Router<AppState> {
+    // Category management, restricted to admins.
+    let admin_routes = Router::new()
+        .route("/", post(create_category))
+        .route("/{id}", put(update_category).delete(delete_category))
+        .route_layer(middleware::from_fn_with_state(
+            authz::ADMIN,
+            authz::require_roles,
+        ));
+
     Router::new()
         .route("/", get(get_categories))
+        .route("/tree", get(get_category_tree))
         .route("/{id}", get(get_category_by_id))
+        .merge(admin_routes)
 }
@@ -1,6 +1,39 @@
 use sqlx::prelude::FromRow;
+
+use crate::common::pagination::Keyset;
+
 #[derive(Debug, Clone, FromRow)]
 pub struct Category {
     pub id: i32,
     pub name: String,
+    pub parent_id: Option<i32>,
+}
+
+/// A category together with the number of products assigned directly to it.
+#[derive(Debug, Clone, FromRow)]
+pub struct CategoryWithProductCount {
+    pub id: i32,
+    pub name: String,
+    pub parent_id: Option<i32>,
+    pub product_count: i64,
+}
+
+/// Number of child categories and products that still reference a category.
+#[derive(Debug, Clone, FromRow)]
+pub struct CategoryUsage {
+    pub child_count: i64,
+    pub product_count: i64,
+}
+
+impl Keyset for Category {
+    fn keyset_id(&self) -> i32 {
+        self.id
+    }
+
+    fn keyset_value(&self, sort: &str) -> String {
+        match sort {
+            "name" => self.name.clone(),
+            _ => self.id.to_string(),
+        }
+    }
 }
@@ -1,11 +1,69 @@
-use super::model::Category;
+use crate::{
+    common::pagination::PageRequest,
+    domains::category::dto::category_dto::{CreateCategoryDto, UpdateCategoryDto},
+};
+
+use super::model::{Category, CategoryUsage, CategoryWithProductCount};
 
 use async_trait::async_trait;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 
 #[async_trait]
 pub trait CategoryRepository: Send + Sync {
-    async fn find_all(&self, pool: PgPool) -> Result<Vec<Category>, sqlx::Error>;
+    /// Returns a page of categories together with the total number of categories.
+    async fn find_all(
+        &self,
+        pool: PgPool,
+        page: &PageRequest,
+    ) -> Result<(Vec<Category>, i64), sqlx::Error>;
 
     async fn find_by_id(&self, pool: PgPool, id: i32) -> Result<Option<Category>, sqlx::Error>;
+
+    /// Lists all categories with the number of products assigned directly to each.
+    async fn find_all_with_product_counts(
+        &self,
+        pool: PgPool,
+    ) -> Result<Vec<CategoryWithProductCount>, sqlx::Error>;
+
+    async fn find_by_id_for_update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+    ) -> Result<Option<Category>, sqlx::Error>;
+
+    /// Returns whether `candidate_id` is the category itself or one of its descendants.
+    async fn is_self_or_descendant(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        candidate_id: i32,
+    ) -> Result<bool, sqlx::Error>;
+
+    async fn find_usage(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+    ) -> Result<CategoryUsage, sqlx::Error>;
+
+    async fn create(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        category: CreateCategoryDto,
+    ) -> Result<Category, sqlx::Error>;
+
+    async fn update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        category: UpdateCategoryDto,
+    ) -> Result<Category, sqlx::Error>;
+
+    /// Returns the storage keys of every image of the products in the category.
+    async fn find_product_image_keys(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+    ) -> Result<Vec<String>, sqlx::Error>;
+
+    async fn delete(&self, tx: &mut Transaction<'_, Postgres>, id: i32) -> Result<(), sqlx::Error>;
 }
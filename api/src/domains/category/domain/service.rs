@@ -1,4 +1,11 @@
-use crate::{common::error::AppError, domains::category::dto::category_dto::CategoryDto};
+use crate::{
+    common::{
+        dto::{Page, PageQuery},
+        error::AppError,
+        storage::Storage,
+    },
+    domains::category::dto::category_dto::{CategoryDto, CreateCategoryDto, UpdateCategoryDto},
+};
 
 use async_trait::async_trait;
 use sqlx::PgPool;
@@ -7,11 +14,26 @@ use std::sync::Arc;
 #[async_trait]
 pub trait CategoryServiceTrait: Send + Sync {
     /// constructor for the service.
-    fn create_service(pool: PgPool) -> Arc<dyn CategoryServiceTrait>
+    fn create_service(pool: PgPool, storage: Arc<dyn Storage>) -> Arc<dyn CategoryServiceTrait>
     where
         Self: Sized;
 
     async fn get_category_by_id(&self, id: i32) -> Result<CategoryDto, AppError>;
 
-    async fn get_categories(&self) -> Result<Vec<CategoryDto>, AppError>;
+    async fn get_categories(&self, page: PageQuery) -> Result<Page<CategoryDto>, AppError>;
+
+    /// Returns the top-level categories with their descendants and product counts nested.
+    async fn get_category_tree(&self) -> Result<Vec<CategoryDto>, AppError>;
+
+    async fn create_category(&self, payload: CreateCategoryDto) -> Result<CategoryDto, AppError>;
+
+    /// Renames a category and sets its parent.
+    async fn update_category(
+        &self,
+        id: i32,
+        payload: UpdateCategoryDto,
+    ) -> Result<CategoryDto, AppError>;
+
+    /// Deletes a category that has neither child categories nor products.
+    async fn delete_category(&self, id: i32) -> Result<String, AppError>;
 }
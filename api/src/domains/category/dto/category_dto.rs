@@ -1,12 +1,24 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
+use validator::Validate;
 
-use crate::domains::category::domain::model::Category;
+use crate::domains::category::domain::model::{Category, CategoryWithProductCount};
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CategoryDto {
     pub id: i32,
     pub name: String,
+    pub parent_id: Option<i32>,
+    /// Number of products in this category and all of its descendants.
+    /// Only present in the category tree.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_count: Option<i64>,
+    /// Child categories; only present in the category tree.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schema(no_recursion)]
+    pub children: Vec<CategoryDto>,
 }
 
 impl From<Category> for CategoryDto {
@@ -14,6 +26,111 @@ impl From<Category> for CategoryDto {
         Self {
             id: category.id,
             name: category.name,
+            parent_id: category.parent_id,
+            product_count: None,
+            children: Vec::new(),
+        }
+    }
+}
+
+impl CategoryDto {
+    /// Builds the category forest from a flat list, rolling product counts up to each ancestor.
+    /// Sibling order follows the order of the input.
+    pub fn build_tree(categories: Vec<CategoryWithProductCount>) -> Vec<CategoryDto> {
+        let mut by_parent: HashMap<Option<i32>, Vec<CategoryWithProductCount>> = HashMap::new();
+        for category in categories {
+            by_parent
+                .entry(category.parent_id)
+                .or_default()
+                .push(category);
+        }
+
+        fn build(
+            parent_id: Option<i32>,
+            by_parent: &mut HashMap<Option<i32>, Vec<CategoryWithProductCount>>,
+        ) -> Vec<CategoryDto> {
+            let categories = by_parent.remove(&parent_id).unwrap_or_default();
+            categories
+                .into_iter()
+                .map(|category| {
+                    let children = build(Some(category.id), by_parent);
+                    let product_count = category.product_count
+                        + children
+                            .iter()
+                            .map(|c| c.product_count.unwrap_or(0))
+                            .sum::<i64>();
+                    CategoryDto {
+                        id: category.id,
+                        name: category.name,
+                        parent_id: category.parent_id,
+                        product_count: Some(product_count),
+                        children,
+                    }
+                })
+                .collect()
+        }
+
+        build(None, &mut by_parent)
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct CreateCategoryDto {
+    #[validate(length(
+        min = 1,
+        max = 64,
+        message = "Name must be between 1 and 64 characters"
+    ))]
+    #[schema(example = "Breakfast Food")]
+    pub name: String,
+    #[schema(example = 2)]
+    pub parent_id: Option<i32>,
+}
+
+/// Request body for renaming or moving a category.
+/// An omitted or null `parent_id` makes the category a top-level one.
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct UpdateCategoryDto {
+    #[validate(length(
+        min = 1,
+        max = 64,
+        message = "Name must be between 1 and 64 characters"
+    ))]
+    #[schema(example = "Breakfast Food")]
+    pub name: String,
+    #[schema(example = 2)]
+    pub parent_id: Option<i32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: i32, parent_id: Option<i32>, product_count: i64) -> CategoryWithProductCount {
+        CategoryWithProductCount {
+            id,
+            name: format!("category {id}"),
+            parent_id,
+            product_count,
         }
     }
+
+    #[test]
+    fn test_build_tree_nests_children_and_rolls_up_counts() {
+        let tree = CategoryDto::build_tree(vec![
+            row(1, None, 2),
+            row(2, Some(1), 3),
+            row(3, Some(2), 4),
+            row(4, None, 0),
+        ]);
+
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].id, 1);
+        assert_eq!(tree[0].product_count, Some(9));
+        assert_eq!(tree[0].children[0].id, 2);
+        assert_eq!(tree[0].children[0].product_count, Some(7));
+        assert_eq!(tree[0].children[0].children[0].product_count, Some(4));
+        assert_eq!(tree[1].product_count, Some(0));
+        assert!(tree[1].children.is_empty());
+    }
 }
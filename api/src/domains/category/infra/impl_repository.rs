@@ -1,11 +1,40 @@
-use crate::domains::category::domain::{model::Category, repository::CategoryRepository};
+use crate::{
+    common::pagination::{PageRequest, SortField, SortType},
+    domains::category::{
+        domain::{
+            model::{Category, CategoryUsage, CategoryWithProductCount},
+            repository::CategoryRepository,
+        },
+        dto::category_dto::{CreateCategoryDto, UpdateCategoryDto},
+    },
+};
 use async_trait::async_trait;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, QueryBuilder, Transaction};
 
 pub struct CategoryRepo;
 
+/// Fields category lists can be sorted by; the first one is the default.
+pub const CATEGORY_SORT_FIELDS: &[SortField] = &[
+    SortField {
+        name: "id",
+        column: "c.id",
+        sort_type: SortType::Integer,
+    },
+    SortField {
+        name: "name",
+        column: "c.name",
+        sort_type: SortType::Text,
+    },
+];
+
 const FIND_ALL_CATEGORIES_QUERY: &str = r#"
-    SELECT *
+    SELECT c.*
+    FROM categories c
+    WHERE 1=1
+"#;
+
+const COUNT_CATEGORIES_QUERY: &str = r#"
+    SELECT COUNT(*)
     FROM categories
 "#;
 
@@ -15,13 +44,82 @@ const FIND_CATEGORY_BY_ID_QUERY: &str = r#"
     WHERE id = $1
 "#;
 
+const FIND_ALL_CATEGORIES_WITH_PRODUCT_COUNTS_QUERY: &str = r#"
+    SELECT c.id, c.name, c.parent_id, COUNT(p.id) AS product_count
+    FROM categories c
+    LEFT JOIN products p ON p.category_id = c.id
+    GROUP BY c.id
+    ORDER BY c.name
+"#;
+
+const FIND_CATEGORY_BY_ID_FOR_UPDATE_QUERY: &str = r#"
+    SELECT *
+    FROM categories
+    WHERE id = $1
+    FOR UPDATE
+"#;
+
+const IS_SELF_OR_DESCENDANT_QUERY: &str = r#"
+    WITH RECURSIVE subtree AS (
+        SELECT id FROM categories WHERE id = $1
+        UNION ALL
+        SELECT c.id FROM categories c INNER JOIN subtree s ON c.parent_id = s.id
+    )
+    SELECT EXISTS (SELECT 1 FROM subtree WHERE id = $2)
+"#;
+
+const FIND_CATEGORY_USAGE_QUERY: &str = r#"
+    SELECT
+        (SELECT COUNT(*) FROM categories WHERE parent_id = $1) AS child_count,
+        (SELECT COUNT(*) FROM products WHERE category_id = $1) AS product_count
+"#;
+
+const CREATE_CATEGORY_QUERY: &str = r#"
+    INSERT INTO categories (name, parent_id)
+    VALUES ($1, $2)
+    RETURNING *
+"#;
+
+const UPDATE_CATEGORY_QUERY: &str = r#"
+    UPDATE categories
+    SET name = $2,
+        parent_id = $3
+    WHERE id = $1
+    RETURNING *
+"#;
+
+const FIND_PRODUCT_IMAGE_KEYS_QUERY: &str = r#"
+    SELECT unnest(ARRAY[i.original_key, i.thumbnail_key, i.card_key, i.detail_key, i.webp_key])
+    FROM product_images i
+    JOIN products p ON p.id = i.product_id
+    WHERE p.category_id = $1
+"#;
+
+const DELETE_CATEGORY_QUERY: &str = r#"
+    DELETE FROM categories
+    WHERE id = $1
+"#;
+
 #[async_trait]
 impl CategoryRepository for CategoryRepo {
-    async fn find_all(&self, pool: PgPool) -> Result<Vec<Category>, sqlx::Error> {
-        let categories = sqlx::query_as::<_, Category>(FIND_ALL_CATEGORIES_QUERY)
+    async fn find_all(
+        &self,
+        pool: PgPool,
+        page: &PageRequest,
+    ) -> Result<(Vec<Category>, i64), sqlx::Error> {
+        let mut builder = QueryBuilder::<Postgres>::new(FIND_ALL_CATEGORIES_QUERY);
+        page.push_after(&mut builder, "c.id");
+        page.push_order_and_limit(&mut builder, "c.id");
+        let categories = builder
+            .build_query_as::<Category>()
             .fetch_all(&pool)
             .await?;
-        Ok(categories)
+
+        let total = sqlx::query_scalar::<_, i64>(COUNT_CATEGORIES_QUERY)
+            .fetch_one(&pool)
+            .await?;
+
+        Ok((categories, total))
     }
 
     async fn find_by_id(&self, pool: PgPool, id: i32) -> Result<Option<Category>, sqlx::Error> {
@@ -31,4 +129,102 @@ impl CategoryRepository for CategoryRepo {
             .await?;
         Ok(category)
     }
+
+    async fn find_all_with_product_counts(
+        &self,
+        pool: PgPool,
+    ) -> Result<Vec<CategoryWithProductCount>, sqlx::Error> {
+        let categories = sqlx::query_as::<_, CategoryWithProductCount>(
+            FIND_ALL_CATEGORIES_WITH_PRODUCT_COUNTS_QUERY,
+        )
+        .fetch_all(&pool)
+        .await?;
+        Ok(categories)
+    }
+
+    async fn find_by_id_for_update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+    ) -> Result<Option<Category>, sqlx::Error> {
+        let category = sqlx::query_as::<_, Category>(FIND_CATEGORY_BY_ID_FOR_UPDATE_QUERY)
+            .bind(id)
+            .fetch_optional(&mut **tx)
+            .await?;
+        Ok(category)
+    }
+
+    async fn is_self_or_descendant(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        candidate_id: i32,
+    ) -> Result<bool, sqlx::Error> {
+        let found = sqlx::query_scalar::<_, bool>(IS_SELF_OR_DESCENDANT_QUERY)
+            .bind(id)
+            .bind(candidate_id)
+            .fetch_one(&mut **tx)
+            .await?;
+        Ok(found)
+    }
+
+    async fn find_usage(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+    ) -> Result<CategoryUsage, sqlx::Error> {
+        let usage = sqlx::query_as::<_, CategoryUsage>(FIND_CATEGORY_USAGE_QUERY)
+            .bind(id)
+            .fetch_one(&mut **tx)
+            .await?;
+        Ok(usage)
+    }
+
+    async fn create(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        category: CreateCategoryDto,
+    ) -> Result<Category, sqlx::Error> {
+        let category = sqlx::query_as::<_, Category>(CREATE_CATEGORY_QUERY)
+            .bind(category.name)
+            .bind(category.parent_id)
+            .fetch_one(&mut **tx)
+            .await?;
+        Ok(category)
+    }
+
+    async fn update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        category: UpdateCategoryDto,
+    ) -> Result<Category, sqlx::Error> {
+        let category = sqlx::query_as::<_, Category>(UPDATE_CATEGORY_QUERY)
+            .bind(id)
+            .bind(category.name)
+            .bind(category.parent_id)
+            .fetch_one(&mut **tx)
+            .await?;
+        Ok(category)
+    }
+
+    async fn find_product_image_keys(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let keys = sqlx::query_scalar::<_, String>(FIND_PRODUCT_IMAGE_KEYS_QUERY)
+            .bind(id)
+            .fetch_all(&mut **tx)
+            .await?;
+        Ok(keys)
+    }
+
+    async fn delete(&self, tx: &mut Transaction<'_, Postgres>, id: i32) -> Result<(), sqlx::Error> {
+        sqlx::query(DELETE_CATEGORY_QUERY)
+            .bind(id)
+            .execute(&mut **tx)
+            .await?;
+        Ok(())
+    }
 }
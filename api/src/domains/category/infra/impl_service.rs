@@ -1,31 +1,53 @@
 use crate::{
-    common::error::AppError,
+    common::{
+        dto::{Page, PageQuery},
+        error::AppError,
+        pagination::PageRequest,
+        storage::Storage,
+    },
     domains::category::{
         domain::{repository::CategoryRepository, service::CategoryServiceTrait},
-        dto::category_dto::CategoryDto,
-        infra::impl_repository::CategoryRepo,
+        dto::category_dto::{CategoryDto, CreateCategoryDto, UpdateCategoryDto},
+        infra::impl_repository::{CategoryRepo, CATEGORY_SORT_FIELDS},
     },
 };
 use async_trait::async_trait;
 use sqlx::PgPool;
 use std::sync::Arc;
 
-/// Service struct for handling user-related operations
-/// such as creating, updating, deleting, and fetching users.
+/// Service struct for handling category-related operations
+/// such as creating, renaming, deleting, and fetching categories.
 /// It uses a repository pattern to abstract the data access layer.
 #[derive(Clone)]
 pub struct CategoryService {
     pub pool: PgPool,
     pub repo: Arc<dyn CategoryRepository + Send + Sync>,
+    /// Public asset storage holding the product images.
+    pub storage: Arc<dyn Storage>,
+}
+
+/// Maps constraint violations on category writes to client errors.
+fn map_write_error(err: sqlx::Error) -> AppError {
+    if let Some(db_err) = err.as_database_error() {
+        if db_err.is_unique_violation() {
+            return AppError::Conflict("A category with this name already exists".into());
+        }
+        if db_err.is_foreign_key_violation() {
+            return AppError::ValidationError("Parent category does not exist".into());
+        }
+    }
+    tracing::error!("Error writing category: {err}");
+    AppError::DatabaseError(err)
 }
 
 #[async_trait]
 impl CategoryServiceTrait for CategoryService {
     /// constructor for the service.
-    fn create_service(pool: PgPool) -> Arc<dyn CategoryServiceTrait> {
+    fn create_service(pool: PgPool, storage: Arc<dyn Storage>) -> Arc<dyn CategoryServiceTrait> {
         Arc::new(Self {
             pool,
             repo: Arc::new(CategoryRepo {}),
+            storage,
         })
     }
 
@@ -40,17 +62,161 @@ impl CategoryServiceTrait for CategoryService {
         }
     }
 
-    async fn get_categories(&self) -> Result<Vec<CategoryDto>, AppError> {
-        match self.repo.find_all(self.pool.clone()).await {
-            Ok(categories) => {
-                let category_dtos: Vec<CategoryDto> =
-                    categories.into_iter().map(Into::into).collect();
-                Ok(category_dtos)
-            }
+    async fn get_categories(&self, page: PageQuery) -> Result<Page<CategoryDto>, AppError> {
+        let page = PageRequest::from_query(page, CATEGORY_SORT_FIELDS)?;
+        match self.repo.find_all(self.pool.clone(), &page).await {
+            Ok((categories, total)) => Ok(page.into_page(categories, total)),
             Err(err) => {
                 tracing::error!("Error fetching products: {err}");
                 Err(AppError::DatabaseError(err))
             }
         }
     }
+
+    async fn get_category_tree(&self) -> Result<Vec<CategoryDto>, AppError> {
+        match self
+            .repo
+            .find_all_with_product_counts(self.pool.clone())
+            .await
+        {
+            Ok(categories) => Ok(CategoryDto::build_tree(categories)),
+            Err(err) => {
+                tracing::error!("Error fetching category tree: {err}");
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
+
+    async fn create_category(&self, payload: CreateCategoryDto) -> Result<CategoryDto, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        match self.repo.create(&mut tx, payload).await {
+            Ok(category) => {
+                tx.commit().await?;
+                Ok(CategoryDto::from(category))
+            }
+            Err(err) => {
+                tx.rollback().await?;
+                Err(map_write_error(err))
+            }
+        }
+    }
+
+    /// Renames and re-parents a category.
+    /// Moving a category under itself or one of its descendants is rejected to keep the tree acyclic.
+    async fn update_category(
+        &self,
+        id: i32,
+        payload: UpdateCategoryDto,
+    ) -> Result<CategoryDto, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        match self.repo.find_by_id_for_update(&mut tx, id).await {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                tx.rollback().await?;
+                return Err(AppError::NotFound("Category not found".into()));
+            }
+            Err(err) => {
+                tracing::error!("Error retrieving category: {err}");
+                tx.rollback().await?;
+                return Err(AppError::DatabaseError(err));
+            }
+        }
+
+        if let Some(parent_id) = payload.parent_id {
+            match self
+                .repo
+                .is_self_or_descendant(&mut tx, id, parent_id)
+                .await
+            {
+                Ok(false) => {}
+                Ok(true) => {
+                    tx.rollback().await?;
+                    return Err(AppError::ValidationError(
+                        "A category cannot be moved under itself or one of its descendants".into(),
+                    ));
+                }
+                Err(err) => {
+                    tracing::error!("Error checking category hierarchy: {err}");
+                    tx.rollback().await?;
+                    return Err(AppError::DatabaseError(err));
+                }
+            }
+        }
+
+        match self.repo.update(&mut tx, id, payload).await {
+            Ok(category) => {
+                tx.commit().await?;
+                Ok(CategoryDto::from(category))
+            }
+            Err(err) => {
+                tx.rollback().await?;
+                Err(map_write_error(err))
+            }
+        }
+    }
+
+    /// Deletes a category.
+    /// Products cascade with their category, so categories still in use are never deleted.
+    async fn delete_category(&self, id: i32) -> Result<String, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        match self.repo.find_by_id_for_update(&mut tx, id).await {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                tx.rollback().await?;
+                return Err(AppError::NotFound("Category not found".into()));
+            }
+            Err(err) => {
+                tracing::error!("Error retrieving category: {err}");
+                tx.rollback().await?;
+                return Err(AppError::DatabaseError(err));
+            }
+        }
+
+        let usage = match self.repo.find_usage(&mut tx, id).await {
+            Ok(usage) => usage,
+            Err(err) => {
+                tracing::error!("Error checking category usage: {err}");
+                tx.rollback().await?;
+                return Err(AppError::DatabaseError(err));
+            }
+        };
+
+        if usage.child_count > 0 || usage.product_count > 0 {
+            tx.rollback().await?;
+            return Err(AppError::Conflict(format!(
+                "Category still has {} child categories and {} products",
+                usage.child_count, usage.product_count
+            )));
+        }
+
+        // Product images are removed by the database cascade, their files are removed here.
+        let image_keys = match self.repo.find_product_image_keys(&mut tx, id).await {
+            Ok(keys) => keys,
+            Err(err) => {
+                tracing::error!("Error fetching product images: {err}");
+                tx.rollback().await?;
+                return Err(AppError::DatabaseError(err));
+            }
+        };
+
+        match self.repo.delete(&mut tx, id).await {
+            Ok(()) => {
+                tx.commit().await?;
+                for key in &image_keys {
+                    if let Err(err) = self.storage.delete(key).await {
+                        tracing::error!("Error removing product image {key}: {err}");
+                    }
+                }
+                Ok("Category deleted".into())
+            }
+            Err(err) => {
+                tracing::error!("Error deleting category: {err}");
+                tx.rollback().await?;
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
 }
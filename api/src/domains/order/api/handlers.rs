@@ -0,0 +1,107 @@
+use crate::{
+    common::{
+        app_state::AppState, authz::AuthUser, dto::RestApiResponse, error::AppError, jwt::Claims,
+    },
+    domains::order::dto::order_dto::{CreateOrderDto, OrderDto, UpdateOrderStatusDto},
+};
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+
+use validator::Validate;
+
+#[utoipa::path(
+    post,
+    path = "/order",
+    request_body = CreateOrderDto,
+    responses(
+        (status = 200, description = "Place an order from the given items or the cart", body = OrderDto),
+        (status = 403, description = "Email address must be verified before checkout")
+    ),
+    tag = "Orders"
+)]
+pub async fn create_order(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<CreateOrderDto>,
+) -> Result<impl IntoResponse, AppError> {
+    if state.config.email_verification_policy.blocks_checkout() {
+        auth_user.require_verified_email()?;
+    }
+
+    payload.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    let order = state
+        .order_service
+        .create_order(auth_user.user_id, payload)
+        .await?;
+    Ok(RestApiResponse::success(order))
+}
+
+#[utoipa::path(
+    get,
+    path = "/order",
+    responses((status = 200, description = "List the current user's orders", body = [OrderDto])),
+    tag = "Orders"
+)]
+pub async fn get_orders(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<impl IntoResponse, AppError> {
+    let orders = state.order_service.get_orders(claims.user_id()?).await?;
+    Ok(RestApiResponse::success(orders))
+}
+
+#[utoipa::path(
+    get,
+    path = "/order/{id}",
+    responses((status = 200, description = "Get order by ID", body = OrderDto)),
+    tag = "Orders"
+)]
+pub async fn get_order_by_id(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let id: i32 = id
+        .parse()
+        .map_err(|_| AppError::ValidationError("Invalid order id".into()))?;
+    let order = state
+        .order_service
+        .get_order_by_id(claims.user_id()?, id)
+        .await?;
+    Ok(RestApiResponse::success(order))
+}
+
+#[utoipa::path(
+    put,
+    path = "/order/{id}/status",
+    request_body = UpdateOrderStatusDto,
+    responses(
+        (status = 200, description = "Update order status", body = OrderDto),
+        (status = 403, description = "Customers may only cancel their own orders"),
+        (status = 409, description = "Illegal status transition")
+    ),
+    tag = "Orders"
+)]
+pub async fn update_order_status(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateOrderStatusDto>,
+) -> Result<impl IntoResponse, AppError> {
+    let id: i32 = id
+        .parse()
+        .map_err(|_| AppError::ValidationError("Invalid order id".into()))?;
+    let order = state
+        .order_service
+        .update_order_status(auth_user, id, payload)
+        .await?;
+    Ok(RestApiResponse::success(order))
+}
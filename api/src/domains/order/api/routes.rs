@@ -0,0 +1,70 @@
+use super::handlers::*;
+use crate::{
+    common::app_state::AppState,
+    domains::order::{
+        domain::model::OrderStatus,
+        dto::order_dto::{
+            CreateOrderDto, OrderDto, OrderItemDto, OrderItemInputDto, UpdateOrderStatusDto,
+        },
+    },
+};
+
+use axum::{
+    routing::{get, put},
+    Router,
+};
+
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    OpenApi,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        create_order,
+        get_orders,
+        get_order_by_id,
+        update_order_status,
+    ),
+    components(schemas(
+        OrderDto,
+        OrderItemDto,
+        OrderStatus,
+        CreateOrderDto,
+        OrderItemInputDto,
+        UpdateOrderStatusDto
+    )),
+    tags(
+        (name = "Orders", description = "Order placement and lifecycle endpoints")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    modifiers(&OrderApiDoc)
+)]
+/// This struct is used to generate OpenAPI documentation for the order routes.
+pub struct OrderApiDoc;
+
+impl utoipa::Modify for OrderApiDoc {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.as_mut().unwrap();
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .description(Some("Input your `<your‑jwt>`"))
+                    .build(),
+            ),
+        )
+    }
+}
+
+pub fn order_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_orders).post(create_order))
+        .route("/{id}", get(get_order_by_id))
+        .route("/{id}/status", put(update_order_status))
+}
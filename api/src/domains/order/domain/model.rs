@@ -0,0 +1,164 @@
+use std::fmt::Display;
+
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+
+/// Lifecycle state of an order.
+///
+/// The happy path is pending → confirmed → preparing → out_for_delivery → delivered.
+/// An order can be cancelled until it leaves the kitchen and refunded once it has been
+/// paid for, i.e. after confirmation.
+///
+/// Stock is reserved when the order is placed, deducted when it goes out for delivery
+/// and released if it is cancelled or refunded before that.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum OrderStatus {
+    Pending,
+    Confirmed,
+    Preparing,
+    OutForDelivery,
+    Delivered,
+    Cancelled,
+    Refunded,
+}
+
+impl OrderStatus {
+    /// Returns the string stored in the `orders.status` column.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderStatus::Pending => "pending",
+            OrderStatus::Confirmed => "confirmed",
+            OrderStatus::Preparing => "preparing",
+            OrderStatus::OutForDelivery => "out_for_delivery",
+            OrderStatus::Delivered => "delivered",
+            OrderStatus::Cancelled => "cancelled",
+            OrderStatus::Refunded => "refunded",
+        }
+    }
+
+    /// Returns whether an order in this status still holds a stock reservation.
+    pub fn holds_reservation(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Pending | OrderStatus::Confirmed | OrderStatus::Preparing
+        )
+    }
+
+    /// Returns whether the order may move from this status to `next`.
+    pub fn can_transition_to(&self, next: OrderStatus) -> bool {
+        use OrderStatus::*;
+
+        matches!(
+            (self, next),
+            (Pending, Confirmed)
+                | (Pending, Cancelled)
+                | (Confirmed, Preparing)
+                | (Confirmed, Cancelled)
+                | (Confirmed, Refunded)
+                | (Preparing, OutForDelivery)
+                | (Preparing, Cancelled)
+                | (OutForDelivery, Delivered)
+                | (Delivered, Refunded)
+        )
+    }
+}
+
+impl Display for OrderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Domain model representing a placed order.
+#[derive(Debug, Clone, FromRow)]
+pub struct Order {
+    pub id: i32,
+    pub user_id: i32,
+    pub status: OrderStatus,
+    pub subtotal: BigDecimal,
+    pub discount_total: BigDecimal,
+    pub total: BigDecimal,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A line item of an order, holding the product data as it was at purchase time.
+#[derive(Debug, Clone, FromRow)]
+pub struct OrderItem {
+    pub id: i32,
+    pub order_id: i32,
+    pub product_id: Option<i32>,
+    pub product_name: String,
+    pub unit_price: BigDecimal,
+    pub discount: BigDecimal,
+    pub quantity: i32,
+    pub line_total: BigDecimal,
+}
+
+/// Current catalogue data of a product being ordered.
+#[derive(Debug, Clone, FromRow)]
+pub struct ProductSnapshot {
+    pub id: i32,
+    pub name: String,
+    pub price: BigDecimal,
+    pub discount: BigDecimal,
+    pub stock_quantity: i32,
+    pub reserved_quantity: i32,
+}
+
+/// A requested product and quantity, taken from the cart or the request body.
+#[derive(Debug, Clone, FromRow)]
+pub struct OrderLine {
+    pub product_id: i32,
+    pub quantity: i32,
+}
+
+/// Data required to insert a new order row.
+#[derive(Debug, Clone)]
+pub struct NewOrder {
+    pub user_id: i32,
+    pub subtotal: BigDecimal,
+    pub discount_total: BigDecimal,
+    pub total: BigDecimal,
+}
+
+/// Data required to insert a new order item row.
+#[derive(Debug, Clone)]
+pub struct NewOrderItem {
+    pub product_id: i32,
+    pub product_name: String,
+    pub unit_price: BigDecimal,
+    pub discount: BigDecimal,
+    pub quantity: i32,
+    pub line_total: BigDecimal,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_status_transitions() {
+        use OrderStatus::*;
+
+        assert!(Pending.can_transition_to(Confirmed));
+        assert!(Confirmed.can_transition_to(Preparing));
+        assert!(Preparing.can_transition_to(OutForDelivery));
+        assert!(OutForDelivery.can_transition_to(Delivered));
+        assert!(Pending.can_transition_to(Cancelled));
+        assert!(Delivered.can_transition_to(Refunded));
+
+        // Skipping steps, going backwards and leaving terminal states are rejected.
+        assert!(!Pending.can_transition_to(Delivered));
+        assert!(!Delivered.can_transition_to(Preparing));
+        assert!(!OutForDelivery.can_transition_to(Cancelled));
+        assert!(!Cancelled.can_transition_to(Pending));
+        assert!(!Refunded.can_transition_to(Delivered));
+        assert!(!Pending.can_transition_to(Pending));
+    }
+}
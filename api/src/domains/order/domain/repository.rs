@@ -0,0 +1,93 @@
+//! This module defines the `OrderRepository` trait, which abstracts
+//! the database operations related to orders and their line items.
+
+use super::model::{
+    NewOrder, NewOrderItem, Order, OrderItem, OrderLine, OrderStatus, ProductSnapshot,
+};
+
+use async_trait::async_trait;
+use sqlx::{PgPool, Postgres, Transaction};
+
+#[async_trait]
+/// Trait representing repository-level operations for orders.
+pub trait OrderRepository: Send + Sync {
+    /// Retrieves all orders placed by the given user, newest first.
+    async fn find_by_user_id(&self, pool: PgPool, user_id: i32) -> Result<Vec<Order>, sqlx::Error>;
+
+    /// Finds an order by its unique identifier.
+    async fn find_by_id(&self, pool: PgPool, id: i32) -> Result<Option<Order>, sqlx::Error>;
+
+    /// Retrieves the line items of the given orders.
+    async fn find_items_by_order_ids(
+        &self,
+        pool: PgPool,
+        order_ids: &[i32],
+    ) -> Result<Vec<OrderItem>, sqlx::Error>;
+
+    /// Reads the products and quantities currently in the user's cart within an active transaction.
+    async fn find_cart_lines(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<Vec<OrderLine>, sqlx::Error>;
+
+    /// Empties the user's cart within an active transaction.
+    async fn clear_cart(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Reads the current catalogue data of the given products within an active transaction,
+    /// locking their rows so stock cannot change until the transaction ends.
+    async fn find_products(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        product_ids: &[i32],
+    ) -> Result<Vec<ProductSnapshot>, sqlx::Error>;
+
+    /// Inserts a new order with its line items within an active transaction.
+    /// Returns the new order's ID.
+    async fn create(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        order: NewOrder,
+        items: Vec<NewOrderItem>,
+    ) -> Result<i32, sqlx::Error>;
+
+    /// Adds the given quantities to the reserved stock of the products.
+    async fn reserve_stock(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        lines: &[OrderLine],
+    ) -> Result<(), sqlx::Error>;
+
+    /// Returns the stock reserved by an order to the available pool.
+    async fn release_stock(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        order_id: i32,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Deducts the stock reserved by an order from the products' stock quantity.
+    async fn commit_stock(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        order_id: i32,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Finds an order by ID and locks its row for the rest of the transaction.
+    async fn find_by_id_for_update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+    ) -> Result<Option<Order>, sqlx::Error>;
+
+    /// Sets the status of an order within an active transaction and returns the updated row.
+    async fn update_status(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        status: OrderStatus,
+    ) -> Result<Order, sqlx::Error>;
+}
@@ -0,0 +1,42 @@
+//! This module defines the `OrderServiceTrait` responsible for order placement
+//! and order lifecycle management.
+
+use crate::{
+    common::{authz::AuthUser, error::AppError},
+    domains::order::dto::order_dto::{CreateOrderDto, OrderDto, UpdateOrderStatusDto},
+};
+
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+#[async_trait]
+/// Trait defining business operations for orders.
+pub trait OrderServiceTrait: Send + Sync {
+    /// constructor for the service.
+    fn create_service(pool: PgPool) -> Arc<dyn OrderServiceTrait>
+    where
+        Self: Sized;
+
+    /// Places an order from the given items, or from the user's cart when none are given.
+    async fn create_order(
+        &self,
+        user_id: i32,
+        payload: CreateOrderDto,
+    ) -> Result<OrderDto, AppError>;
+
+    /// Retrieves all orders of the user.
+    async fn get_orders(&self, user_id: i32) -> Result<Vec<OrderDto>, AppError>;
+
+    /// Retrieves one of the user's orders by its ID.
+    async fn get_order_by_id(&self, user_id: i32, id: i32) -> Result<OrderDto, AppError>;
+
+    /// Moves an order to a new status, rejecting illegal transitions.
+    /// Staff can move any order; customers can only cancel their own.
+    async fn update_order_status(
+        &self,
+        auth_user: AuthUser,
+        id: i32,
+        payload: UpdateOrderStatusDto,
+    ) -> Result<OrderDto, AppError>;
+}
@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::{
+    common::price_util::round_money,
+    domains::order::domain::model::{Order, OrderItem, OrderStatus},
+};
+
+#[derive(Debug, Clone, Deserialize, ToSchema, Validate)]
+pub struct OrderItemInputDto {
+    #[schema(example = 1)]
+    pub product_id: i32,
+    #[validate(range(min = 1, max = 99, message = "Quantity must be between 1 and 99"))]
+    #[schema(example = 2)]
+    pub quantity: i32,
+}
+
+/// Request body for placing an order.
+/// When `items` is omitted or empty, the order is created from the user's cart
+/// and the cart is emptied.
+#[derive(Debug, Default, Deserialize, ToSchema, Validate)]
+pub struct CreateOrderDto {
+    #[validate(nested)]
+    pub items: Option<Vec<OrderItemInputDto>>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateOrderStatusDto {
+    #[schema(example = "confirmed")]
+    pub status: OrderStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrderItemDto {
+    pub id: i32,
+    pub product_id: Option<i32>,
+    pub product_name: String,
+    pub unit_price: String,
+    pub discount: String,
+    pub quantity: i32,
+    pub line_total: String,
+}
+
+impl From<OrderItem> for OrderItemDto {
+    fn from(item: OrderItem) -> Self {
+        Self {
+            id: item.id,
+            product_id: item.product_id,
+            product_name: item.product_name,
+            unit_price: round_money(&item.unit_price).to_string(),
+            discount: item.discount.to_string(),
+            quantity: item.quantity,
+            line_total: round_money(&item.line_total).to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrderDto {
+    pub id: i32,
+    pub user_id: i32,
+    pub status: OrderStatus,
+    pub items: Vec<OrderItemDto>,
+    pub subtotal: String,
+    pub discount_total: String,
+    pub total: String,
+    #[serde(with = "crate::common::ts_format")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "crate::common::ts_format")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl OrderDto {
+    /// Builds the DTO from an order and its line items.
+    pub fn from_parts(order: Order, items: Vec<OrderItem>) -> Self {
+        Self {
+            id: order.id,
+            user_id: order.user_id,
+            status: order.status,
+            items: items.into_iter().map(Into::into).collect(),
+            subtotal: round_money(&order.subtotal).to_string(),
+            discount_total: round_money(&order.discount_total).to_string(),
+            total: round_money(&order.total).to_string(),
+            created_at: order.created_at,
+            updated_at: order.updated_at,
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use crate::domains::order::domain::{
+    model::{NewOrder, NewOrderItem, Order, OrderItem, OrderLine, OrderStatus, ProductSnapshot},
+    repository::OrderRepository,
+};
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use sqlx::{PgPool, Postgres, Transaction};
+
+pub struct OrderRepo;
+
+#[async_trait]
+impl OrderRepository for OrderRepo {
+    async fn find_by_user_id(&self, pool: PgPool, user_id: i32) -> Result<Vec<Order>, sqlx::Error> {
+        let orders = sqlx::query_as!(
+            Order,
+            r#"
+            SELECT id, user_id, status as "status: OrderStatus", subtotal, discount_total, total,
+                   created_at, updated_at
+            FROM orders
+            WHERE user_id = $1
+            ORDER BY created_at DESC, id DESC
+            "#,
+            user_id
+        )
+        .fetch_all(&pool)
+        .await?;
+        Ok(orders)
+    }
+
+    async fn find_by_id(&self, pool: PgPool, id: i32) -> Result<Option<Order>, sqlx::Error> {
+        let order = sqlx::query_as!(
+            Order,
+            r#"
+            SELECT id, user_id, status as "status: OrderStatus", subtotal, discount_total, total,
+                   created_at, updated_at
+            FROM orders
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&pool)
+        .await?;
+        Ok(order)
+    }
+
+    async fn find_items_by_order_ids(
+        &self,
+        pool: PgPool,
+        order_ids: &[i32],
+    ) -> Result<Vec<OrderItem>, sqlx::Error> {
+        let items = sqlx::query_as!(
+            OrderItem,
+            r#"
+            SELECT id, order_id, product_id, product_name, unit_price, discount, quantity, line_total
+            FROM order_items
+            WHERE order_id = ANY($1)
+            ORDER BY order_id, id
+            "#,
+            order_ids
+        )
+        .fetch_all(&pool)
+        .await?;
+        Ok(items)
+    }
+
+    async fn find_cart_lines(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<Vec<OrderLine>, sqlx::Error> {
+        let lines = sqlx::query_as!(
+            OrderLine,
+            r#"
+            SELECT product_id, quantity
+            FROM cart_items
+            WHERE user_id = $1
+            ORDER BY created_at, product_id
+            FOR UPDATE
+            "#,
+            user_id
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+        Ok(lines)
+    }
+
+    async fn clear_cart(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(r#"DELETE FROM cart_items WHERE user_id = $1"#, user_id)
+            .execute(&mut **tx)
+            .await?;
+        Ok(())
+    }
+
+    async fn find_products(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        product_ids: &[i32],
+    ) -> Result<Vec<ProductSnapshot>, sqlx::Error> {
+        let products = sqlx::query_as!(
+            ProductSnapshot,
+            r#"
+            SELECT id, name, price, discount, stock_quantity, reserved_quantity
+            FROM products
+            WHERE id = ANY($1)
+            ORDER BY id
+            FOR UPDATE
+            "#,
+            product_ids
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+        Ok(products)
+    }
+
+    async fn create(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        order: NewOrder,
+        items: Vec<NewOrderItem>,
+    ) -> Result<i32, sqlx::Error> {
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO orders (user_id, subtotal, discount_total, total)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
+            "#,
+            order.user_id,
+            order.subtotal,
+            order.discount_total,
+            order.total
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        let product_ids: Vec<i32> = items.iter().map(|i| i.product_id).collect();
+        let product_names: Vec<String> = items.iter().map(|i| i.product_name.clone()).collect();
+        let unit_prices: Vec<BigDecimal> = items.iter().map(|i| i.unit_price.clone()).collect();
+        let discounts: Vec<BigDecimal> = items.iter().map(|i| i.discount.clone()).collect();
+        let quantities: Vec<i32> = items.iter().map(|i| i.quantity).collect();
+        let line_totals: Vec<BigDecimal> = items.iter().map(|i| i.line_total.clone()).collect();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO order_items
+                (order_id, product_id, product_name, unit_price, discount, quantity, line_total)
+            SELECT $1, *
+            FROM UNNEST($2::int[], $3::varchar[], $4::numeric[], $5::numeric[], $6::int[], $7::numeric[])
+            "#,
+            inserted.id,
+            &product_ids,
+            &product_names,
+            &unit_prices,
+            &discounts,
+            &quantities,
+            &line_totals
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(inserted.id)
+    }
+
+    async fn reserve_stock(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        lines: &[OrderLine],
+    ) -> Result<(), sqlx::Error> {
+        let product_ids: Vec<i32> = lines.iter().map(|l| l.product_id).collect();
+        let quantities: Vec<i32> = lines.iter().map(|l| l.quantity).collect();
+
+        sqlx::query!(
+            r#"
+            UPDATE products p
+            SET reserved_quantity = p.reserved_quantity + r.quantity
+            FROM UNNEST($1::int[], $2::int[]) AS r(product_id, quantity)
+            WHERE p.id = r.product_id
+            "#,
+            &product_ids,
+            &quantities
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    async fn release_stock(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        order_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE products p
+            SET reserved_quantity = p.reserved_quantity - oi.quantity
+            FROM order_items oi
+            WHERE oi.order_id = $1 AND oi.product_id = p.id
+            "#,
+            order_id
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    async fn commit_stock(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        order_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE products p
+            SET stock_quantity = p.stock_quantity - oi.quantity,
+                reserved_quantity = p.reserved_quantity - oi.quantity
+            FROM order_items oi
+            WHERE oi.order_id = $1 AND oi.product_id = p.id
+            "#,
+            order_id
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    async fn find_by_id_for_update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+    ) -> Result<Option<Order>, sqlx::Error> {
+        let order = sqlx::query_as!(
+            Order,
+            r#"
+            SELECT id, user_id, status as "status: OrderStatus", subtotal, discount_total, total,
+                   created_at, updated_at
+            FROM orders
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            id
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+        Ok(order)
+    }
+
+    async fn update_status(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        status: OrderStatus,
+    ) -> Result<Order, sqlx::Error> {
+        let order = sqlx::query_as!(
+            Order,
+            r#"
+            UPDATE orders
+            SET status = $2,
+                updated_at = now()
+            WHERE id = $1
+            RETURNING id, user_id, status as "status: OrderStatus", subtotal, discount_total,
+                      total, created_at, updated_at
+            "#,
+            id,
+            status.as_str()
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+        Ok(order)
+    }
+}
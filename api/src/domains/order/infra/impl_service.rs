@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+
+use crate::{
+    common::{
+        authz::{self, AuthUser},
+        error::AppError,
+        price_util::{apply_discount, round_money},
+    },
+    domains::order::{
+        domain::{
+            model::{NewOrder, NewOrderItem, Order, OrderLine, OrderStatus},
+            repository::OrderRepository,
+            service::OrderServiceTrait,
+        },
+        dto::order_dto::{CreateOrderDto, OrderDto, UpdateOrderStatusDto},
+        infra::impl_repository::OrderRepo,
+    },
+};
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::sync::Arc;
+
+/// Service struct for placing orders and driving their lifecycle.
+/// It uses a repository pattern to abstract the data access layer.
+#[derive(Clone)]
+pub struct OrderService {
+    pub pool: PgPool,
+    pub repo: Arc<dyn OrderRepository + Send + Sync>,
+}
+
+impl OrderService {
+    /// Loads the line items of the given orders and assembles their DTOs.
+    async fn to_dtos(&self, orders: Vec<Order>) -> Result<Vec<OrderDto>, AppError> {
+        let order_ids: Vec<i32> = orders.iter().map(|o| o.id).collect();
+        let items = self
+            .repo
+            .find_items_by_order_ids(self.pool.clone(), &order_ids)
+            .await
+            .map_err(|err| {
+                tracing::error!("Error fetching order items: {err}");
+                AppError::DatabaseError(err)
+            })?;
+
+        let mut items_by_order: HashMap<i32, Vec<_>> = HashMap::new();
+        for item in items {
+            items_by_order.entry(item.order_id).or_default().push(item);
+        }
+
+        Ok(orders
+            .into_iter()
+            .map(|order| {
+                let items = items_by_order.remove(&order.id).unwrap_or_default();
+                OrderDto::from_parts(order, items)
+            })
+            .collect())
+    }
+
+    /// Snapshots the ordered products, reserves their stock and inserts the order
+    /// within the transaction.
+    async fn place_order(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        lines: Vec<OrderLine>,
+    ) -> Result<i32, AppError> {
+        let product_ids: Vec<i32> = lines.iter().map(|l| l.product_id).collect();
+        let products: HashMap<i32, _> = self
+            .repo
+            .find_products(tx, &product_ids)
+            .await?
+            .into_iter()
+            .map(|p| (p.id, p))
+            .collect();
+
+        let mut subtotal = BigDecimal::from(0);
+        let mut total = BigDecimal::from(0);
+        let mut items = Vec::with_capacity(lines.len());
+
+        for line in &lines {
+            let product = products.get(&line.product_id).ok_or_else(|| {
+                AppError::NotFound(format!("Product {} not found", line.product_id))
+            })?;
+
+            if product.stock_quantity - product.reserved_quantity < line.quantity {
+                return Err(AppError::InsufficientStock(format!(
+                    "only {} of {} left",
+                    product.stock_quantity - product.reserved_quantity,
+                    product.name
+                )));
+            }
+
+            let quantity = BigDecimal::from(line.quantity);
+            let unit_price = round_money(&product.price);
+            let line_total =
+                round_money(&(apply_discount(&product.price, &product.discount) * &quantity));
+
+            subtotal += &unit_price * &quantity;
+            total += &line_total;
+
+            items.push(NewOrderItem {
+                product_id: product.id,
+                product_name: product.name.clone(),
+                unit_price,
+                discount: product.discount.clone(),
+                quantity: line.quantity,
+                line_total,
+            });
+        }
+
+        let subtotal = round_money(&subtotal);
+        let total = round_money(&total);
+        let order = NewOrder {
+            user_id,
+            discount_total: &subtotal - &total,
+            subtotal,
+            total,
+        };
+
+        self.repo.reserve_stock(tx, &lines).await?;
+
+        Ok(self.repo.create(tx, order, items).await?)
+    }
+}
+
+/// Merges repeated products into a single line, keeping the order of first appearance.
+fn merge_lines(lines: Vec<OrderLine>) -> Vec<OrderLine> {
+    let mut merged: Vec<OrderLine> = Vec::with_capacity(lines.len());
+    for line in lines {
+        match merged.iter_mut().find(|l| l.product_id == line.product_id) {
+            Some(existing) => existing.quantity += line.quantity,
+            None => merged.push(line),
+        }
+    }
+    merged
+}
+
+#[async_trait]
+impl OrderServiceTrait for OrderService {
+    /// constructor for the service.
+    fn create_service(pool: PgPool) -> Arc<dyn OrderServiceTrait> {
+        Arc::new(Self {
+            pool,
+            repo: Arc::new(OrderRepo {}),
+        })
+    }
+
+    /// Places an order inside a single transaction.
+    /// Without explicit items the cart is used and then emptied in the same transaction.
+    async fn create_order(
+        &self,
+        user_id: i32,
+        payload: CreateOrderDto,
+    ) -> Result<OrderDto, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let explicit_lines = payload.items.filter(|items| !items.is_empty());
+        let from_cart = explicit_lines.is_none();
+
+        let lines = match explicit_lines {
+            Some(items) => items
+                .into_iter()
+                .map(|item| OrderLine {
+                    product_id: item.product_id,
+                    quantity: item.quantity,
+                })
+                .collect(),
+            None => self.repo.find_cart_lines(&mut tx, user_id).await?,
+        };
+        let lines = merge_lines(lines);
+
+        if lines.is_empty() {
+            tx.rollback().await?;
+            return Err(AppError::ValidationError("Cart is empty".into()));
+        }
+
+        let order_id = match self.place_order(&mut tx, user_id, lines).await {
+            Ok(order_id) => order_id,
+            Err(err) => {
+                tracing::error!("Error creating order: {err}");
+                tx.rollback().await?;
+                return Err(err);
+            }
+        };
+
+        if from_cart {
+            if let Err(err) = self.repo.clear_cart(&mut tx, user_id).await {
+                tracing::error!("Error clearing cart: {err}");
+                tx.rollback().await?;
+                return Err(AppError::DatabaseError(err));
+            }
+        }
+
+        tx.commit().await?;
+
+        self.get_order_by_id(user_id, order_id).await
+    }
+
+    async fn get_orders(&self, user_id: i32) -> Result<Vec<OrderDto>, AppError> {
+        match self.repo.find_by_user_id(self.pool.clone(), user_id).await {
+            Ok(orders) => self.to_dtos(orders).await,
+            Err(err) => {
+                tracing::error!("Error fetching orders: {err}");
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
+
+    async fn get_order_by_id(&self, user_id: i32, id: i32) -> Result<OrderDto, AppError> {
+        match self.repo.find_by_id(self.pool.clone(), id).await {
+            Ok(Some(order)) if order.user_id == user_id => {
+                let mut dtos = self.to_dtos(vec![order]).await?;
+                dtos.pop().ok_or(AppError::InternalError)
+            }
+            Ok(_) => Err(AppError::NotFound("Order not found".into())),
+            Err(err) => {
+                tracing::error!("Error retrieving order: {err}");
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
+
+    /// Applies a status change after checking it against the order state machine,
+    /// committing or releasing the order's stock reservation as needed.
+    /// The order row is locked so concurrent transitions cannot skip a check.
+    async fn update_order_status(
+        &self,
+        auth_user: AuthUser,
+        id: i32,
+        payload: UpdateOrderStatusDto,
+    ) -> Result<OrderDto, AppError> {
+        let is_staff = auth_user.has_any_role(authz::STAFF);
+        if !is_staff && payload.status != OrderStatus::Cancelled {
+            return Err(AppError::Forbidden);
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        let order = match self.repo.find_by_id_for_update(&mut tx, id).await {
+            Ok(Some(order)) if is_staff || order.user_id == auth_user.user_id => order,
+            Ok(_) => {
+                tx.rollback().await?;
+                return Err(AppError::NotFound("Order not found".into()));
+            }
+            Err(err) => {
+                tracing::error!("Error retrieving order: {err}");
+                tx.rollback().await?;
+                return Err(AppError::DatabaseError(err));
+            }
+        };
+
+        if !order.status.can_transition_to(payload.status) {
+            tx.rollback().await?;
+            return Err(AppError::InvalidStatusTransition {
+                from: order.status.to_string(),
+                to: payload.status.to_string(),
+            });
+        }
+
+        if order.status.holds_reservation() && !payload.status.holds_reservation() {
+            let stock_result = if payload.status == OrderStatus::OutForDelivery {
+                self.repo.commit_stock(&mut tx, id).await
+            } else {
+                self.repo.release_stock(&mut tx, id).await
+            };
+
+            if let Err(err) = stock_result {
+                tracing::error!("Error updating reserved stock: {err}");
+                tx.rollback().await?;
+                return Err(AppError::DatabaseError(err));
+            }
+        }
+
+        let order = match self.repo.update_status(&mut tx, id, payload.status).await {
+            Ok(order) => order,
+            Err(err) => {
+                tracing::error!("Error updating order status: {err}");
+                tx.rollback().await?;
+                return Err(AppError::DatabaseError(err));
+            }
+        };
+
+        tx.commit().await?;
+
+        let mut dtos = self.to_dtos(vec![order]).await?;
+        dtos.pop().ok_or(AppError::InternalError)
+    }
+}
@@ -1,15 +1,25 @@
 use crate::{
-    common::{app_state::AppState, dto::RestApiResponse, error::AppError},
+    common::{
+        app_state::AppState,
+        dto::{Page, PageQuery, RestApiResponse},
+        error::{AppError, FieldError},
+        multipart_helper::{parse_multipart, UploadOptions},
+        query_params::ValidatedQuery,
+    },
     domains::product::dto::product_dto::{
-        BestSellerQuery, FilterQuery, PriceRangeQuery, ProductDto,
+        BestSellerQuery, BulkPatchProductDto, CategoryProductsQuery, CreateProductDto, FilterQuery,
+        PatchProductDto, PriceRangeQuery, ProductDto, ProductFilterResultDto, ProductImageDto,
+        ProductSearchResultDto, ProductStockDto, ReorderProductImagesDto, RestockDto, SearchQuery,
+        UpdateProductDto, UpdateStockDto, UploadProductImagesForm,
     },
 };
 
 use axum::{
-    extract::{Path, Query, State},
+    extract::{Multipart, Path, Query, State},
     response::IntoResponse,
+    Json,
 };
-use bigdecimal::BigDecimal;
+use validator::Validate;
 
 #[utoipa::path(
     get,
@@ -29,28 +39,36 @@ pub async fn get_product_by_id(
 #[utoipa::path(
     get,
     path = "/product",
-    responses((status = 200, description = "List all product", body = [ProductDto])),
+    params(PageQuery),
+    responses((status = 200, description = "List products, sortable by id, name, price or discount", body = Page<ProductDto>)),
     tag = "Products"
 )]
-pub async fn get_products(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
-    let products = state.product_service.get_products().await?;
+pub async fn get_products(
+    State(state): State<AppState>,
+    Query(page): Query<PageQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let products = state.product_service.get_products(page).await?;
     Ok(RestApiResponse::success(products))
 }
 
 #[utoipa::path(
     get,
     path = "/product/category/{category_id}",
+    params(
+        ("include_descendants" = Option<bool>, Query, description = "Also return products of descendant categories")
+    ),
     responses((status = 200, description = "Get products by category ID", body = [ProductDto])),
     tag = "Products"
 )]
 pub async fn get_products_by_category_id(
     State(state): State<AppState>,
     Path(category_id): Path<String>,
+    Query(query): Query<CategoryProductsQuery>,
 ) -> Result<impl IntoResponse, AppError> {
     let category_id: i32 = category_id.parse().map_err(|_| AppError::InternalError)?;
     let products = state
         .product_service
-        .get_products_by_category_id(category_id)
+        .get_products_by_category_id(category_id, query.include_descendants.unwrap_or(false))
         .await?;
     Ok(RestApiResponse::success(products))
 }
@@ -94,28 +112,22 @@ pub async fn get_deals_of_the_day(
     get,
     path = "/product/price-range",
     params(
-        ("min_price" = String, Query, description = "Minimum price"),
-        ("max_price" = String, Query, description = "Maximum price")
+        ("min_price" = f64, Query, description = "Minimum price"),
+        ("max_price" = f64, Query, description = "Maximum price, at least min_price")
+    ),
+    responses(
+        (status = 200, description = "Get products by price range", body = [ProductDto]),
+        (status = 400, description = "Invalid parameters, listed in `data`", body = [FieldError])
     ),
-    responses((status = 200, description = "Get products by price range", body = [ProductDto])),
     tag = "Products"
 )]
 pub async fn get_products_by_price_range(
     State(state): State<AppState>,
-    Query(query): Query<PriceRangeQuery>,
+    ValidatedQuery(query): ValidatedQuery<PriceRangeQuery>,
 ) -> Result<impl IntoResponse, AppError> {
-    let min_price: BigDecimal = query
-        .min_price
-        .parse()
-        .map_err(|_| AppError::InternalError)?;
-    let max_price: BigDecimal = query
-        .max_price
-        .parse()
-        .map_err(|_| AppError::InternalError)?;
-
     let products = state
         .product_service
-        .get_products_by_price_range(min_price, max_price)
+        .get_products_by_price_range(query.min_price, query.max_price)
         .await?;
 
     Ok(RestApiResponse::success(products))
@@ -125,29 +137,364 @@ pub async fn get_products_by_price_range(
     get,
     path = "/product/filter",
     params(
-        ("category" = Option<String>, Query, description = "Filter by category"),
+        ("category_id" = Option<Vec<i32>>, Query, description = "Filter by category id; repeat or separate with commas for several"),
+        ("category" = Option<Vec<String>>, Query, description = "Filter by category name; repeat for several"),
+        ("category_match" = Option<String>, Query, description = "How category names match: `fuzzy` (default, any part of the name) or `exact`"),
         ("is_best_seller" = Option<bool>, Query, description = "Filter by best seller status"),
         ("is_deal_of_the_day" = Option<bool>, Query, description = "Filter by deal of the day status"),
-        ("min_price" = Option<String>, Query, description = "Minimum price"),
-        ("max_price" = Option<String>, Query, description = "Maximum price")
+        ("min_price" = Option<f64>, Query, description = "Minimum price"),
+        ("max_price" = Option<f64>, Query, description = "Maximum price, at least min_price"),
+        ("in_stock_only" = Option<bool>, Query, description = "Only return products that can currently be ordered"),
+        PageQuery
+    ),
+    responses(
+        (status = 200, description = "Get products by filter, sortable by id, name, price or discount, with facet counts of all matches", body = ProductFilterResultDto),
+        (status = 400, description = "Invalid parameters, listed in `data`", body = [FieldError])
     ),
-    responses((status = 200, description = "Get products by filter", body = [ProductDto])),
     tag = "Products"
 )]
 pub async fn get_products_by_filter(
     State(state): State<AppState>,
-    Query(query): Query<FilterQuery>,
+    ValidatedQuery((query, page)): ValidatedQuery<(FilterQuery, PageQuery)>,
 ) -> Result<impl IntoResponse, AppError> {
     let products = state
         .product_service
-        .get_products_by_filter(
-            query.category,
-            query.is_best_seller,
-            query.is_deal_of_the_day,
-            query.min_price,
-            query.max_price,
-        )
+        .get_products_by_filter(query, page)
         .await?;
 
     Ok(RestApiResponse::success(products))
 }
+
+#[utoipa::path(
+    get,
+    path = "/product/search",
+    params(
+        ("q" = String, Query, description = "Search text; each word also matches as a prefix"),
+        ("limit" = Option<i64>, Query, description = "Maximum number of results, 1 to 50 (default 10)"),
+        ("offset" = Option<i64>, Query, description = "Number of results to skip")
+    ),
+    responses((status = 200, description = "Search products by name and description, best matches first", body = [ProductSearchResultDto])),
+    tag = "Products"
+)]
+pub async fn search_products(
+    State(state): State<AppState>,
+    Query(query): Query<SearchQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    query.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    let results = state.product_service.search_products(query).await?;
+    Ok(RestApiResponse::success(results))
+}
+
+#[utoipa::path(
+    get,
+    path = "/product/low-stock",
+    responses((status = 200, description = "List products at or below their low-stock threshold", body = [ProductStockDto])),
+    tag = "Products"
+)]
+pub async fn get_low_stock_products(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let products = state.product_service.get_low_stock_products().await?;
+    Ok(RestApiResponse::success(products))
+}
+
+#[utoipa::path(
+    post,
+    path = "/product/{id}/restock",
+    request_body = RestockDto,
+    responses((status = 200, description = "Add stock to a product", body = ProductStockDto)),
+    tag = "Products"
+)]
+pub async fn restock_product(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<RestockDto>,
+) -> Result<impl IntoResponse, AppError> {
+    let id: i32 = id
+        .parse()
+        .map_err(|_| AppError::ValidationError("Invalid product id".into()))?;
+
+    payload.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    let product = state.product_service.restock_product(id, payload).await?;
+    Ok(RestApiResponse::success(product))
+}
+
+#[utoipa::path(
+    put,
+    path = "/product/{id}/stock",
+    request_body = UpdateStockDto,
+    responses((status = 200, description = "Set the stock of a product", body = ProductStockDto)),
+    tag = "Products"
+)]
+pub async fn update_product_stock(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateStockDto>,
+) -> Result<impl IntoResponse, AppError> {
+    let id: i32 = id
+        .parse()
+        .map_err(|_| AppError::ValidationError("Invalid product id".into()))?;
+
+    payload.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    let product = state
+        .product_service
+        .update_product_stock(id, payload)
+        .await?;
+    Ok(RestApiResponse::success(product))
+}
+
+#[utoipa::path(
+    post,
+    path = "/product",
+    request_body = CreateProductDto,
+    responses(
+        (status = 200, description = "Create a product", body = ProductDto),
+        (status = 409, description = "A product with this name already exists")
+    ),
+    tag = "Products"
+)]
+pub async fn create_product(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateProductDto>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    let product = state.product_service.create_product(payload).await?;
+    Ok(RestApiResponse::success(product))
+}
+
+#[utoipa::path(
+    put,
+    path = "/product/{id}",
+    request_body = UpdateProductDto,
+    responses(
+        (status = 200, description = "Replace a product", body = ProductDto),
+        (status = 409, description = "A product with this name already exists")
+    ),
+    tag = "Products"
+)]
+pub async fn update_product(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateProductDto>,
+) -> Result<impl IntoResponse, AppError> {
+    let id: i32 = id
+        .parse()
+        .map_err(|_| AppError::ValidationError("Invalid product id".into()))?;
+
+    payload.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    let product = state.product_service.update_product(id, payload).await?;
+    Ok(RestApiResponse::success(product))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/product/{id}",
+    request_body = PatchProductDto,
+    responses(
+        (status = 200, description = "Update some fields of a product", body = ProductDto),
+        (status = 409, description = "A product with this name already exists")
+    ),
+    tag = "Products"
+)]
+pub async fn patch_product(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<PatchProductDto>,
+) -> Result<impl IntoResponse, AppError> {
+    let id: i32 = id
+        .parse()
+        .map_err(|_| AppError::ValidationError("Invalid product id".into()))?;
+
+    payload.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    let product = state.product_service.patch_product(id, payload).await?;
+    Ok(RestApiResponse::success(product))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/product/bulk",
+    request_body = BulkPatchProductDto,
+    responses(
+        (status = 200, description = "Update several products at once", body = [ProductDto]),
+        (status = 409, description = "A product with this name already exists")
+    ),
+    tag = "Products"
+)]
+pub async fn bulk_patch_products(
+    State(state): State<AppState>,
+    Json(payload): Json<BulkPatchProductDto>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    let products = state.product_service.bulk_patch_products(payload).await?;
+    Ok(RestApiResponse::success(products))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/product/{id}",
+    responses(
+        (status = 200, description = "Product deleted"),
+        (status = 409, description = "Product has stock reserved by open orders")
+    ),
+    tag = "Products"
+)]
+pub async fn delete_product(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let id: i32 = id
+        .parse()
+        .map_err(|_| AppError::ValidationError("Invalid product id".into()))?;
+
+    let message = state.product_service.delete_product(id).await?;
+    Ok(RestApiResponse::success_with_message(message, ()))
+}
+
+fn parse_image_path(id: &str, image_id: &str) -> Result<(i32, i32), AppError> {
+    let id = id
+        .parse()
+        .map_err(|_| AppError::ValidationError("Invalid product id".into()))?;
+    let image_id = image_id
+        .parse()
+        .map_err(|_| AppError::ValidationError("Invalid image id".into()))?;
+    Ok((id, image_id))
+}
+
+#[utoipa::path(
+    get,
+    path = "/product/{id}/images",
+    responses((status = 200, description = "List the images of a product in gallery order", body = [ProductImageDto])),
+    tag = "Products"
+)]
+pub async fn get_product_images(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let id: i32 = id
+        .parse()
+        .map_err(|_| AppError::ValidationError("Invalid product id".into()))?;
+
+    let images = state.product_service.get_product_images(id).await?;
+    Ok(RestApiResponse::success(images))
+}
+
+#[utoipa::path(
+    post,
+    path = "/product/{id}/images",
+    request_body(content = UploadProductImagesForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Add images to the end of a product's gallery; returns the whole gallery", body = [ProductImageDto]),
+        (status = 400, description = "Not an image, too large, or the gallery is full")
+    ),
+    tag = "Products"
+)]
+pub async fn upload_product_images(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    multipart: Multipart,
+) -> Result<impl IntoResponse, AppError> {
+    let id: i32 = id
+        .parse()
+        .map_err(|_| AppError::ValidationError("Invalid product id".into()))?;
+
+    let form = parse_multipart(multipart, &UploadOptions::public(&state)).await?;
+    let images = state
+        .product_service
+        .add_product_images(id, form.files)
+        .await?;
+    Ok(RestApiResponse::success(images))
+}
+
+#[utoipa::path(
+    put,
+    path = "/product/{id}/images/order",
+    request_body = ReorderProductImagesDto,
+    responses((status = 200, description = "Rearrange a product's gallery", body = [ProductImageDto])),
+    tag = "Products"
+)]
+pub async fn reorder_product_images(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<ReorderProductImagesDto>,
+) -> Result<impl IntoResponse, AppError> {
+    let id: i32 = id
+        .parse()
+        .map_err(|_| AppError::ValidationError("Invalid product id".into()))?;
+
+    payload.validate().map_err(|err| {
+        tracing::error!("Validation error: {err}");
+        AppError::ValidationError(format!("Invalid input: {}", err))
+    })?;
+
+    let images = state
+        .product_service
+        .reorder_product_images(id, payload)
+        .await?;
+    Ok(RestApiResponse::success(images))
+}
+
+#[utoipa::path(
+    put,
+    path = "/product/{id}/images/{image_id}/primary",
+    responses((status = 200, description = "Make an image the one shown in product lists", body = [ProductImageDto])),
+    tag = "Products"
+)]
+pub async fn set_primary_product_image(
+    State(state): State<AppState>,
+    Path((id, image_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    let (id, image_id) = parse_image_path(&id, &image_id)?;
+
+    let images = state
+        .product_service
+        .set_primary_product_image(id, image_id)
+        .await?;
+    Ok(RestApiResponse::success(images))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/product/{id}/images/{image_id}",
+    responses((status = 200, description = "Image deleted")),
+    tag = "Products"
+)]
+pub async fn delete_product_image(
+    State(state): State<AppState>,
+    Path((id, image_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    let (id, image_id) = parse_image_path(&id, &image_id)?;
+
+    let message = state
+        .product_service
+        .delete_product_image(id, image_id)
+        .await?;
+    Ok(RestApiResponse::success_with_message(message, ()))
+}
@@ -1,7 +1,19 @@
 use super::handlers::*;
-use crate::{common::app_state::AppState, domains::product::dto::product_dto::ProductDto};
+use crate::{
+    common::{app_state::AppState, authz, error::FieldError},
+    domains::product::dto::product_dto::{
+        BulkPatchItemDto, BulkPatchProductDto, CategoryFacetDto, CreateProductDto, PatchProductDto,
+        ProductDto, ProductFacetsDto, ProductFilterResultDto, ProductImageDto,
+        ProductSearchResultDto, ProductStockDto, RangeFacetDto, ReorderProductImagesDto,
+        RestockDto, UpdateProductDto, UpdateStockDto, UploadProductImagesForm,
+    },
+};
 
-use axum::{routing::get, Router};
+use axum::{
+    middleware,
+    routing::{delete, get, patch, post, put},
+    Router,
+};
 
 use utoipa::{
     openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
@@ -17,9 +29,42 @@ use utoipa::{
         get_best_sellers,
         get_deals_of_the_day,
         get_products_by_price_range,
-        get_products_by_filter
+        get_products_by_filter,
+        search_products,
+        get_low_stock_products,
+        restock_product,
+        update_product_stock,
+        create_product,
+        update_product,
+        patch_product,
+        bulk_patch_products,
+        delete_product,
+        get_product_images,
+        upload_product_images,
+        reorder_product_images,
+        set_primary_product_image,
+        delete_product_image
     ),
-    components(schemas(ProductDto)),
+    components(schemas(
+        ProductDto,
+        ProductImageDto,
+        UploadProductImagesForm,
+        ReorderProductImagesDto,
+        ProductSearchResultDto,
+        ProductFilterResultDto,
+        ProductFacetsDto,
+        CategoryFacetDto,
+        RangeFacetDto,
+        FieldError,
+        ProductStockDto,
+        RestockDto,
+        UpdateStockDto,
+        CreateProductDto,
+        UpdateProductDto,
+        PatchProductDto,
+        BulkPatchItemDto,
+        BulkPatchProductDto
+    )),
     tags(
         (name = "Products", description = "Product management endpoints")
     ),
@@ -48,12 +93,48 @@ impl utoipa::Modify for ProductApiDoc {
 }
 
 pub fn product_routes() -> Router<AppState> {
+    // Catalogue management, restricted to admins.
+    let admin_routes = Router::new()
+        .route("/", post(create_product))
+        .route("/bulk", patch(bulk_patch_products))
+        .route(
+            "/{id}",
+            put(update_product)
+                .patch(patch_product)
+                .delete(delete_product),
+        )
+        .route("/{id}/images", post(upload_product_images))
+        .route("/{id}/images/order", put(reorder_product_images))
+        .route(
+            "/{id}/images/{image_id}/primary",
+            put(set_primary_product_image),
+        )
+        .route("/{id}/images/{image_id}", delete(delete_product_image))
+        .route_layer(middleware::from_fn_with_state(
+            authz::ADMIN,
+            authz::require_roles,
+        ));
+
+    // Inventory management, open to staff.
+    let staff_routes = Router::new()
+        .route("/low-stock", get(get_low_stock_products))
+        .route("/{id}/restock", post(restock_product))
+        .route("/{id}/stock", put(update_product_stock))
+        .route_layer(middleware::from_fn_with_state(
+            authz::STAFF,
+            authz::require_roles,
+        ));
+
     Router::new()
         .route("/", get(get_products))
         .route("/{id}", get(get_product_by_id))
+        .route("/{id}/images", get(get_product_images))
         .route("/category/{category_id}", get(get_products_by_category_id))
         .route("/best-sellers", get(get_best_sellers))
         .route("/deal-of-the-day", get(get_deals_of_the_day))
         .route("/price-range", get(get_products_by_price_range))
         .route("/filter", get(get_products_by_filter))
+        .route("/search", get(search_products))
+        .merge(admin_routes)
+        .merge(staff_routes)
 }
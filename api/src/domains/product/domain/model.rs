@@ -1,6 +1,8 @@
 use bigdecimal::BigDecimal;
 use sqlx::prelude::FromRow;
 
+use crate::common::pagination::Keyset;
+
 #[derive(Debug, Clone, FromRow)]
 pub struct Product {
     pub id: i32,
@@ -11,6 +13,9 @@ pub struct Product {
     pub is_deal_of_the_day: bool,
     pub discount: BigDecimal,
     pub category_id: i32,
+    pub stock_quantity: i32,
+    pub reserved_quantity: i32,
+    pub low_stock_threshold: i32,
 }
 
 #[derive(Debug, Clone, FromRow)]
@@ -23,5 +28,158 @@ pub struct ProductWithCategory {
     pub is_deal_of_the_day: bool,
     pub discount: BigDecimal,
     pub category_id: i32,
+    pub stock_quantity: i32,
+    pub reserved_quantity: i32,
+    pub low_stock_threshold: i32,
+    pub category_name: String,
+}
+
+/// A product matching a search, with its relevance and the matches highlighted.
+#[derive(Debug, Clone, FromRow)]
+pub struct ProductSearchHit {
+    pub id: i32,
+    pub name: String,
+    pub description: String,
+    pub price: BigDecimal,
+    pub is_best_seller: bool,
+    pub is_deal_of_the_day: bool,
+    pub discount: BigDecimal,
+    pub category_id: i32,
+    pub stock_quantity: i32,
+    pub reserved_quantity: i32,
+    pub low_stock_threshold: i32,
     pub category_name: String,
+    pub rank: f32,
+    /// Raw product text with the matches between [`HIGHLIGHT_START`] and [`HIGHLIGHT_STOP`].
+    pub highlighted_name: String,
+    pub snippet: String,
+}
+
+/// Private-use characters `ts_headline` puts around matches, so the product text can be
+/// HTML-escaped before they are replaced with `<mark>` tags.
+pub const HIGHLIGHT_START: char = '\u{E000}';
+pub const HIGHLIGHT_STOP: char = '\u{E001}';
+
+/// A count of the products matching a filter. Exactly one of the facet columns is set,
+/// or none of them for the row that counts all matches.
+#[derive(Debug, Clone, FromRow)]
+pub struct ProductFacetCount {
+    pub category_id: Option<i32>,
+    pub category_name: Option<String>,
+    /// Index into the price facet bounds, as computed by `width_bucket`.
+    pub price_bucket: Option<i32>,
+    /// Index into the discount facet bounds, as computed by `width_bucket`.
+    pub discount_bucket: Option<i32>,
+    pub is_best_seller: Option<bool>,
+    pub is_deal_of_the_day: Option<bool>,
+    pub count: i64,
+}
+
+impl ProductFacetCount {
+    /// Whether this is the row counting all matches.
+    pub fn is_total(&self) -> bool {
+        self.category_id.is_none()
+            && self.price_bucket.is_none()
+            && self.discount_bucket.is_none()
+            && self.is_best_seller.is_none()
+            && self.is_deal_of_the_day.is_none()
+    }
+}
+
+/// An image in a product's gallery. Keys are paths relative to the public asset directory.
+#[derive(Debug, Clone, FromRow)]
+pub struct ProductImage {
+    pub id: i32,
+    pub product_id: i32,
+    pub position: i32,
+    pub is_primary: bool,
+    pub original_key: String,
+    pub thumbnail_key: String,
+    pub card_key: String,
+    pub detail_key: String,
+    pub webp_key: String,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// An uploaded image and its variants, before it is added to a gallery.
+#[derive(Debug, Clone)]
+pub struct NewProductImage {
+    pub original_key: String,
+    pub thumbnail_key: String,
+    pub card_key: String,
+    pub detail_key: String,
+    pub webp_key: String,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl NewProductImage {
+    /// Keys of the original and all its variants.
+    pub fn keys(&self) -> [&str; 5] {
+        [
+            &self.original_key,
+            &self.thumbnail_key,
+            &self.card_key,
+            &self.detail_key,
+            &self.webp_key,
+        ]
+    }
+}
+
+impl ProductImage {
+    /// Keys of the original and all its variants.
+    pub fn keys(&self) -> [&str; 5] {
+        [
+            &self.original_key,
+            &self.thumbnail_key,
+            &self.card_key,
+            &self.detail_key,
+            &self.webp_key,
+        ]
+    }
+}
+
+impl Product {
+    /// Quantity that can still be ordered, i.e. stock not held by open orders.
+    pub fn available_quantity(&self) -> i32 {
+        self.stock_quantity - self.reserved_quantity
+    }
+}
+
+impl ProductWithCategory {
+    /// Quantity that can still be ordered, i.e. stock not held by open orders.
+    pub fn available_quantity(&self) -> i32 {
+        self.stock_quantity - self.reserved_quantity
+    }
+}
+
+impl Keyset for Product {
+    fn keyset_id(&self) -> i32 {
+        self.id
+    }
+
+    fn keyset_value(&self, sort: &str) -> String {
+        match sort {
+            "name" => self.name.clone(),
+            "price" => self.price.to_string(),
+            "discount" => self.discount.to_string(),
+            _ => self.id.to_string(),
+        }
+    }
+}
+
+impl Keyset for ProductWithCategory {
+    fn keyset_id(&self) -> i32 {
+        self.id
+    }
+
+    fn keyset_value(&self, sort: &str) -> String {
+        match sort {
+            "name" => self.name.clone(),
+            "price" => self.price.to_string(),
+            "discount" => self.discount.to_string(),
+            _ => self.id.to_string(),
+        }
+    }
 }
@@ -1,21 +1,36 @@
-use crate::domains::product::domain::model::ProductWithCategory;
+use crate::{
+    common::pagination::PageRequest,
+    domains::product::{
+        domain::model::{
+            NewProductImage, ProductFacetCount, ProductImage, ProductSearchHit, ProductWithCategory,
+        },
+        dto::product_dto::{CreateProductDto, FilterQuery, PatchProductDto},
+    },
+};
 
 use super::model::Product;
 
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 
 #[async_trait]
 pub trait ProductRepository: Send + Sync {
-    async fn find_all(&self, pool: PgPool) -> Result<Vec<Product>, sqlx::Error>;
+    /// Returns a page of products together with the total number of products.
+    async fn find_all(
+        &self,
+        pool: PgPool,
+        page: &PageRequest,
+    ) -> Result<(Vec<Product>, i64), sqlx::Error>;
 
     async fn find_by_id(&self, pool: PgPool, id: i32) -> Result<Option<Product>, sqlx::Error>;
 
+    /// Finds the products of a category, optionally including those of its descendant categories.
     async fn find_by_category_id(
         &self,
         pool: PgPool,
         category_id: i32,
+        include_descendants: bool,
     ) -> Result<Vec<ProductWithCategory>, sqlx::Error>;
 
     async fn find_best_sellers(
@@ -37,14 +52,109 @@ pub trait ProductRepository: Send + Sync {
         max_price: BigDecimal,
     ) -> Result<Vec<Product>, sqlx::Error>;
 
+    /// Returns a page of the matching products together with the facet counts of all matches.
     async fn find_by_filter(
         &self,
         pool: PgPool,
-        category: Option<String>,
-        is_best_seller: Option<bool>,
-        is_deal_of_the_day: Option<bool>,
-        min_price: Option<String>,
-        max_price: Option<String>,
-    ) -> Result<Vec<ProductWithCategory>, sqlx::Error>;
+        filter: FilterQuery,
+        page: &PageRequest,
+    ) -> Result<(Vec<ProductWithCategory>, Vec<ProductFacetCount>), sqlx::Error>;
+
+    /// Full-text search over names and descriptions, ordered by relevance.
+    /// Every word of the query matches as a prefix; names also match with small typos.
+    async fn search(
+        &self,
+        pool: PgPool,
+        query: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ProductSearchHit>, sqlx::Error>;
+
+    async fn find_low_stock(&self, pool: PgPool) -> Result<Vec<Product>, sqlx::Error>;
+
+    async fn find_by_id_for_update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+    ) -> Result<Option<Product>, sqlx::Error>;
+
+    async fn restock(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        quantity: i32,
+    ) -> Result<Option<Product>, sqlx::Error>;
+
+    async fn update_stock(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        stock_quantity: i32,
+        low_stock_threshold: i32,
+    ) -> Result<Product, sqlx::Error>;
+
+    async fn create(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        product: CreateProductDto,
+    ) -> Result<Product, sqlx::Error>;
 
+    /// Applies the given changes, leaving fields that are `None` untouched.
+    /// Returns `Ok(None)` if the product does not exist.
+    async fn update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        changes: PatchProductDto,
+    ) -> Result<Option<Product>, sqlx::Error>;
+
+    async fn delete(&self, tx: &mut Transaction<'_, Postgres>, id: i32) -> Result<(), sqlx::Error>;
+
+    /// Lists the images of the given products in gallery order.
+    async fn find_images(
+        &self,
+        pool: PgPool,
+        product_ids: &[i32],
+    ) -> Result<Vec<ProductImage>, sqlx::Error>;
+
+    /// Lists the images of a product in gallery order.
+    async fn find_images_of_product(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        product_id: i32,
+    ) -> Result<Vec<ProductImage>, sqlx::Error>;
+
+    /// Appends images to the end of a product's gallery. The first image of a gallery
+    /// becomes its primary image.
+    async fn add_images(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        product_id: i32,
+        images: Vec<NewProductImage>,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Sets the position of each image to its index in `image_ids`.
+    async fn reorder_images(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        product_id: i32,
+        image_ids: &[i32],
+    ) -> Result<(), sqlx::Error>;
+
+    /// Makes the image the only primary image of its product.
+    async fn set_primary_image(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        product_id: i32,
+        image_id: i32,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Deletes an image, making the first remaining one primary if it was the primary image.
+    /// Returns `Ok(None)` if the product has no such image.
+    async fn delete_image(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        product_id: i32,
+        image_id: i32,
+    ) -> Result<Option<ProductImage>, sqlx::Error>;
 }
@@ -1,4 +1,17 @@
-use crate::{common::error::AppError, domains::product::dto::product_dto::ProductDto};
+use crate::{
+    common::{
+        config::Config,
+        dto::{Page, PageQuery},
+        error::AppError,
+        multipart_helper::UploadedFile,
+        storage::Storage,
+    },
+    domains::product::dto::product_dto::{
+        BulkPatchProductDto, CreateProductDto, FilterQuery, PatchProductDto, ProductDto,
+        ProductFilterResultDto, ProductImageDto, ProductSearchResultDto, ProductStockDto,
+        ReorderProductImagesDto, RestockDto, SearchQuery, UpdateProductDto, UpdateStockDto,
+    },
+};
 
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
@@ -7,17 +20,22 @@ use std::sync::Arc;
 
 #[async_trait]
 pub trait ProductServiceTrait: Send + Sync {
-    fn create_service(pool: PgPool) -> Arc<dyn ProductServiceTrait>
+    fn create_service(
+        pool: PgPool,
+        config: Config,
+        storage: Arc<dyn Storage>,
+    ) -> Arc<dyn ProductServiceTrait>
     where
         Self: Sized;
 
     async fn get_product_by_id(&self, id: i32) -> Result<ProductDto, AppError>;
 
-    async fn get_products(&self) -> Result<Vec<ProductDto>, AppError>;
+    async fn get_products(&self, page: PageQuery) -> Result<Page<ProductDto>, AppError>;
 
     async fn get_products_by_category_id(
         &self,
         category_id: i32,
+        include_descendants: bool,
     ) -> Result<Vec<ProductDto>, AppError>;
 
     async fn get_best_sellers(&self, limit: i64) -> Result<Vec<ProductDto>, AppError>;
@@ -32,10 +50,84 @@ pub trait ProductServiceTrait: Send + Sync {
 
     async fn get_products_by_filter(
         &self,
-        category: Option<String>,
-        is_best_seller: Option<bool>,
-        is_deal_of_the_day: Option<bool>,
-        min_price: Option<String>,
-        max_price: Option<String>,
+        filter: FilterQuery,
+        page: PageQuery,
+    ) -> Result<ProductFilterResultDto, AppError>;
+
+    /// Searches product names and descriptions, best matches first.
+    async fn search_products(
+        &self,
+        query: SearchQuery,
+    ) -> Result<Vec<ProductSearchResultDto>, AppError>;
+
+    /// Lists products whose available quantity is at or below their low-stock threshold.
+    async fn get_low_stock_products(&self) -> Result<Vec<ProductStockDto>, AppError>;
+
+    /// Adds the given quantity to a product's stock.
+    async fn restock_product(
+        &self,
+        id: i32,
+        payload: RestockDto,
+    ) -> Result<ProductStockDto, AppError>;
+
+    /// Sets a product's stock quantity and optionally its low-stock threshold.
+    async fn update_product_stock(
+        &self,
+        id: i32,
+        payload: UpdateStockDto,
+    ) -> Result<ProductStockDto, AppError>;
+
+    /// Creates a new product.
+    async fn create_product(&self, payload: CreateProductDto) -> Result<ProductDto, AppError>;
+
+    /// Replaces the catalogue data of a product.
+    async fn update_product(
+        &self,
+        id: i32,
+        payload: UpdateProductDto,
+    ) -> Result<ProductDto, AppError>;
+
+    /// Updates only the fields present in the payload.
+    async fn patch_product(
+        &self,
+        id: i32,
+        payload: PatchProductDto,
+    ) -> Result<ProductDto, AppError>;
+
+    /// Patches several products at once; either all changes are applied or none.
+    async fn bulk_patch_products(
+        &self,
+        payload: BulkPatchProductDto,
     ) -> Result<Vec<ProductDto>, AppError>;
+
+    /// Deletes a product that has no stock reserved by open orders.
+    async fn delete_product(&self, id: i32) -> Result<String, AppError>;
+
+    /// Lists the images of a product in gallery order.
+    async fn get_product_images(&self, id: i32) -> Result<Vec<ProductImageDto>, AppError>;
+
+    /// Generates the variants of uploaded images and appends them to a product's gallery.
+    /// The uploaded files are removed again if this fails.
+    async fn add_product_images(
+        &self,
+        id: i32,
+        files: Vec<UploadedFile>,
+    ) -> Result<Vec<ProductImageDto>, AppError>;
+
+    /// Rearranges a product's gallery.
+    async fn reorder_product_images(
+        &self,
+        id: i32,
+        payload: ReorderProductImagesDto,
+    ) -> Result<Vec<ProductImageDto>, AppError>;
+
+    /// Makes an image the one shown in product lists.
+    async fn set_primary_product_image(
+        &self,
+        id: i32,
+        image_id: i32,
+    ) -> Result<Vec<ProductImageDto>, AppError>;
+
+    /// Deletes an image and its files.
+    async fn delete_product_image(&self, id: i32, image_id: i32) -> Result<String, AppError>;
 }
@@ -1,7 +1,20 @@
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
 
-use crate::domains::product::domain::model::{Product, ProductWithCategory};
+use crate::{
+    common::{
+        dto::Page,
+        query_params::{FromQueryParams, QueryParams},
+    },
+    domains::product::domain::model::{
+        Product, ProductFacetCount, ProductImage, ProductSearchHit, ProductWithCategory,
+        HIGHLIGHT_START, HIGHLIGHT_STOP,
+    },
+};
 
 #[derive(Deserialize, ToSchema)]
 pub struct BestSellerQuery {
@@ -10,25 +23,142 @@ pub struct BestSellerQuery {
 }
 
 #[derive(Deserialize, ToSchema)]
+pub struct CategoryProductsQuery {
+    #[schema(example = "true")]
+    pub include_descendants: Option<bool>,
+}
+
+/// Query parameters of `GET /product/price-range`.
+#[derive(Debug)]
 pub struct PriceRangeQuery {
-    #[schema(example = "10.5")]
-    pub min_price: String,
-    #[schema(example = "50.0")]
-    pub max_price: String,
+    pub min_price: BigDecimal,
+    pub max_price: BigDecimal,
 }
 
-#[derive(Deserialize, ToSchema)]
+impl FromQueryParams for PriceRangeQuery {
+    fn from_query_params(params: &mut QueryParams) -> Self {
+        let min_price = read_price(params, "min_price", true);
+        let max_price = read_price(params, "max_price", true);
+        check_price_range(params, &min_price, &max_price);
+        Self {
+            min_price: min_price.unwrap_or_default(),
+            max_price: max_price.unwrap_or_default(),
+        }
+    }
+}
+
+/// How the category names of a product filter are matched.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CategoryMatch {
+    /// The whole name, ignoring case.
+    Exact,
+    /// Any part of the name, ignoring case.
+    #[default]
+    Fuzzy,
+}
+
+impl FromStr for CategoryMatch {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "exact" => Ok(CategoryMatch::Exact),
+            "fuzzy" => Ok(CategoryMatch::Fuzzy),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Query parameters of `GET /product/filter`.
+/// A product matches if it is in any of the given categories and meets every other condition.
+#[derive(Debug, Default)]
 pub struct FilterQuery {
-    #[schema(example = "Breakfast")]
-    pub category: Option<String>,
-    #[schema(example = "true")]
+    pub category_ids: Vec<i32>,
+    pub categories: Vec<String>,
+    pub category_match: CategoryMatch,
     pub is_best_seller: Option<bool>,
-    #[schema(example = "true")]
     pub is_deal_of_the_day: Option<bool>,
-    #[schema(example = "10.0")]
-    pub min_price: Option<String>,
-    #[schema(example = "100.0")]
-    pub max_price: Option<String>,
+    pub min_price: Option<BigDecimal>,
+    pub max_price: Option<BigDecimal>,
+    pub in_stock_only: bool,
+}
+
+impl FromQueryParams for FilterQuery {
+    fn from_query_params(params: &mut QueryParams) -> Self {
+        let category_ids: Vec<i32> = params.list("category_id", "a category id");
+        if category_ids.iter().any(|id| *id <= 0) {
+            params.invalid("category_id", "Category ids must be positive");
+        }
+        let categories = params.values("category");
+        if categories.iter().any(|name| name.chars().count() > 64) {
+            params.invalid("category", "Category names cannot exceed 64 characters");
+        }
+        let category_match = params
+            .optional("category_match", "'exact' or 'fuzzy'")
+            .unwrap_or_default();
+
+        let min_price = read_price(params, "min_price", false);
+        let max_price = read_price(params, "max_price", false);
+        check_price_range(params, &min_price, &max_price);
+
+        Self {
+            category_ids,
+            categories,
+            category_match,
+            is_best_seller: params.optional("is_best_seller", "true or false"),
+            is_deal_of_the_day: params.optional("is_deal_of_the_day", "true or false"),
+            min_price,
+            max_price,
+            in_stock_only: params
+                .optional("in_stock_only", "true or false")
+                .unwrap_or(false),
+        }
+    }
+}
+
+fn read_price(params: &mut QueryParams, name: &str, required: bool) -> Option<BigDecimal> {
+    let price: Option<BigDecimal> = if required {
+        params.required(name, "a number")
+    } else {
+        params.optional(name, "a number")
+    };
+    if price
+        .as_ref()
+        .is_some_and(|price| *price < BigDecimal::from(0))
+    {
+        params.invalid(name, "Cannot be negative");
+        return None;
+    }
+    price
+}
+
+fn check_price_range(
+    params: &mut QueryParams,
+    min_price: &Option<BigDecimal>,
+    max_price: &Option<BigDecimal>,
+) {
+    if let (Some(min), Some(max)) = (min_price, max_price) {
+        if min > max {
+            params.invalid("min_price", "Cannot be greater than max_price");
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct SearchQuery {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Search query must be between 1 and 100 characters"
+    ))]
+    #[schema(example = "borek")]
+    pub q: String,
+    #[validate(range(min = 1, max = 50, message = "Limit must be between 1 and 50"))]
+    #[schema(example = 10)]
+    pub limit: Option<i64>,
+    #[validate(range(min = 0, message = "Offset cannot be negative"))]
+    #[schema(example = 0)]
+    pub offset: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -42,10 +172,16 @@ pub struct ProductDto {
     pub discount: String,
     pub category_id: i32,
     pub category_name: Option<String>,
+    /// Quantity that can currently be ordered.
+    pub stock: i32,
+    pub in_stock: bool,
+    /// Gallery images in display order.
+    pub images: Vec<ProductImageDto>,
 }
 
 impl From<Product> for ProductDto {
     fn from(product: Product) -> Self {
+        let available_quantity = product.available_quantity();
         Self {
             id: product.id,
             name: product.name,
@@ -56,12 +192,16 @@ impl From<Product> for ProductDto {
             discount: product.discount.to_string(),
             category_id: product.category_id,
             category_name: None,
+            stock: available_quantity,
+            in_stock: available_quantity > 0,
+            images: Vec::new(),
         }
     }
 }
 
 impl From<ProductWithCategory> for ProductDto {
     fn from(product: ProductWithCategory) -> Self {
+        let available_quantity = product.available_quantity();
         Self {
             id: product.id,
             name: product.name,
@@ -72,6 +212,564 @@ impl From<ProductWithCategory> for ProductDto {
             discount: product.discount.to_string(),
             category_id: product.category_id,
             category_name: Some(product.category_name),
+            stock: available_quantity,
+            in_stock: available_quantity > 0,
+            images: Vec::new(),
+        }
+    }
+}
+
+/// An image of a product with the URLs of its resized variants.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProductImageDto {
+    pub id: i32,
+    /// Place in the gallery, starting at 0.
+    pub position: i32,
+    /// Whether this is the image shown in product lists.
+    pub is_primary: bool,
+    /// Width of the original in pixels.
+    pub width: i32,
+    /// Height of the original in pixels.
+    pub height: i32,
+    #[schema(example = "/assets/public/3f/3f2a9c.png")]
+    pub original_url: String,
+    /// At most 160x160 pixels, JPEG.
+    #[schema(example = "/assets/public/3f/3f2a9c-thumbnail.jpg")]
+    pub thumbnail_url: String,
+    /// At most 480x480 pixels, JPEG.
+    pub card_url: String,
+    /// At most 1200x1200 pixels, JPEG.
+    pub detail_url: String,
+    /// The detail size as lossless WebP, keeping transparency.
+    pub webp_url: String,
+}
+
+impl ProductImageDto {
+    /// Builds the URLs from the keys, which are relative to `base_url`.
+    pub fn new(image: ProductImage, base_url: &str) -> Self {
+        let url = |key: &str| format!("{}/{}", base_url.trim_end_matches('/'), key);
+        Self {
+            id: image.id,
+            position: image.position,
+            is_primary: image.is_primary,
+            width: image.width,
+            height: image.height,
+            original_url: url(&image.original_key),
+            thumbnail_url: url(&image.thumbnail_key),
+            card_url: url(&image.card_key),
+            detail_url: url(&image.detail_key),
+            webp_url: url(&image.webp_key),
+        }
+    }
+}
+
+/// Multipart form of `POST /product/{id}/images`; every file part is added to the gallery.
+#[derive(ToSchema)]
+pub struct UploadProductImagesForm {
+    #[schema(value_type = Vec<String>, format = Binary)]
+    pub images: Vec<Vec<u8>>,
+}
+
+/// The new order of a product's gallery, listing every image exactly once.
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct ReorderProductImagesDto {
+    #[validate(length(min = 1, message = "At least one image id is required"))]
+    #[schema(example = json!([3, 1, 2]))]
+    pub image_ids: Vec<i32>,
+}
+
+/// Boundaries of the price ranges counted for filtered product lists.
+/// The ranges are `[0, 10)`, `[10, 25)`, ... and `[100, ∞)`.
+pub const PRICE_FACET_BOUNDS: &[i32] = &[10, 25, 50, 100];
+
+/// Boundaries of the discount ranges, in percent, counted for filtered product lists.
+pub const DISCOUNT_FACET_BOUNDS: &[i32] = &[10, 25, 50];
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CategoryFacetDto {
+    pub category_id: i32,
+    pub category_name: String,
+    pub count: i64,
+}
+
+/// Number of matches with a value from `min` (inclusive) to `max` (exclusive).
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RangeFacetDto {
+    #[schema(example = "10")]
+    pub min: String,
+    /// Absent for the last, open-ended range.
+    #[schema(example = "25")]
+    pub max: Option<String>,
+    pub count: i64,
+}
+
+/// Counts of the products matching a filter, for rendering filter options.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProductFacetsDto {
+    /// Categories with at least one match, most matches first.
+    pub categories: Vec<CategoryFacetDto>,
+    pub price_ranges: Vec<RangeFacetDto>,
+    pub discount_ranges: Vec<RangeFacetDto>,
+    pub best_sellers: i64,
+    pub deals_of_the_day: i64,
+}
+
+impl ProductFacetsDto {
+    /// Lists every range, including the empty ones, so the options do not jump around.
+    fn ranges(bounds: &[i32], counts: &[(i32, i64)]) -> Vec<RangeFacetDto> {
+        (0..=bounds.len())
+            .map(|bucket| RangeFacetDto {
+                min: if bucket == 0 { 0 } else { bounds[bucket - 1] }.to_string(),
+                max: bounds.get(bucket).map(|bound| bound.to_string()),
+                count: counts
+                    .iter()
+                    .find(|(b, _)| *b as usize == bucket)
+                    .map_or(0, |(_, count)| *count),
+            })
+            .collect()
+    }
+}
+
+impl From<Vec<ProductFacetCount>> for ProductFacetsDto {
+    fn from(counts: Vec<ProductFacetCount>) -> Self {
+        let mut categories = Vec::new();
+        let mut price_buckets = Vec::new();
+        let mut discount_buckets = Vec::new();
+        let mut best_sellers = 0;
+        let mut deals_of_the_day = 0;
+
+        for row in counts {
+            if let (Some(category_id), Some(category_name)) = (row.category_id, row.category_name) {
+                categories.push(CategoryFacetDto {
+                    category_id,
+                    category_name,
+                    count: row.count,
+                });
+            } else if let Some(bucket) = row.price_bucket {
+                price_buckets.push((bucket, row.count));
+            } else if let Some(bucket) = row.discount_bucket {
+                discount_buckets.push((bucket, row.count));
+            } else if row.is_best_seller == Some(true) {
+                best_sellers = row.count;
+            } else if row.is_deal_of_the_day == Some(true) {
+                deals_of_the_day = row.count;
+            }
+        }
+        categories.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.category_name.cmp(&b.category_name))
+        });
+
+        Self {
+            categories,
+            price_ranges: Self::ranges(PRICE_FACET_BOUNDS, &price_buckets),
+            discount_ranges: Self::ranges(DISCOUNT_FACET_BOUNDS, &discount_buckets),
+            best_sellers,
+            deals_of_the_day,
         }
     }
 }
+
+/// A page of filtered products together with the facet counts of all matches.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ProductFilterResultDto {
+    #[serde(flatten)]
+    pub page: Page<ProductDto>,
+    pub facets: ProductFacetsDto,
+}
+
+/// A search result. `highlighted_name` and `snippet` are HTML-escaped product text
+/// with the matched words wrapped in `<mark>` tags.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProductSearchResultDto {
+    #[serde(flatten)]
+    pub product: ProductDto,
+    /// Relevance; higher is better.
+    pub rank: f32,
+    pub highlighted_name: String,
+    /// Part of the description around the matches.
+    pub snippet: String,
+}
+
+impl From<ProductSearchHit> for ProductSearchResultDto {
+    fn from(hit: ProductSearchHit) -> Self {
+        let available_quantity = hit.stock_quantity - hit.reserved_quantity;
+        Self {
+            product: ProductDto {
+                id: hit.id,
+                name: hit.name,
+                description: hit.description,
+                price: hit.price.to_string(),
+                is_best_seller: hit.is_best_seller,
+                is_deal_of_the_day: hit.is_deal_of_the_day,
+                discount: hit.discount.to_string(),
+                category_id: hit.category_id,
+                category_name: Some(hit.category_name),
+                stock: available_quantity,
+                in_stock: available_quantity > 0,
+                images: Vec::new(),
+            },
+            rank: hit.rank,
+            highlighted_name: highlight_html(&hit.highlighted_name),
+            snippet: highlight_html(&hit.snippet),
+        }
+    }
+}
+
+/// Escapes highlighted text for HTML and turns the highlight markers into `<mark>` tags.
+fn highlight_html(text: &str) -> String {
+    let mut html = String::with_capacity(text.len() + 16);
+    for c in text.chars() {
+        match c {
+            HIGHLIGHT_START => html.push_str("<mark>"),
+            HIGHLIGHT_STOP => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+    html
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct RestockDto {
+    #[validate(range(
+        min = 1,
+        max = 100000,
+        message = "Quantity must be between 1 and 100000"
+    ))]
+    #[schema(example = 20)]
+    pub quantity: i32,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct UpdateStockDto {
+    #[validate(range(min = 0, message = "Stock quantity cannot be negative"))]
+    #[schema(example = 50)]
+    pub stock_quantity: i32,
+    #[validate(range(min = 0, message = "Low stock threshold cannot be negative"))]
+    #[schema(example = 5)]
+    pub low_stock_threshold: Option<i32>,
+}
+
+/// Inventory view of a product, including stock held by open orders.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProductStockDto {
+    pub product_id: i32,
+    pub name: String,
+    pub stock_quantity: i32,
+    pub reserved_quantity: i32,
+    pub available_quantity: i32,
+    pub low_stock_threshold: i32,
+    pub is_low_stock: bool,
+}
+
+impl From<Product> for ProductStockDto {
+    fn from(product: Product) -> Self {
+        let available_quantity = product.available_quantity();
+        Self {
+            product_id: product.id,
+            name: product.name,
+            stock_quantity: product.stock_quantity,
+            reserved_quantity: product.reserved_quantity,
+            available_quantity,
+            low_stock_threshold: product.low_stock_threshold,
+            is_low_stock: available_quantity <= product.low_stock_threshold,
+        }
+    }
+}
+
+/// Largest price that fits the `decimal(10, 2)` price column.
+const MAX_PRICE: i64 = 100_000_000;
+
+/// Mirrors the `price >= 0` CHECK constraint and the column's precision.
+fn validate_price(price: &BigDecimal) -> Result<(), ValidationError> {
+    if *price < BigDecimal::from(0) || *price >= BigDecimal::from(MAX_PRICE) {
+        return Err(ValidationError::new("price")
+            .with_message("Price must be between 0 and 99999999.99".into()));
+    }
+    Ok(())
+}
+
+/// Mirrors the `discount >= 0 and discount <= 100` CHECK constraint.
+fn validate_discount(discount: &BigDecimal) -> Result<(), ValidationError> {
+    if *discount < BigDecimal::from(0) || *discount > BigDecimal::from(100) {
+        return Err(ValidationError::new("discount")
+            .with_message("Discount must be between 0 and 100".into()));
+    }
+    Ok(())
+}
+
+fn default_low_stock_threshold() -> i32 {
+    5
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct CreateProductDto {
+    #[validate(length(
+        min = 1,
+        max = 64,
+        message = "Name must be between 1 and 64 characters"
+    ))]
+    #[schema(example = "Avocado Toast")]
+    pub name: String,
+    #[schema(example = "Sourdough toast topped with smashed avocado")]
+    pub description: String,
+    #[validate(custom(function = "validate_price"))]
+    #[schema(value_type = String, example = "8.50")]
+    pub price: BigDecimal,
+    #[serde(default)]
+    pub is_best_seller: bool,
+    #[serde(default)]
+    pub is_deal_of_the_day: bool,
+    #[serde(default)]
+    #[validate(custom(function = "validate_discount"))]
+    #[schema(value_type = String, example = "10")]
+    pub discount: BigDecimal,
+    #[schema(example = 1)]
+    pub category_id: i32,
+    #[serde(default)]
+    #[validate(range(min = 0, message = "Stock quantity cannot be negative"))]
+    #[schema(example = 50)]
+    pub stock_quantity: i32,
+    #[serde(default = "default_low_stock_threshold")]
+    #[validate(range(min = 0, message = "Low stock threshold cannot be negative"))]
+    #[schema(example = 5)]
+    pub low_stock_threshold: i32,
+}
+
+/// Request body for replacing a product's catalogue data.
+/// Stock is managed through the dedicated stock endpoints.
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct UpdateProductDto {
+    #[validate(length(
+        min = 1,
+        max = 64,
+        message = "Name must be between 1 and 64 characters"
+    ))]
+    #[schema(example = "Avocado Toast")]
+    pub name: String,
+    #[schema(example = "Sourdough toast topped with smashed avocado")]
+    pub description: String,
+    #[validate(custom(function = "validate_price"))]
+    #[schema(value_type = String, example = "8.50")]
+    pub price: BigDecimal,
+    pub is_best_seller: bool,
+    pub is_deal_of_the_day: bool,
+    #[validate(custom(function = "validate_discount"))]
+    #[schema(value_type = String, example = "10")]
+    pub discount: BigDecimal,
+    #[schema(example = 1)]
+    pub category_id: i32,
+}
+
+/// Request body for a partial product update; omitted fields are left unchanged.
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema, Validate)]
+pub struct PatchProductDto {
+    #[validate(length(
+        min = 1,
+        max = 64,
+        message = "Name must be between 1 and 64 characters"
+    ))]
+    #[schema(example = "Avocado Toast")]
+    pub name: Option<String>,
+    pub description: Option<String>,
+    #[validate(custom(function = "validate_price"))]
+    #[schema(value_type = Option<String>, example = "8.50")]
+    pub price: Option<BigDecimal>,
+    pub is_best_seller: Option<bool>,
+    pub is_deal_of_the_day: Option<bool>,
+    #[validate(custom(function = "validate_discount"))]
+    #[schema(value_type = Option<String>, example = "10")]
+    pub discount: Option<BigDecimal>,
+    pub category_id: Option<i32>,
+}
+
+impl From<UpdateProductDto> for PatchProductDto {
+    fn from(dto: UpdateProductDto) -> Self {
+        Self {
+            name: Some(dto.name),
+            description: Some(dto.description),
+            price: Some(dto.price),
+            is_best_seller: Some(dto.is_best_seller),
+            is_deal_of_the_day: Some(dto.is_deal_of_the_day),
+            discount: Some(dto.discount),
+            category_id: Some(dto.category_id),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct BulkPatchItemDto {
+    #[schema(example = 1)]
+    pub id: i32,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub changes: PatchProductDto,
+}
+
+/// Request body for patching several products at once.
+/// All changes are applied in a single transaction.
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct BulkPatchProductDto {
+    #[validate(
+        length(
+            min = 1,
+            max = 100,
+            message = "Between 1 and 100 products can be updated at once"
+        ),
+        nested
+    )]
+    pub items: Vec<BulkPatchItemDto>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        common::{dto::PageQuery, error::AppError},
+        domains::product::domain::model::ProductFacetCount,
+    };
+
+    fn invalid_fields<T: FromQueryParams + std::fmt::Debug>(query: &str) -> Vec<String> {
+        match QueryParams::extract::<T>(query) {
+            Err(AppError::InvalidParameters(errors)) => {
+                errors.into_iter().map(|error| error.field).collect()
+            }
+            other => panic!("expected invalid parameters, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_price_range_is_checked() {
+        let range: PriceRangeQuery = QueryParams::extract("min_price=2.5&max_price=2.5").unwrap();
+        assert_eq!(range.min_price, range.max_price);
+
+        assert_eq!(
+            invalid_fields::<PriceRangeQuery>("min_price=5&max_price=2"),
+            ["min_price"]
+        );
+        assert_eq!(
+            invalid_fields::<PriceRangeQuery>("min_price=-1"),
+            ["min_price", "max_price"]
+        );
+        assert_eq!(
+            invalid_fields::<(FilterQuery, PageQuery)>(
+                "min_price=9&max_price=1&category_id=0&limit=500&offset=x"
+            ),
+            ["category_id", "min_price", "limit", "offset"]
+        );
+    }
+
+    fn facet_count(count: i64) -> ProductFacetCount {
+        ProductFacetCount {
+            category_id: None,
+            category_name: None,
+            price_bucket: None,
+            discount_bucket: None,
+            is_best_seller: None,
+            is_deal_of_the_day: None,
+            count,
+        }
+    }
+
+    #[test]
+    fn test_facets_from_grouping_sets() {
+        // Rows as returned by the grouping sets query, in no particular order.
+        let counts = vec![
+            facet_count(9),
+            ProductFacetCount {
+                category_id: Some(2),
+                category_name: Some("Drinks".into()),
+                ..facet_count(3)
+            },
+            ProductFacetCount {
+                category_id: Some(1),
+                category_name: Some("Bakery".into()),
+                ..facet_count(3)
+            },
+            ProductFacetCount {
+                category_id: Some(3),
+                category_name: Some("Mains".into()),
+                ..facet_count(3)
+            },
+            ProductFacetCount {
+                price_bucket: Some(0),
+                ..facet_count(5)
+            },
+            ProductFacetCount {
+                price_bucket: Some(4),
+                ..facet_count(4)
+            },
+            ProductFacetCount {
+                discount_bucket: Some(1),
+                ..facet_count(9)
+            },
+            ProductFacetCount {
+                is_best_seller: Some(false),
+                ..facet_count(7)
+            },
+            ProductFacetCount {
+                is_best_seller: Some(true),
+                ..facet_count(2)
+            },
+            ProductFacetCount {
+                is_deal_of_the_day: Some(false),
+                ..facet_count(9)
+            },
+        ];
+        assert!(counts[0].is_total());
+        assert!(!counts[7].is_total());
+
+        let facets = ProductFacetsDto::from(counts);
+        let categories: Vec<(i32, i64)> = facets
+            .categories
+            .iter()
+            .map(|category| (category.category_id, category.count))
+            .collect();
+        assert_eq!(categories, [(1, 3), (2, 3), (3, 3)]);
+
+        let ranges = |ranges: &[RangeFacetDto]| -> Vec<(String, Option<String>, i64)> {
+            ranges
+                .iter()
+                .map(|range| (range.min.clone(), range.max.clone(), range.count))
+                .collect()
+        };
+        let range = |min: &str, max: Option<&str>, count| (min.into(), max.map(Into::into), count);
+        assert_eq!(
+            ranges(&facets.price_ranges),
+            [
+                range("0", Some("10"), 5),
+                range("10", Some("25"), 0),
+                range("25", Some("50"), 0),
+                range("50", Some("100"), 0),
+                range("100", None, 4),
+            ]
+        );
+        assert_eq!(
+            ranges(&facets.discount_ranges),
+            [
+                range("0", Some("10"), 0),
+                range("10", Some("25"), 9),
+                range("25", Some("50"), 0),
+                range("50", None, 0),
+            ]
+        );
+        assert_eq!(facets.best_sellers, 2);
+        assert_eq!(facets.deals_of_the_day, 0);
+    }
+
+    #[test]
+    fn test_highlights_are_escaped() {
+        let text =
+            format!("<img src=x onerror=\"alert('x')\"> & {HIGHLIGHT_START}Börek{HIGHLIGHT_STOP}");
+        assert_eq!(
+            highlight_html(&text),
+            "&lt;img src=x onerror=&quot;alert(&#39;x&#39;)&quot;&gt; &amp; <mark>Börek</mark>"
+        );
+    }
+}
@@ -1,35 +1,165 @@
-use std::str::FromStr;
-
-use crate::domains::product::domain::{
-    model::{Product, ProductWithCategory},
-    repository::ProductRepository,
+use crate::{
+    common::pagination::{PageRequest, SortField, SortType},
+    domains::product::{
+        domain::{
+            model::{
+                NewProductImage, Product, ProductFacetCount, ProductImage, ProductSearchHit,
+                ProductWithCategory, HIGHLIGHT_START, HIGHLIGHT_STOP,
+            },
+            repository::ProductRepository,
+        },
+        dto::product_dto::{
+            CategoryMatch, CreateProductDto, FilterQuery, PatchProductDto, DISCOUNT_FACET_BOUNDS,
+            PRICE_FACET_BOUNDS,
+        },
+    },
 };
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, QueryBuilder, Transaction};
 
 pub struct ProductRepo;
 
+/// Fields product lists can be sorted by; the first one is the default.
+pub const PRODUCT_SORT_FIELDS: &[SortField] = &[
+    SortField {
+        name: "id",
+        column: "p.id",
+        sort_type: SortType::Integer,
+    },
+    SortField {
+        name: "name",
+        column: "p.name",
+        sort_type: SortType::Text,
+    },
+    SortField {
+        name: "price",
+        column: "p.price",
+        sort_type: SortType::Numeric,
+    },
+    SortField {
+        name: "discount",
+        column: "p.discount",
+        sort_type: SortType::Numeric,
+    },
+];
+
+/// Minimum `word_similarity` between the query and a product name for a typo match.
+/// The pg_trgm default of 0.6 rejects most single-letter typos in short words.
+const SEARCH_NAME_SIMILARITY: &str = "0.4";
+
+/// Turns free text into a `to_tsquery` expression that matches every word as a prefix.
+/// Anything but letters and digits is dropped, so user input cannot inject query operators.
+fn prefix_tsquery(query: &str) -> String {
+    query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("{word}:*"))
+        .collect::<Vec<_>>()
+        .join(" & ")
+}
+
+fn facet_bounds(bounds: &[i32]) -> Vec<BigDecimal> {
+    bounds
+        .iter()
+        .map(|bound| BigDecimal::from(*bound))
+        .collect()
+}
+
+/// Appends the conditions of a product filter to a query over `products p`
+/// joined with `categories c`.
+fn push_filter(query_builder: &mut QueryBuilder<'_, Postgres>, filter: &FilterQuery) {
+    if !filter.category_ids.is_empty() || !filter.categories.is_empty() {
+        query_builder.push(" AND (p.category_id = ANY(");
+        query_builder.push_bind(filter.category_ids.clone());
+        match filter.category_match {
+            CategoryMatch::Exact => {
+                query_builder.push(") OR lower(c.name) = ANY(");
+                query_builder.push_bind(
+                    filter
+                        .categories
+                        .iter()
+                        .map(|name| name.to_lowercase())
+                        .collect::<Vec<_>>(),
+                );
+            }
+            CategoryMatch::Fuzzy => {
+                query_builder.push(") OR c.name ILIKE ANY(");
+                query_builder.push_bind(
+                    filter
+                        .categories
+                        .iter()
+                        .map(|name| format!("%{}%", escape_like(name)))
+                        .collect::<Vec<_>>(),
+                );
+            }
+        }
+        query_builder.push("))");
+    }
+    if let Some(best_seller) = filter.is_best_seller {
+        query_builder.push(" AND p.is_best_seller = ");
+        query_builder.push_bind(best_seller);
+    }
+    if let Some(deal_of_the_day) = filter.is_deal_of_the_day {
+        query_builder.push(" AND p.is_deal_of_the_day = ");
+        query_builder.push_bind(deal_of_the_day);
+    }
+    if let Some(min_price) = &filter.min_price {
+        query_builder.push(" AND p.price >= ");
+        query_builder.push_bind(min_price.clone());
+    }
+    if let Some(max_price) = &filter.max_price {
+        query_builder.push(" AND p.price <= ");
+        query_builder.push_bind(max_price.clone());
+    }
+    if filter.in_stock_only {
+        query_builder.push(" AND p.stock_quantity - p.reserved_quantity > 0");
+    }
+}
+
+/// Escapes the `LIKE` wildcards in user input so they match literally.
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
 #[async_trait]
 impl ProductRepository for ProductRepo {
-    async fn find_all(&self, pool: PgPool) -> Result<Vec<Product>, sqlx::Error> {
-        let products = sqlx::query_as!(
-            Product,
-            r#"
-            SELECT id, name, description, price, is_best_seller, is_deal_of_the_day, discount, category_id
-            FROM products
-            "#
-        )
-        .fetch_all(&pool)
-        .await?;
-        Ok(products)
+    async fn find_all(
+        &self,
+        pool: PgPool,
+        page: &PageRequest,
+    ) -> Result<(Vec<Product>, i64), sqlx::Error> {
+        let mut query_builder = QueryBuilder::new(
+            "SELECT p.id, p.name, p.description, p.price, p.is_best_seller, p.is_deal_of_the_day,
+                    p.discount, p.category_id, p.stock_quantity, p.reserved_quantity,
+                    p.low_stock_threshold
+             FROM products p
+             WHERE 1=1",
+        );
+        page.push_after(&mut query_builder, "p.id");
+        page.push_order_and_limit(&mut query_builder, "p.id");
+
+        let products = query_builder
+            .build_query_as::<Product>()
+            .fetch_all(&pool)
+            .await?;
+
+        let total = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM products"#)
+            .fetch_one(&pool)
+            .await?;
+
+        Ok((products, total))
     }
 
     async fn find_by_id(&self, pool: PgPool, id: i32) -> Result<Option<Product>, sqlx::Error> {
         let product = sqlx::query_as!(
             Product,
             r#"
-            SELECT id, name, description, price, is_best_seller, is_deal_of_the_day, discount, category_id
+            SELECT id, name, description, price, is_best_seller, is_deal_of_the_day, discount, category_id,
+                   stock_quantity, reserved_quantity, low_stock_threshold
             FROM products
             WHERE id = $1
             "#,
@@ -44,17 +174,26 @@ impl ProductRepository for ProductRepo {
         &self,
         pool: PgPool,
         category_id: i32,
+        include_descendants: bool,
     ) -> Result<Vec<ProductWithCategory>, sqlx::Error> {
         let products = sqlx::query_as!(
             ProductWithCategory,
             r#"
+            WITH RECURSIVE subtree AS (
+                SELECT id FROM categories WHERE id = $1
+                UNION ALL
+                SELECT c.id FROM categories c INNER JOIN subtree s ON c.parent_id = s.id
+                WHERE $2
+            )
             SELECT p.id, p.name, p.description, p.price, p.is_best_seller, p.is_deal_of_the_day, 
-                   p.discount, p.category_id, c.name as category_name
+                   p.discount, p.category_id, p.stock_quantity, p.reserved_quantity,
+                   p.low_stock_threshold, c.name as category_name
             FROM products p
             INNER JOIN categories c ON p.category_id = c.id
-            WHERE p.category_id = $1
+            WHERE p.category_id IN (SELECT id FROM subtree)
             "#,
-            category_id
+            category_id,
+            include_descendants
         )
         .fetch_all(&pool)
         .await?;
@@ -69,7 +208,8 @@ impl ProductRepository for ProductRepo {
         let products = sqlx::query_as!(
             Product,
             r#"
-            SELECT id, name, description, price, is_best_seller, is_deal_of_the_day, discount, category_id
+            SELECT id, name, description, price, is_best_seller, is_deal_of_the_day, discount, category_id,
+                   stock_quantity, reserved_quantity, low_stock_threshold
             FROM products
             WHERE is_best_seller = true
             LIMIT $1
@@ -89,7 +229,8 @@ impl ProductRepository for ProductRepo {
         let products = sqlx::query_as!(
             Product,
             r#"
-            SELECT id, name, description, price, is_best_seller, is_deal_of_the_day, discount, category_id
+            SELECT id, name, description, price, is_best_seller, is_deal_of_the_day, discount, category_id,
+                   stock_quantity, reserved_quantity, low_stock_threshold
             FROM products
             WHERE is_deal_of_the_day = true
             LIMIT $1
@@ -110,7 +251,8 @@ impl ProductRepository for ProductRepo {
         let products = sqlx::query_as!(
             Product,
             r#"
-            SELECT id, name, description, price, is_best_seller, is_deal_of_the_day, discount, category_id
+            SELECT id, name, description, price, is_best_seller, is_deal_of_the_day, discount, category_id,
+                   stock_quantity, reserved_quantity, low_stock_threshold
             FROM products
             WHERE price BETWEEN $1 AND $2
             "#,
@@ -125,51 +267,456 @@ impl ProductRepository for ProductRepo {
     async fn find_by_filter(
         &self,
         pool: PgPool,
-        category: Option<String>,
-        is_best_seller: Option<bool>,
-        is_deal_of_the_day: Option<bool>,
-        min_price: Option<String>,
-        max_price: Option<String>,
-    ) -> Result<Vec<ProductWithCategory>, sqlx::Error> {
-        let mut query_builder = sqlx::QueryBuilder::new(
+        filter: FilterQuery,
+        page: &PageRequest,
+    ) -> Result<(Vec<ProductWithCategory>, Vec<ProductFacetCount>), sqlx::Error> {
+        let mut query_builder = QueryBuilder::new(
             "SELECT p.id, p.name, p.description, p.price, p.is_best_seller, p.is_deal_of_the_day, 
-                    p.discount, p.category_id, c.name as category_name
+                    p.discount, p.category_id, p.stock_quantity, p.reserved_quantity,
+                   p.low_stock_threshold, c.name as category_name
              FROM products p
              INNER JOIN categories c ON p.category_id = c.id
              WHERE 1=1",
         );
+        push_filter(&mut query_builder, &filter);
+        page.push_after(&mut query_builder, "p.id");
+        page.push_order_and_limit(&mut query_builder, "p.id");
 
-        if let Some(cat) = &category {
-            query_builder.push(" AND c.name ILIKE ");
-            query_builder.push_bind(format!("%{}%", cat));
-        }
-        if let Some(best_seller) = is_best_seller {
-            query_builder.push(" AND p.is_best_seller = ");
-            query_builder.push_bind(best_seller);
-        }
-        if let Some(deal_of_the_day) = is_deal_of_the_day {
-            query_builder.push(" AND p.is_deal_of_the_day = ");
-            query_builder.push_bind(deal_of_the_day);
-        }
-        if let Some(min) = &min_price {
-            // Convert min_price to BigDecimal, only add filter if conversion succeeds
-            if let Ok(min_val) = BigDecimal::from_str(min) {
-                query_builder.push(" AND p.price >= ");
-                query_builder.push_bind(min_val);
-            }
-        }
-        if let Some(max) = &max_price {
-            if let Ok(max_val) = BigDecimal::from_str(max) {
-                query_builder.push(" AND p.price <= ");
-                query_builder.push_bind(max_val);
-            }
-        }
+        // The facets are counted by a second query over the same filter, in the same snapshot
+        // as the page so the total agrees with it. Its empty grouping set counts all matches.
+        let mut tx = pool.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .execute(&mut *tx)
+            .await?;
 
         let products = query_builder
             .build_query_as::<ProductWithCategory>()
-            .fetch_all(&pool)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let mut facet_builder = QueryBuilder::new(
+            "WITH matches AS (
+                 SELECT p.category_id, c.name AS category_name, p.is_best_seller,
+                        p.is_deal_of_the_day, width_bucket(p.price, ",
+        );
+        facet_builder.push_bind(facet_bounds(PRICE_FACET_BOUNDS));
+        facet_builder.push("::NUMERIC[]) AS price_bucket, width_bucket(p.discount, ");
+        facet_builder.push_bind(facet_bounds(DISCOUNT_FACET_BOUNDS));
+        facet_builder.push(
+            "::NUMERIC[]) AS discount_bucket
+                 FROM products p
+                 INNER JOIN categories c ON p.category_id = c.id
+                 WHERE 1=1",
+        );
+        push_filter(&mut facet_builder, &filter);
+        facet_builder.push(
+            ")
+             SELECT category_id, category_name, price_bucket, discount_bucket, is_best_seller,
+                    is_deal_of_the_day, COUNT(*) AS count
+             FROM matches
+             GROUP BY GROUPING SETS (
+                 (), (category_id, category_name), (price_bucket), (discount_bucket),
+                 (is_best_seller), (is_deal_of_the_day)
+             )",
+        );
+        let facets = facet_builder
+            .build_query_as::<ProductFacetCount>()
+            .fetch_all(&mut *tx)
             .await?;
 
+        tx.commit().await?;
+        Ok((products, facets))
+    }
+
+    async fn search(
+        &self,
+        pool: PgPool,
+        query: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ProductSearchHit>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        // Scoped to this transaction, so other queries keep the default threshold.
+        sqlx::query!(
+            "SELECT set_config('pg_trgm.word_similarity_threshold', $1, true)",
+            SEARCH_NAME_SIMILARITY
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let hits = sqlx::query_as!(
+            ProductSearchHit,
+            r#"
+            WITH q AS (
+                SELECT to_tsquery('product_search', $1) AS query, search_fold($2) AS text
+            )
+            SELECT p.id, p.name, p.description, p.price, p.is_best_seller, p.is_deal_of_the_day,
+                   p.discount, p.category_id, p.stock_quantity, p.reserved_quantity,
+                   p.low_stock_threshold, c.name as category_name, r.rank AS "rank!",
+                   ts_headline('product_search', p.name, q.query,
+                               'HighlightAll=true, ' || $5) AS "highlighted_name!",
+                   ts_headline('product_search', p.description, q.query,
+                               'MaxFragments=1, MinWords=5, MaxWords=20, ' || $5) AS "snippet!"
+            FROM products p
+            INNER JOIN categories c ON p.category_id = c.id
+            CROSS JOIN q
+            CROSS JOIN LATERAL (
+                SELECT (ts_rank_cd(p.search_vector, q.query)
+                        + word_similarity(q.text, search_fold(p.name)))::REAL AS rank
+            ) r
+            WHERE p.search_vector @@ q.query OR q.text <% search_fold(p.name)
+            ORDER BY r.rank DESC, p.id
+            LIMIT $3 OFFSET $4
+            "#,
+            prefix_tsquery(query),
+            query,
+            limit,
+            offset,
+            format!("StartSel=\"{HIGHLIGHT_START}\", StopSel=\"{HIGHLIGHT_STOP}\"")
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(hits)
+    }
+
+    async fn find_low_stock(&self, pool: PgPool) -> Result<Vec<Product>, sqlx::Error> {
+        let products = sqlx::query_as!(
+            Product,
+            r#"
+            SELECT id, name, description, price, is_best_seller, is_deal_of_the_day, discount, category_id,
+                   stock_quantity, reserved_quantity, low_stock_threshold
+            FROM products
+            WHERE stock_quantity - reserved_quantity <= low_stock_threshold
+            ORDER BY stock_quantity - reserved_quantity, id
+            "#
+        )
+        .fetch_all(&pool)
+        .await?;
         Ok(products)
     }
+
+    async fn find_by_id_for_update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+    ) -> Result<Option<Product>, sqlx::Error> {
+        let product = sqlx::query_as!(
+            Product,
+            r#"
+            SELECT id, name, description, price, is_best_seller, is_deal_of_the_day, discount, category_id,
+                   stock_quantity, reserved_quantity, low_stock_threshold
+            FROM products
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            id
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+        Ok(product)
+    }
+
+    async fn restock(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        quantity: i32,
+    ) -> Result<Option<Product>, sqlx::Error> {
+        let product = sqlx::query_as!(
+            Product,
+            r#"
+            UPDATE products
+            SET stock_quantity = stock_quantity + $2
+            WHERE id = $1
+            RETURNING id, name, description, price, is_best_seller, is_deal_of_the_day, discount,
+                      category_id, stock_quantity, reserved_quantity, low_stock_threshold
+            "#,
+            id,
+            quantity
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+        Ok(product)
+    }
+
+    async fn update_stock(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        stock_quantity: i32,
+        low_stock_threshold: i32,
+    ) -> Result<Product, sqlx::Error> {
+        let product = sqlx::query_as!(
+            Product,
+            r#"
+            UPDATE products
+            SET stock_quantity = $2,
+                low_stock_threshold = $3
+            WHERE id = $1
+            RETURNING id, name, description, price, is_best_seller, is_deal_of_the_day, discount,
+                      category_id, stock_quantity, reserved_quantity, low_stock_threshold
+            "#,
+            id,
+            stock_quantity,
+            low_stock_threshold
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+        Ok(product)
+    }
+
+    async fn create(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        product: CreateProductDto,
+    ) -> Result<Product, sqlx::Error> {
+        let product = sqlx::query_as!(
+            Product,
+            r#"
+            INSERT INTO products
+                (name, description, price, is_best_seller, is_deal_of_the_day, discount, category_id,
+                 stock_quantity, low_stock_threshold)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, name, description, price, is_best_seller, is_deal_of_the_day, discount,
+                      category_id, stock_quantity, reserved_quantity, low_stock_threshold
+            "#,
+            product.name,
+            product.description,
+            product.price,
+            product.is_best_seller,
+            product.is_deal_of_the_day,
+            product.discount,
+            product.category_id,
+            product.stock_quantity,
+            product.low_stock_threshold
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+        Ok(product)
+    }
+
+    async fn update(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        changes: PatchProductDto,
+    ) -> Result<Option<Product>, sqlx::Error> {
+        let product = sqlx::query_as!(
+            Product,
+            r#"
+            UPDATE products
+            SET name = COALESCE($2, name),
+                description = COALESCE($3, description),
+                price = COALESCE($4, price),
+                is_best_seller = COALESCE($5, is_best_seller),
+                is_deal_of_the_day = COALESCE($6, is_deal_of_the_day),
+                discount = COALESCE($7, discount),
+                category_id = COALESCE($8, category_id)
+            WHERE id = $1
+            RETURNING id, name, description, price, is_best_seller, is_deal_of_the_day, discount,
+                      category_id, stock_quantity, reserved_quantity, low_stock_threshold
+            "#,
+            id,
+            changes.name,
+            changes.description,
+            changes.price,
+            changes.is_best_seller,
+            changes.is_deal_of_the_day,
+            changes.discount,
+            changes.category_id
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+        Ok(product)
+    }
+
+    async fn delete(&self, tx: &mut Transaction<'_, Postgres>, id: i32) -> Result<(), sqlx::Error> {
+        sqlx::query!(r#"DELETE FROM products WHERE id = $1"#, id)
+            .execute(&mut **tx)
+            .await?;
+        Ok(())
+    }
+
+    async fn find_images(
+        &self,
+        pool: PgPool,
+        product_ids: &[i32],
+    ) -> Result<Vec<ProductImage>, sqlx::Error> {
+        let images = sqlx::query_as!(
+            ProductImage,
+            r#"
+            SELECT id, product_id, position, is_primary, original_key, thumbnail_key, card_key,
+                   detail_key, webp_key, width, height
+            FROM product_images
+            WHERE product_id = ANY($1)
+            ORDER BY product_id, position, id
+            "#,
+            product_ids
+        )
+        .fetch_all(&pool)
+        .await?;
+        Ok(images)
+    }
+
+    async fn find_images_of_product(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        product_id: i32,
+    ) -> Result<Vec<ProductImage>, sqlx::Error> {
+        let images = sqlx::query_as!(
+            ProductImage,
+            r#"
+            SELECT id, product_id, position, is_primary, original_key, thumbnail_key, card_key,
+                   detail_key, webp_key, width, height
+            FROM product_images
+            WHERE product_id = $1
+            ORDER BY position, id
+            "#,
+            product_id
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+        Ok(images)
+    }
+
+    async fn add_images(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        product_id: i32,
+        images: Vec<NewProductImage>,
+    ) -> Result<(), sqlx::Error> {
+        for image in images {
+            sqlx::query!(
+                r#"
+                INSERT INTO product_images (product_id, position, is_primary, original_key,
+                    thumbnail_key, card_key, detail_key, webp_key, width, height)
+                SELECT $1,
+                       COALESCE(MAX(position) + 1, 0),
+                       NOT COALESCE(bool_or(is_primary), false),
+                       $2, $3, $4, $5, $6, $7, $8
+                FROM product_images
+                WHERE product_id = $1
+                "#,
+                product_id,
+                image.original_key,
+                image.thumbnail_key,
+                image.card_key,
+                image.detail_key,
+                image.webp_key,
+                image.width,
+                image.height
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn reorder_images(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        product_id: i32,
+        image_ids: &[i32],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE product_images
+            SET position = o.ordinality - 1
+            FROM unnest($2::INT[]) WITH ORDINALITY AS o(id, ordinality)
+            WHERE product_images.id = o.id AND product_images.product_id = $1
+            "#,
+            product_id,
+            image_ids
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    async fn set_primary_image(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        product_id: i32,
+        image_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        // Cleared first, as the unique index on primary images is checked row by row.
+        sqlx::query!(
+            r#"UPDATE product_images SET is_primary = false WHERE product_id = $1 AND is_primary"#,
+            product_id
+        )
+        .execute(&mut **tx)
+        .await?;
+        sqlx::query!(
+            r#"UPDATE product_images SET is_primary = true WHERE product_id = $1 AND id = $2"#,
+            product_id,
+            image_id
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_image(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        product_id: i32,
+        image_id: i32,
+    ) -> Result<Option<ProductImage>, sqlx::Error> {
+        let image = sqlx::query_as!(
+            ProductImage,
+            r#"
+            DELETE FROM product_images
+            WHERE product_id = $1 AND id = $2
+            RETURNING id, product_id, position, is_primary, original_key, thumbnail_key, card_key,
+                      detail_key, webp_key, width, height
+            "#,
+            product_id,
+            image_id
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        if image.as_ref().is_some_and(|image| image.is_primary) {
+            sqlx::query!(
+                r#"
+                UPDATE product_images SET is_primary = true
+                WHERE id = (
+                    SELECT id FROM product_images
+                    WHERE product_id = $1
+                    ORDER BY position, id
+                    LIMIT 1
+                )
+                "#,
+                product_id
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+        Ok(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_tsquery() {
+        assert_eq!(prefix_tsquery("chicken"), "chicken:*");
+        assert_eq!(prefix_tsquery("  grilled   chick "), "grilled:* & chick:*");
+        assert_eq!(
+            prefix_tsquery("a & b | !c <-> (d:*) 'e'"),
+            "a:* & b:* & c:* & d:* & e:*"
+        );
+        assert_eq!(prefix_tsquery(""), "");
+        assert_eq!(prefix_tsquery("&|!():* <->"), "");
+        assert_eq!(
+            prefix_tsquery("Börek çiğ-köfte"),
+            "Börek:* & çiğ:* & köfte:*"
+        );
+        assert_eq!(prefix_tsquery("寿司 ramen2go"), "寿司:* & ramen2go:*");
+    }
 }
@@ -1,35 +1,304 @@
 use crate::{
-    common::error::AppError,
+    common::{
+        config::Config,
+        dto::{Page, PageQuery},
+        error::AppError,
+        file_type::ImageLimits,
+        image_variants::{generate_variants, Variant, VariantFormat},
+        multipart_helper::{remove_files, UploadedFile},
+        pagination::PageRequest,
+        storage::Storage,
+    },
     domains::product::{
-        domain::{repository::ProductRepository, service::ProductServiceTrait},
-        dto::product_dto::ProductDto,
-        infra::impl_repository::ProductRepo,
+        domain::{
+            model::{NewProductImage, ProductImage},
+            repository::ProductRepository,
+            service::ProductServiceTrait,
+        },
+        dto::product_dto::{
+            BulkPatchProductDto, CreateProductDto, FilterQuery, PatchProductDto, ProductDto,
+            ProductFilterResultDto, ProductImageDto, ProductSearchResultDto, ProductStockDto,
+            ReorderProductImagesDto, RestockDto, SearchQuery, UpdateProductDto, UpdateStockDto,
+        },
+        infra::impl_repository::{ProductRepo, PRODUCT_SORT_FIELDS},
     },
 };
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
 use sqlx::PgPool;
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 #[derive(Clone)]
 pub struct ProductService {
     pub pool: PgPool,
     pub repo: Arc<dyn ProductRepository + Send + Sync>,
+    pub config: Config,
+    /// Storage product images and their variants are kept in.
+    pub storage: Arc<dyn Storage>,
+}
+
+/// Number of search results returned when the request does not set a limit.
+const DEFAULT_SEARCH_LIMIT: i64 = 10;
+
+/// Most images a product's gallery can hold.
+const MAX_PRODUCT_IMAGES: usize = 20;
+
+/// Variants generated for every product image, in the order of the `*_key` columns
+/// after `original_key`.
+const PRODUCT_IMAGE_VARIANTS: &[Variant] = &[
+    Variant {
+        name: "thumbnail",
+        max_size: 160,
+        format: VariantFormat::Jpeg,
+    },
+    Variant {
+        name: "card",
+        max_size: 480,
+        format: VariantFormat::Jpeg,
+    },
+    Variant {
+        name: "detail",
+        max_size: 1200,
+        format: VariantFormat::Jpeg,
+    },
+    Variant {
+        name: "detail",
+        max_size: 1200,
+        format: VariantFormat::Webp,
+    },
+];
+
+/// Maps constraint violations on product writes to client errors.
+fn map_write_error(err: sqlx::Error) -> AppError {
+    if let Some(db_err) = err.as_database_error() {
+        if db_err.is_unique_violation() {
+            return AppError::Conflict("A product with this name already exists".into());
+        }
+        if db_err.is_foreign_key_violation() {
+            return AppError::ValidationError("Category does not exist".into());
+        }
+    }
+    tracing::error!("Error writing product: {err}");
+    AppError::DatabaseError(err)
+}
+
+fn map_image_error(err: sqlx::Error) -> AppError {
+    tracing::error!("Error updating product images: {err}");
+    AppError::DatabaseError(err)
+}
+
+impl ProductService {
+    fn image_dtos(&self, images: Vec<ProductImage>) -> Vec<ProductImageDto> {
+        images
+            .into_iter()
+            .map(|image| ProductImageDto::new(image, &self.config.assets_public_url))
+            .collect()
+    }
+
+    /// Fills in the gallery images of the given products.
+    async fn attach_images(&self, mut products: Vec<&mut ProductDto>) -> Result<(), AppError> {
+        if products.is_empty() {
+            return Ok(());
+        }
+        let ids: Vec<i32> = products.iter().map(|product| product.id).collect();
+        let images = self
+            .repo
+            .find_images(self.pool.clone(), &ids)
+            .await
+            .map_err(|err| {
+                tracing::error!("Error fetching product images: {err}");
+                AppError::DatabaseError(err)
+            })?;
+
+        let mut by_product: HashMap<i32, Vec<ProductImage>> = HashMap::new();
+        for image in images {
+            by_product.entry(image.product_id).or_default().push(image);
+        }
+        for product in products.iter_mut() {
+            if let Some(images) = by_product.remove(&product.id) {
+                product.images = self.image_dtos(images);
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts products to DTOs with their gallery images.
+    async fn with_images<P: Into<ProductDto>>(
+        &self,
+        products: Vec<P>,
+    ) -> Result<Vec<ProductDto>, AppError> {
+        let mut product_dtos: Vec<ProductDto> = products.into_iter().map(Into::into).collect();
+        self.attach_images(product_dtos.iter_mut().collect())
+            .await?;
+        Ok(product_dtos)
+    }
+
+    /// Generates the variants of an uploaded image on a blocking thread and stores them.
+    /// If storing a variant fails, those stored so far are removed again.
+    async fn generate_image_variants(
+        &self,
+        file: &UploadedFile,
+    ) -> Result<NewProductImage, AppError> {
+        let data = self.storage.get_bytes(&file.key).await?.ok_or_else(|| {
+            tracing::error!("Uploaded image {} is missing from storage", file.key);
+            AppError::InternalError
+        })?;
+        let key = file.key.clone();
+        let limits = ImageLimits {
+            max_dimension: self.config.asset_max_image_dimension,
+            max_pixels: self.config.asset_max_image_pixels,
+        };
+        let generated = tokio::task::spawn_blocking(move || {
+            generate_variants(&key, &data, PRODUCT_IMAGE_VARIANTS, limits)
+        })
+        .await
+        .map_err(|err| {
+            tracing::error!("Image processing task failed: {err}");
+            AppError::InternalError
+        })??;
+
+        let mut keys = Vec::with_capacity(generated.variants.len());
+        for variant in generated.variants {
+            if let Err(err) = self
+                .storage
+                .put_bytes(&variant.key, variant.data, variant.content_type)
+                .await
+            {
+                let stored: Vec<&str> = keys.iter().map(String::as_str).collect();
+                self.remove_image_files(&stored).await;
+                return Err(err);
+            }
+            keys.push(variant.key);
+        }
+
+        let [thumbnail_key, card_key, detail_key, webp_key] =
+            <[String; 4]>::try_from(keys).map_err(|_| AppError::InternalError)?;
+        Ok(NewProductImage {
+            original_key: file.key.clone(),
+            thumbnail_key,
+            card_key,
+            detail_key,
+            webp_key,
+            width: generated.width as i32,
+            height: generated.height as i32,
+        })
+    }
+
+    /// Deletes stored image files, logging rather than failing on errors.
+    async fn remove_image_files(&self, keys: &[&str]) {
+        for key in keys {
+            if let Err(err) = self.storage.delete(key).await {
+                tracing::error!("Error removing product image {key}: {err}");
+            }
+        }
+    }
+
+    /// Reads back the changed gallery of the product and commits the transaction.
+    async fn commit_gallery(
+        &self,
+        mut tx: sqlx::Transaction<'_, sqlx::Postgres>,
+        id: i32,
+    ) -> Result<Vec<ProductImageDto>, AppError> {
+        match self.repo.find_images_of_product(&mut tx, id).await {
+            Ok(gallery) => {
+                tx.commit().await?;
+                Ok(self.image_dtos(gallery))
+            }
+            Err(err) => {
+                tx.rollback().await?;
+                Err(map_image_error(err))
+            }
+        }
+    }
+
+    /// Locks the product so concurrent changes to its gallery are applied one after another.
+    async fn lock_product(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        id: i32,
+    ) -> Result<(), AppError> {
+        match self.repo.find_by_id_for_update(tx, id).await {
+            Ok(Some(_)) => Ok(()),
+            Ok(None) => Err(AppError::NotFound("Product not found".into())),
+            Err(err) => {
+                tracing::error!("Error retrieving product: {err}");
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
+
+    async fn store_product_images(
+        &self,
+        id: i32,
+        files: &[UploadedFile],
+        images: &mut Vec<NewProductImage>,
+    ) -> Result<Vec<ProductImageDto>, AppError> {
+        if files.is_empty() {
+            return Err(AppError::ValidationError(
+                "No image files were uploaded".into(),
+            ));
+        }
+        if let Some(file) = files
+            .iter()
+            .find(|file| !file.content_type.starts_with("image/"))
+        {
+            return Err(AppError::ValidationError(format!(
+                "{} is not an image",
+                file.original_name
+            )));
+        }
+        // Checked up front as well, to skip generating variants for a missing product.
+        self.get_product_by_id(id).await?;
+
+        for file in files {
+            images.push(self.generate_image_variants(file).await?);
+        }
+
+        let mut tx = self.pool.begin().await?;
+        if let Err(err) = self.lock_product(&mut tx, id).await {
+            tx.rollback().await?;
+            return Err(err);
+        }
+        let existing = match self.repo.find_images_of_product(&mut tx, id).await {
+            Ok(existing) => existing,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(map_image_error(err));
+            }
+        };
+        if existing.len() + images.len() > MAX_PRODUCT_IMAGES {
+            tx.rollback().await?;
+            return Err(AppError::ValidationError(format!(
+                "A product can have at most {MAX_PRODUCT_IMAGES} images"
+            )));
+        }
+        if let Err(err) = self.repo.add_images(&mut tx, id, images.clone()).await {
+            tx.rollback().await?;
+            return Err(map_image_error(err));
+        }
+        self.commit_gallery(tx, id).await
+    }
 }
 
 #[async_trait]
 impl ProductServiceTrait for ProductService {
     /// constructor for the service.
-    fn create_service(pool: PgPool) -> Arc<dyn ProductServiceTrait> {
+    fn create_service(
+        pool: PgPool,
+        config: Config,
+        storage: Arc<dyn Storage>,
+    ) -> Arc<dyn ProductServiceTrait> {
         Arc::new(Self {
             pool,
             repo: Arc::new(ProductRepo {}),
+            config,
+            storage,
         })
     }
 
     async fn get_product_by_id(&self, id: i32) -> Result<ProductDto, AppError> {
         match self.repo.find_by_id(self.pool.clone(), id).await {
-            Ok(Some(product)) => Ok(ProductDto::from(product)),
+            Ok(Some(product)) => Ok(self.with_images(vec![product]).await?.remove(0)),
             Ok(None) => Err(AppError::NotFound("Product not found".into())),
             Err(err) => {
                 tracing::error!("Error retrieving product: {err}");
@@ -38,11 +307,13 @@ impl ProductServiceTrait for ProductService {
         }
     }
 
-    async fn get_products(&self) -> Result<Vec<ProductDto>, AppError> {
-        match self.repo.find_all(self.pool.clone()).await {
-            Ok(products) => {
-                let product_dtos: Vec<ProductDto> = products.into_iter().map(Into::into).collect();
-                Ok(product_dtos)
+    async fn get_products(&self, page: PageQuery) -> Result<Page<ProductDto>, AppError> {
+        let page = PageRequest::from_query(page, PRODUCT_SORT_FIELDS)?;
+        match self.repo.find_all(self.pool.clone(), &page).await {
+            Ok((products, total)) => {
+                let mut page: Page<ProductDto> = page.into_page(products, total);
+                self.attach_images(page.items.iter_mut().collect()).await?;
+                Ok(page)
             }
             Err(err) => {
                 tracing::error!("Error fetching products: {err}");
@@ -54,16 +325,14 @@ impl ProductServiceTrait for ProductService {
     async fn get_products_by_category_id(
         &self,
         category_id: i32,
+        include_descendants: bool,
     ) -> Result<Vec<ProductDto>, AppError> {
         match self
             .repo
-            .find_by_category_id(self.pool.clone(), category_id)
+            .find_by_category_id(self.pool.clone(), category_id, include_descendants)
             .await
         {
-            Ok(products) => {
-                let product_dtos: Vec<ProductDto> = products.into_iter().map(Into::into).collect();
-                Ok(product_dtos)
-            }
+            Ok(products) => self.with_images(products).await,
             Err(err) => {
                 tracing::error!("Error fetching products by category: {err}");
                 Err(AppError::DatabaseError(err))
@@ -73,10 +342,7 @@ impl ProductServiceTrait for ProductService {
 
     async fn get_best_sellers(&self, limit: i64) -> Result<Vec<ProductDto>, AppError> {
         match self.repo.find_best_sellers(self.pool.clone(), limit).await {
-            Ok(products) => {
-                let product_dtos: Vec<ProductDto> = products.into_iter().map(Into::into).collect();
-                Ok(product_dtos)
-            }
+            Ok(products) => self.with_images(products).await,
             Err(err) => {
                 tracing::error!("Error fetching best sellers: {err}");
                 Err(AppError::DatabaseError(err))
@@ -90,10 +356,7 @@ impl ProductServiceTrait for ProductService {
             .find_deals_of_the_day(self.pool.clone(), limit)
             .await
         {
-            Ok(products) => {
-                let product_dtos: Vec<ProductDto> = products.into_iter().map(Into::into).collect();
-                Ok(product_dtos)
-            }
+            Ok(products) => self.with_images(products).await,
             Err(err) => {
                 tracing::error!("Error fetching deals of the day: {err}");
                 Err(AppError::DatabaseError(err))
@@ -111,10 +374,7 @@ impl ProductServiceTrait for ProductService {
             .find_by_price_range(self.pool.clone(), min_price, max_price)
             .await
         {
-            Ok(products) => {
-                let product_dtos: Vec<ProductDto> = products.into_iter().map(Into::into).collect();
-                Ok(product_dtos)
-            }
+            Ok(products) => self.with_images(products).await,
             Err(err) => {
                 tracing::error!("Error fetching products by price range: {err}");
                 Err(AppError::DatabaseError(err))
@@ -124,32 +384,386 @@ impl ProductServiceTrait for ProductService {
 
     async fn get_products_by_filter(
         &self,
-        category: Option<String>,
-        is_best_seller: Option<bool>,
-        is_deal_of_the_day: Option<bool>,
-        min_price: Option<String>,
-        max_price: Option<String>,
-    ) -> Result<Vec<ProductDto>, AppError> {
+        filter: FilterQuery,
+        page: PageQuery,
+    ) -> Result<ProductFilterResultDto, AppError> {
+        let page = PageRequest::from_query(page, PRODUCT_SORT_FIELDS)?;
+        match self
+            .repo
+            .find_by_filter(self.pool.clone(), filter, &page)
+            .await
+        {
+            Ok((products, counts)) => {
+                let total = counts
+                    .iter()
+                    .find(|count| count.is_total())
+                    .map_or(0, |count| count.count);
+                let mut result = ProductFilterResultDto {
+                    page: page.into_page(products, total),
+                    facets: counts.into(),
+                };
+                self.attach_images(result.page.items.iter_mut().collect())
+                    .await?;
+                Ok(result)
+            }
+            Err(err) => {
+                tracing::error!("Error fetching products by filter: {err}");
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
+
+    async fn search_products(
+        &self,
+        query: SearchQuery,
+    ) -> Result<Vec<ProductSearchResultDto>, AppError> {
+        if !query.q.chars().any(char::is_alphanumeric) {
+            return Err(AppError::ValidationError(
+                "Search query must contain a letter or digit".into(),
+            ));
+        }
+
         match self
             .repo
-            .find_by_filter(
+            .search(
                 self.pool.clone(),
-                category,
-                is_best_seller,
-                is_deal_of_the_day,
-                min_price,
-                max_price,
+                &query.q,
+                query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+                query.offset.unwrap_or(0),
             )
             .await
         {
-            Ok(products) => {
-                let product_dtos: Vec<ProductDto> = products.into_iter().map(Into::into).collect();
-                Ok(product_dtos)
+            Ok(hits) => {
+                let mut results: Vec<ProductSearchResultDto> =
+                    hits.into_iter().map(Into::into).collect();
+                self.attach_images(results.iter_mut().map(|hit| &mut hit.product).collect())
+                    .await?;
+                Ok(results)
             }
             Err(err) => {
-                tracing::error!("Error fetching products by filter: {err}");
+                tracing::error!("Error searching products: {err}");
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
+
+    async fn get_low_stock_products(&self) -> Result<Vec<ProductStockDto>, AppError> {
+        match self.repo.find_low_stock(self.pool.clone()).await {
+            Ok(products) => Ok(products.into_iter().map(Into::into).collect()),
+            Err(err) => {
+                tracing::error!("Error fetching low stock products: {err}");
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
+
+    async fn restock_product(
+        &self,
+        id: i32,
+        payload: RestockDto,
+    ) -> Result<ProductStockDto, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        match self.repo.restock(&mut tx, id, payload.quantity).await {
+            Ok(Some(product)) => {
+                tx.commit().await?;
+                Ok(ProductStockDto::from(product))
+            }
+            Ok(None) => {
+                tx.rollback().await?;
+                Err(AppError::NotFound("Product not found".into()))
+            }
+            Err(err) => {
+                tracing::error!("Error restocking product: {err}");
+                tx.rollback().await?;
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
+
+    /// Overwrites the stock quantity of a product.
+    /// The new quantity may not drop below what open orders have already reserved.
+    async fn update_product_stock(
+        &self,
+        id: i32,
+        payload: UpdateStockDto,
+    ) -> Result<ProductStockDto, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let product = match self.repo.find_by_id_for_update(&mut tx, id).await {
+            Ok(Some(product)) => product,
+            Ok(None) => {
+                tx.rollback().await?;
+                return Err(AppError::NotFound("Product not found".into()));
+            }
+            Err(err) => {
+                tracing::error!("Error retrieving product: {err}");
+                tx.rollback().await?;
+                return Err(AppError::DatabaseError(err));
+            }
+        };
+
+        if payload.stock_quantity < product.reserved_quantity {
+            tx.rollback().await?;
+            return Err(AppError::ValidationError(format!(
+                "Stock quantity cannot be lower than the reserved quantity ({})",
+                product.reserved_quantity
+            )));
+        }
+
+        let low_stock_threshold = payload
+            .low_stock_threshold
+            .unwrap_or(product.low_stock_threshold);
+
+        match self
+            .repo
+            .update_stock(&mut tx, id, payload.stock_quantity, low_stock_threshold)
+            .await
+        {
+            Ok(product) => {
+                tx.commit().await?;
+                Ok(ProductStockDto::from(product))
+            }
+            Err(err) => {
+                tracing::error!("Error updating product stock: {err}");
+                tx.rollback().await?;
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
+
+    async fn create_product(&self, payload: CreateProductDto) -> Result<ProductDto, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        match self.repo.create(&mut tx, payload).await {
+            Ok(product) => {
+                tx.commit().await?;
+                Ok(ProductDto::from(product))
+            }
+            Err(err) => {
+                tx.rollback().await?;
+                Err(map_write_error(err))
+            }
+        }
+    }
+
+    async fn update_product(
+        &self,
+        id: i32,
+        payload: UpdateProductDto,
+    ) -> Result<ProductDto, AppError> {
+        self.patch_product(id, payload.into()).await
+    }
+
+    async fn patch_product(
+        &self,
+        id: i32,
+        payload: PatchProductDto,
+    ) -> Result<ProductDto, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        match self.repo.update(&mut tx, id, payload).await {
+            Ok(Some(product)) => {
+                tx.commit().await?;
+                Ok(self.with_images(vec![product]).await?.remove(0))
+            }
+            Ok(None) => {
+                tx.rollback().await?;
+                Err(AppError::NotFound("Product not found".into()))
+            }
+            Err(err) => {
+                tx.rollback().await?;
+                Err(map_write_error(err))
+            }
+        }
+    }
+
+    async fn bulk_patch_products(
+        &self,
+        payload: BulkPatchProductDto,
+    ) -> Result<Vec<ProductDto>, AppError> {
+        let mut tx = self.pool.begin().await?;
+        let mut products = Vec::with_capacity(payload.items.len());
+
+        for item in payload.items {
+            match self.repo.update(&mut tx, item.id, item.changes).await {
+                Ok(Some(product)) => products.push(product),
+                Ok(None) => {
+                    tx.rollback().await?;
+                    return Err(AppError::NotFound(format!("Product {} not found", item.id)));
+                }
+                Err(err) => {
+                    tx.rollback().await?;
+                    return Err(map_write_error(err));
+                }
+            }
+        }
+
+        tx.commit().await?;
+        self.with_images(products).await
+    }
+
+    /// Deletes a product.
+    /// Products with reserved stock are kept so open orders can still be fulfilled.
+    async fn delete_product(&self, id: i32) -> Result<String, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let product = match self.repo.find_by_id_for_update(&mut tx, id).await {
+            Ok(Some(product)) => product,
+            Ok(None) => {
+                tx.rollback().await?;
+                return Err(AppError::NotFound("Product not found".into()));
+            }
+            Err(err) => {
+                tracing::error!("Error retrieving product: {err}");
+                tx.rollback().await?;
+                return Err(AppError::DatabaseError(err));
+            }
+        };
+
+        if product.reserved_quantity > 0 {
+            tx.rollback().await?;
+            return Err(AppError::Conflict(
+                "Product has stock reserved by open orders".into(),
+            ));
+        }
+
+        let images = match self.repo.find_images_of_product(&mut tx, id).await {
+            Ok(images) => images,
+            Err(err) => {
+                tracing::error!("Error fetching product images: {err}");
+                tx.rollback().await?;
+                return Err(AppError::DatabaseError(err));
+            }
+        };
+
+        match self.repo.delete(&mut tx, id).await {
+            Ok(()) => {
+                tx.commit().await?;
+                for image in &images {
+                    self.remove_image_files(&image.keys()).await;
+                }
+                Ok("Product deleted".into())
+            }
+            Err(err) => {
+                tracing::error!("Error deleting product: {err}");
+                tx.rollback().await?;
                 Err(AppError::DatabaseError(err))
             }
         }
     }
+
+    async fn get_product_images(&self, id: i32) -> Result<Vec<ProductImageDto>, AppError> {
+        Ok(self.get_product_by_id(id).await?.images)
+    }
+
+    async fn add_product_images(
+        &self,
+        id: i32,
+        files: Vec<UploadedFile>,
+    ) -> Result<Vec<ProductImageDto>, AppError> {
+        let mut images = Vec::with_capacity(files.len());
+        let result = self.store_product_images(id, &files, &mut images).await;
+        if result.is_err() {
+            remove_files(self.storage.as_ref(), &files).await;
+            for image in &images {
+                self.remove_image_files(&image.keys()[1..]).await;
+            }
+        }
+        result
+    }
+
+    async fn reorder_product_images(
+        &self,
+        id: i32,
+        payload: ReorderProductImagesDto,
+    ) -> Result<Vec<ProductImageDto>, AppError> {
+        let mut tx = self.pool.begin().await?;
+        if let Err(err) = self.lock_product(&mut tx, id).await {
+            tx.rollback().await?;
+            return Err(err);
+        }
+
+        let images = match self.repo.find_images_of_product(&mut tx, id).await {
+            Ok(images) => images,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(map_image_error(err));
+            }
+        };
+        let mut current: Vec<i32> = images.iter().map(|image| image.id).collect();
+        let mut requested = payload.image_ids.clone();
+        current.sort_unstable();
+        requested.sort_unstable();
+        if current != requested {
+            tx.rollback().await?;
+            return Err(AppError::ValidationError(
+                "image_ids must list every image of the product exactly once".into(),
+            ));
+        }
+
+        if let Err(err) = self
+            .repo
+            .reorder_images(&mut tx, id, &payload.image_ids)
+            .await
+        {
+            tx.rollback().await?;
+            return Err(map_image_error(err));
+        }
+        self.commit_gallery(tx, id).await
+    }
+
+    async fn set_primary_product_image(
+        &self,
+        id: i32,
+        image_id: i32,
+    ) -> Result<Vec<ProductImageDto>, AppError> {
+        let mut tx = self.pool.begin().await?;
+        if let Err(err) = self.lock_product(&mut tx, id).await {
+            tx.rollback().await?;
+            return Err(err);
+        }
+
+        let images = match self.repo.find_images_of_product(&mut tx, id).await {
+            Ok(images) => images,
+            Err(err) => {
+                tx.rollback().await?;
+                return Err(map_image_error(err));
+            }
+        };
+        if !images.iter().any(|image| image.id == image_id) {
+            tx.rollback().await?;
+            return Err(AppError::NotFound("Image not found".into()));
+        }
+
+        if let Err(err) = self.repo.set_primary_image(&mut tx, id, image_id).await {
+            tx.rollback().await?;
+            return Err(map_image_error(err));
+        }
+        self.commit_gallery(tx, id).await
+    }
+
+    async fn delete_product_image(&self, id: i32, image_id: i32) -> Result<String, AppError> {
+        let mut tx = self.pool.begin().await?;
+        if let Err(err) = self.lock_product(&mut tx, id).await {
+            tx.rollback().await?;
+            return Err(err);
+        }
+
+        match self.repo.delete_image(&mut tx, id, image_id).await {
+            Ok(Some(image)) => {
+                tx.commit().await?;
+                self.remove_image_files(&image.keys()).await;
+                Ok("Image deleted".into())
+            }
+            Ok(None) => {
+                tx.rollback().await?;
+                Err(AppError::NotFound("Image not found".into()))
+            }
+            Err(err) => {
+                tx.rollback().await?;
+                Err(map_image_error(err))
+            }
+        }
+    }
 }
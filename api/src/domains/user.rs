@@ -19,8 +19,6 @@ mod infra {
 }
 
 // Re-export commonly used items for convenience
-pub use api::routes::{
-    user_private_routes, user_public_routes, UserPrivateApiDoc, UserPublicApiDoc,
-};
+pub use api::routes::{user_private_routes, UserPrivateApiDoc};
 pub use domain::service::UserServiceTrait;
 pub use infra::impl_service::UserService;
@@ -1,13 +1,14 @@
 use crate::{
     common::{
-        app_state::AppState, dto::RestApiResponse, error::AppError,
-        multipart_helper::parse_multipart_to_maps,
+        app_state::AppState,
+        dto::{Page, PageQuery, RestApiResponse},
+        error::AppError,
     },
-    domains::user::dto::user_dto::{CreateUserMultipartDto, SearchUserDto, UpdateUserDto, UserDto},
+    domains::user::dto::user_dto::{SearchUserDto, UpdateUserDto, UpdateUserRoleDto, UserDto},
 };
 
 use axum::{
-    extract::{Multipart, Path, State},
+    extract::{Path, Query, State},
     response::IntoResponse,
     Json,
 };
@@ -46,51 +47,16 @@ pub async fn get_user_list(
 #[utoipa::path(
     get,
     path = "/user",
-    responses((status = 200, description = "List all users", body = [UserDto])),
+    params(PageQuery),
+    responses((status = 200, description = "List users, sortable by id or username", body = Page<UserDto>)),
     tag = "Users"
 )]
-pub async fn get_users(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
-    let users = state.user_service.get_users().await?;
-    Ok(RestApiResponse::success(users))
-}
-
-#[utoipa::path(
-    post,
-    path = "/user",
-    request_body(
-        content = CreateUserMultipartDto,
-        content_type = "multipart/form-data",
-        description = "User creation"
-    ),
-    responses((status = 200, description = "Create a new user", body = UserDto)),
-    tag = "Users (Public)"
-)]
-pub async fn create_user(
+pub async fn get_users(
     State(state): State<AppState>,
-    multipart: Multipart,
+    Query(page): Query<PageQuery>,
 ) -> Result<impl IntoResponse, AppError> {
-    let mut fields =
-        parse_multipart_to_maps(multipart, &state.config.asset_allowed_extensions_pattern).await?;
-
-    // Validate required fields.
-    let username = fields
-        .remove("username")
-        .ok_or(AppError::ValidationError("Missing username".into()))?;
-    let email = fields
-        .remove("email")
-        .ok_or(AppError::ValidationError("Missing email".into()))?;
-
-    // Prepare the CreateUser DTO.
-    let create_user = CreateUserMultipartDto { username, email };
-
-    // Validate the CreateUser DTO.
-    create_user
-        .validate()
-        .map_err(|err| AppError::ValidationError(format!("Invalid input: {}", err)))?;
-
-    let user = state.user_service.create_user(create_user).await?;
-
-    Ok(RestApiResponse::success(user))
+    let users = state.user_service.get_users(page).await?;
+    Ok(RestApiResponse::success(users))
 }
 
 #[utoipa::path(
@@ -127,3 +93,19 @@ pub async fn delete_user(
     let message = state.user_service.delete_user(id).await?;
     Ok(RestApiResponse::success_with_message(message, ()))
 }
+
+#[utoipa::path(
+    put,
+    path = "/user/{id}/role",
+    request_body = UpdateUserRoleDto,
+    responses((status = 200, description = "Change the role of a user", body = UserDto)),
+    tag = "Users"
+)]
+pub async fn update_user_role(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateUserRoleDto>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = state.user_service.update_user_role(id, payload).await?;
+    Ok(RestApiResponse::success(user))
+}
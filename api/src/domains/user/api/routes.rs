@@ -1,11 +1,12 @@
 use super::handlers::*;
 use crate::{
-    common::app_state::AppState,
-    domains::user::dto::user_dto::{CreateUserMultipartDto, SearchUserDto, UpdateUserDto, UserDto},
+    common::{app_state::AppState, authz},
+    domains::user::dto::user_dto::{SearchUserDto, UpdateUserDto, UpdateUserRoleDto, UserDto},
 };
 
 use axum::{
-    routing::{delete, get, post, put},
+    middleware,
+    routing::{get, post, put},
     Router,
 };
 
@@ -14,16 +15,6 @@ use utoipa::{
     OpenApi,
 };
 
-#[derive(OpenApi)]
-#[openapi(
-    paths(create_user),
-    components(schemas(CreateUserMultipartDto)),
-    tags(
-        (name = "Users (Public)", description = "Public user registration endpoint")
-    )
-)]
-pub struct UserPublicApiDoc;
-
 #[derive(OpenApi)]
 #[openapi(
     paths(
@@ -32,8 +23,14 @@ pub struct UserPublicApiDoc;
         get_user_list,
         update_user,
         delete_user,
+        update_user_role,
     ),
-    components(schemas(UserDto, SearchUserDto, CreateUserMultipartDto, UpdateUserDto)),
+    components(schemas(
+        UserDto,
+        SearchUserDto,
+        UpdateUserDto,
+        UpdateUserRoleDto
+    )),
     tags(
         (name = "Users", description = "User management endpoints")
     ),
@@ -62,14 +59,41 @@ impl utoipa::Modify for UserPrivateApiDoc {
 }
 
 pub fn user_private_routes() -> Router<AppState> {
-    Router::new()
+    // Browsing accounts is open to staff.
+    let staff_routes = Router::new()
         .route("/", get(get_users))
         .route("/list", post(get_user_list))
+        .route_layer(middleware::from_fn_with_state(
+            authz::STAFF,
+            authz::require_roles,
+        ));
+
+    // Users can read their own account; staff can read any.
+    let read_routes = Router::new()
         .route("/{id}", get(get_user_by_id))
-        .route("/{id}", put(update_user))
-        .route("/{id}", delete(delete_user))
-}
+        .route_layer(middleware::from_fn_with_state(
+            authz::STAFF,
+            authz::require_self_or_roles,
+        ));
+
+    // Users can change or delete their own account; admins can change any.
+    let write_routes = Router::new()
+        .route("/{id}", put(update_user).delete(delete_user))
+        .route_layer(middleware::from_fn_with_state(
+            authz::ADMIN,
+            authz::require_self_or_roles,
+        ));
 
-pub fn user_public_routes() -> Router<AppState> {
-    Router::new().route("/", post(create_user))
+    let admin_routes = Router::new()
+        .route("/{id}/role", put(update_user_role))
+        .route_layer(middleware::from_fn_with_state(
+            authz::ADMIN,
+            authz::require_roles,
+        ));
+
+    Router::new()
+        .merge(staff_routes)
+        .merge(read_routes)
+        .merge(write_routes)
+        .merge(admin_routes)
 }
@@ -1,8 +1,26 @@
 use sqlx::FromRow;
+
+use crate::common::{authz::Role, pagination::Keyset};
+
 /// Domain model representing a user in the application.
 #[derive(Debug, Clone, FromRow)]
 pub struct User {
     pub id: i32,
     pub username: String,
     pub email: Option<String>,
+    pub role: Role,
+    pub email_verified: bool,
+}
+
+impl Keyset for User {
+    fn keyset_id(&self) -> i32 {
+        self.id
+    }
+
+    fn keyset_value(&self, sort: &str) -> String {
+        match sort {
+            "username" => self.username.clone(),
+            _ => self.id.to_string(),
+        }
+    }
 }
@@ -1,7 +1,10 @@
 //! This module defines the `UserRepository` trait, which abstracts
 //! the database operations related to user entities.
 
-use crate::domains::user::dto::user_dto::{CreateUserMultipartDto, SearchUserDto, UpdateUserDto};
+use crate::{
+    common::{authz::Role, pagination::PageRequest},
+    domains::user::dto::user_dto::{SearchUserDto, UpdateUserDto},
+};
 
 use super::model::User;
 
@@ -12,8 +15,12 @@ use sqlx::{PgPool, Postgres, Transaction};
 /// Trait representing repository-level operations for user entities.
 /// Provides methods for creating, retrieving, updating, and deleting users in the database.
 pub trait UserRepository: Send + Sync {
-    /// Retrieves all users from the database.
-    async fn find_all(&self, pool: PgPool) -> Result<Vec<User>, sqlx::Error>;
+    /// Retrieves a page of users together with the total number of users.
+    async fn find_all(
+        &self,
+        pool: PgPool,
+        page: &PageRequest,
+    ) -> Result<(Vec<User>, i64), sqlx::Error>;
 
     /// Finds a user by their unique identifier.
     async fn find_by_id(&self, pool: PgPool, id: String) -> Result<Option<User>, sqlx::Error>;
@@ -25,13 +32,6 @@ pub trait UserRepository: Send + Sync {
         search_user_dto: SearchUserDto,
     ) -> Result<Vec<User>, sqlx::Error>;
 
-    /// Creates a new user record using the provided data within an active transaction.
-    async fn create(
-        &self,
-        tx: &mut Transaction<'_, Postgres>,
-        user: CreateUserMultipartDto,
-    ) -> Result<String, sqlx::Error>;
-
     /// Updates an existing user record using the provided data.
     async fn update(
         &self,
@@ -46,4 +46,12 @@ pub trait UserRepository: Send + Sync {
         tx: &mut Transaction<'_, Postgres>,
         id: String,
     ) -> Result<bool, sqlx::Error>;
+
+    /// Sets the role of a user. Returns `Ok(None)` if the user does not exist.
+    async fn update_role(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        role: Role,
+    ) -> Result<Option<User>, sqlx::Error>;
 }
@@ -2,8 +2,11 @@
 //! It abstracts operations such as user creation, retrieval, update, and deletion.
 
 use crate::{
-    common::error::AppError,
-    domains::user::dto::user_dto::{CreateUserMultipartDto, SearchUserDto, UpdateUserDto, UserDto},
+    common::{
+        dto::{Page, PageQuery},
+        error::AppError,
+    },
+    domains::user::dto::user_dto::{SearchUserDto, UpdateUserDto, UpdateUserRoleDto, UserDto},
 };
 
 use async_trait::async_trait;
@@ -26,15 +29,19 @@ pub trait UserServiceTrait: Send + Sync {
     async fn get_user_list(&self, search_user_dto: SearchUserDto)
         -> Result<Vec<UserDto>, AppError>;
 
-    /// Retrieves all users.
-    async fn get_users(&self) -> Result<Vec<UserDto>, AppError>;
-
-    /// Creates a new user.
-    async fn create_user(&self, create_user: CreateUserMultipartDto) -> Result<UserDto, AppError>;
+    /// Retrieves a page of users.
+    async fn get_users(&self, page: PageQuery) -> Result<Page<UserDto>, AppError>;
 
     /// Updates an existing user with the given payload.
     async fn update_user(&self, id: String, payload: UpdateUserDto) -> Result<UserDto, AppError>;
 
     /// Deletes a user by their unique identifier.
     async fn delete_user(&self, id: String) -> Result<String, AppError>;
+
+    /// Changes the role of a user.
+    async fn update_user_role(
+        &self,
+        id: String,
+        payload: UpdateUserRoleDto,
+    ) -> Result<UserDto, AppError>;
 }
@@ -2,13 +2,15 @@ use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use validator::Validate;
 
-use crate::domains::user::domain::model::User;
+use crate::{common::authz::Role, domains::user::domain::model::User};
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UserDto {
     pub id: i32,
     pub username: String,
     pub email: Option<String>,
+    pub role: Role,
+    pub email_verified: bool,
 }
 
 impl From<User> for UserDto {
@@ -17,6 +19,8 @@ impl From<User> for UserDto {
             id: user.id,
             username: user.username,
             email: user.email,
+            role: user.role,
+            email_verified: user.email_verified,
         }
     }
 }
@@ -28,18 +32,20 @@ pub struct SearchUserDto {
     pub email: Option<String>,
 }
 
+/// Request body for updating a user.
+/// Changing the email marks it as unverified until the new address is confirmed.
 #[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
-pub struct CreateUserMultipartDto {
+pub struct UpdateUserDto {
     #[validate(length(max = 64, message = "Username cannot exceed 64 characters"))]
     pub username: String,
     #[validate(email(message = "Invalid email format"))]
     pub email: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
-pub struct UpdateUserDto {
-    #[validate(length(max = 64, message = "Username cannot exceed 64 characters"))]
-    pub username: String,
-    #[validate(email(message = "Invalid email format"))]
-    pub email: String,
+/// Request body for changing a user's role.
+/// The new role is carried in tokens issued from the user's next login.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UpdateUserRoleDto {
+    #[schema(example = "staff")]
+    pub role: Role,
 }
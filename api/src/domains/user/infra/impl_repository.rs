@@ -1,17 +1,37 @@
+use crate::common::{
+    authz::Role,
+    pagination::{PageRequest, SortField, SortType},
+};
 use crate::domains::user::{
     domain::{model::User, repository::UserRepository},
-    dto::user_dto::{CreateUserMultipartDto, SearchUserDto, UpdateUserDto},
+    dto::user_dto::{SearchUserDto, UpdateUserDto},
 };
 use async_trait::async_trait;
 use sqlx::{PgPool, Postgres, QueryBuilder, Transaction};
 
 pub struct UserRepo;
 
+/// Fields user lists can be sorted by; the first one is the default.
+pub const USER_SORT_FIELDS: &[SortField] = &[
+    SortField {
+        name: "id",
+        column: "u.id",
+        sort_type: SortType::Integer,
+    },
+    SortField {
+        name: "username",
+        column: "u.username",
+        sort_type: SortType::Text,
+    },
+];
+
 const FIND_USER_QUERY: &str = r#"
     SELECT
         u.id,
         u.username,
-        u.email
+        u.email,
+        u.role,
+        u.email_verified_at IS NOT NULL AS email_verified
     FROM users u
     WHERE 1=1
 "#;
@@ -20,18 +40,30 @@ const FIND_USER_INFO_QUERY: &str = r#"
     SELECT
         u.id,
         u.username,
-        u.email
+        u.email,
+        u.role,
+        u.email_verified_at IS NOT NULL AS email_verified
     FROM users u
     WHERE u.id = $1
 "#;
 
 #[async_trait]
 impl UserRepository for UserRepo {
-    async fn find_all(&self, pool: PgPool) -> Result<Vec<User>, sqlx::Error> {
-        let users = sqlx::query_as::<_, User>(FIND_USER_QUERY)
-            .fetch_all(&pool)
+    async fn find_all(
+        &self,
+        pool: PgPool,
+        page: &PageRequest,
+    ) -> Result<(Vec<User>, i64), sqlx::Error> {
+        let mut builder = QueryBuilder::<Postgres>::new(FIND_USER_QUERY);
+        page.push_after(&mut builder, "u.id");
+        page.push_order_and_limit(&mut builder, "u.id");
+        let users = builder.build_query_as::<User>().fetch_all(&pool).await?;
+
+        let total = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM users"#)
+            .fetch_one(&pool)
             .await?;
-        Ok(users)
+
+        Ok((users, total))
     }
 
     async fn find_list(
@@ -77,26 +109,6 @@ impl UserRepository for UserRepo {
         Ok(user)
     }
 
-    async fn create(
-        &self,
-        tx: &mut Transaction<'_, Postgres>,
-        user: CreateUserMultipartDto,
-    ) -> Result<String, sqlx::Error> {
-        let inserted = sqlx::query!(
-            r#"
-                INSERT INTO users (username, email)
-                VALUES ($1, $2)
-                RETURNING id
-            "#,
-            user.username,
-            Some(user.email)
-        )
-        .fetch_one(&mut **tx)
-        .await?;
-
-        Ok(inserted.id.to_string())
-    }
-
     async fn update(
         &self,
         tx: &mut Transaction<'_, Postgres>,
@@ -115,7 +127,11 @@ impl UserRepository for UserRepo {
                 r#"
                     UPDATE users
                     SET username = $1,
-                        email = $2
+                        email = $2,
+                        email_verified_at = CASE
+                            WHEN email IS DISTINCT FROM $2::VARCHAR THEN NULL
+                            ELSE email_verified_at
+                        END
                     WHERE id = $3
                 "#,
                 user.username,
@@ -142,13 +158,34 @@ impl UserRepository for UserRepo {
         id: String,
     ) -> Result<bool, sqlx::Error> {
         let user_id = id.parse::<i32>().unwrap_or_default();
-        let res = sqlx::query!(
-            r#"DELETE FROM users WHERE id = $1"#,
-            user_id
+        let res = sqlx::query!(r#"DELETE FROM users WHERE id = $1"#, user_id)
+            .execute(&mut **tx)
+            .await?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn update_role(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: i32,
+        role: Role,
+    ) -> Result<Option<User>, sqlx::Error> {
+        let user = sqlx::query_as!(
+            User,
+            r#"
+                UPDATE users
+                SET role = $2
+                WHERE id = $1
+                RETURNING id, username, email, role as "role: Role",
+                          email_verified_at IS NOT NULL as "email_verified!"
+            "#,
+            id,
+            role as Role
         )
-        .execute(&mut **tx)
+        .fetch_optional(&mut **tx)
         .await?;
 
-        Ok(res.rows_affected() > 0)
+        Ok(user)
     }
-}
\ No newline at end of file
+}
@@ -1,9 +1,13 @@
 use crate::{
-    common::error::AppError,
+    common::{
+        dto::{Page, PageQuery},
+        error::AppError,
+        pagination::PageRequest,
+    },
     domains::user::{
         domain::{repository::UserRepository, service::UserServiceTrait},
-        dto::user_dto::{CreateUserMultipartDto, SearchUserDto, UpdateUserDto, UserDto},
-        infra::impl_repository::UserRepo,
+        dto::user_dto::{SearchUserDto, UpdateUserDto, UpdateUserRoleDto, UserDto},
+        infra::impl_repository::{UserRepo, USER_SORT_FIELDS},
     },
 };
 use async_trait::async_trait;
@@ -63,14 +67,12 @@ impl UserServiceTrait for UserService {
         }
     }
 
-    /// Retrieves all users.
-    /// Returns a vector of UserDto objects.
-    async fn get_users(&self) -> Result<Vec<UserDto>, AppError> {
-        match self.repo.find_all(self.pool.clone()).await {
-            Ok(users) => {
-                let user_dtos: Vec<UserDto> = users.into_iter().map(Into::into).collect();
-                Ok(user_dtos)
-            }
+    /// Retrieves a page of users.
+    /// Returns the UserDto objects of the page with the total number of users.
+    async fn get_users(&self, page: PageQuery) -> Result<Page<UserDto>, AppError> {
+        let page = PageRequest::from_query(page, USER_SORT_FIELDS)?;
+        match self.repo.find_all(self.pool.clone(), &page).await {
+            Ok((users, total)) => Ok(page.into_page(users, total)),
             Err(err) => {
                 tracing::error!("Error fetching users: {err}");
                 Err(AppError::DatabaseError(err))
@@ -78,32 +80,6 @@ impl UserServiceTrait for UserService {
         }
     }
 
-    /// Creates a new user.
-    /// Takes a CreateUserMultipartDto object.
-    async fn create_user(&self, create_user: CreateUserMultipartDto) -> Result<UserDto, AppError> {
-        let mut tx = self.pool.begin().await?;
-
-        let user_id = match self.repo.create(&mut tx, create_user).await {
-            Ok(user_id) => user_id,
-            Err(err) => {
-                tracing::error!("Error creating user: {err}");
-                tx.rollback().await?;
-                return Err(AppError::DatabaseError(err));
-            }
-        };
-
-        tx.commit().await?;
-
-        match self.repo.find_by_id(self.pool.clone(), user_id).await {
-            Ok(Some(user)) => Ok(UserDto::from(user)),
-            Ok(None) => Err(AppError::NotFound("User not found".into())),
-            Err(err) => {
-                tracing::error!("Error retrieving user: {err}");
-                Err(AppError::DatabaseError(err))
-            }
-        }
-    }
-
     /// Updates an existing user.
     async fn update_user(&self, id: String, payload: UpdateUserDto) -> Result<UserDto, AppError> {
         let mut tx = self.pool.begin().await?;
@@ -145,4 +121,33 @@ impl UserServiceTrait for UserService {
             }
         }
     }
+
+    /// Changes the role of a user.
+    async fn update_user_role(
+        &self,
+        id: String,
+        payload: UpdateUserRoleDto,
+    ) -> Result<UserDto, AppError> {
+        let user_id: i32 = id
+            .parse()
+            .map_err(|_| AppError::ValidationError("Invalid user id".into()))?;
+
+        let mut tx = self.pool.begin().await?;
+
+        match self.repo.update_role(&mut tx, user_id, payload.role).await {
+            Ok(Some(user)) => {
+                tx.commit().await?;
+                Ok(UserDto::from(user))
+            }
+            Ok(None) => {
+                tx.rollback().await?;
+                Err(AppError::NotFound("User not found".into()))
+            }
+            Err(err) => {
+                tracing::error!("Error updating user role: {err}");
+                tx.rollback().await?;
+                Err(AppError::DatabaseError(err))
+            }
+        }
+    }
 }
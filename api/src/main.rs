@@ -1,8 +1,12 @@
-use foodzy_api::{app::create_router, common};
 use common::{
     bootstrap::{build_app_state, shutdown_signal},
     config::{setup_database, Config},
+    jwt::JwtKeys,
+    oidc::OidcClient,
+    signed_url::UrlSigner,
 };
+use foodzy_api::{app::create_router, common};
+use std::{net::SocketAddr, sync::Arc};
 use tracing::info;
 
 /// Main entry point for the application.
@@ -16,8 +20,11 @@ use tracing::info;
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let config = Config::from_env()?;
+    let jwt_keys = Arc::new(JwtKeys::from_config(&config)?);
+    let oidc = OidcClient::from_config(&config)?.map(Arc::new);
+    let url_signer = Arc::new(UrlSigner::from_config(&config)?);
     let pool = setup_database(&config).await?;
-    let state = build_app_state(pool, config.clone());
+    let state = build_app_state(pool, config.clone(), jwt_keys, oidc, url_signer);
     let app = create_router(state);
 
     let addr = format!("{}:{}", config.service_host, config.service_port);
@@ -26,9 +33,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await?;
 
     Ok(())
 }